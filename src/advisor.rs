@@ -0,0 +1,396 @@
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use hdf5::types::TypeDescriptor;
+use hdf5_metno::{self as hdf5, Dataset};
+
+use crate::chunk_map;
+use crate::filters::{self, PipelineFilter};
+use crate::h5_utils;
+use crate::hexdump::HexDump;
+use crate::num_utils;
+
+/// Below this each chunk costs more in index entries and per chunk overhead than it holds.
+const TINY_CHUNK_BYTES: usize = 10 * 1024;
+
+/// `H5D_CHUNK_CACHE_NBYTES_DEFAULT`, chunks larger than this are read again on every access.
+const CHUNK_CACHE_BYTES: usize = 1024 * 1024;
+
+/// Size suggested for chunks, well inside the default chunk cache.
+const TARGET_CHUNK_BYTES: usize = 256 * 1024;
+
+/// Smaller datasets gain too little from compression to bother.
+const MIN_COMPRESSIBLE_BYTES: u64 = 256 * 1024;
+
+/// Estimated compression ratio from which uncompressed data is flagged.
+const HIGHLY_COMPRESSIBLE_RATIO: f64 = 2.0;
+
+/// A problem with how a dataset is stored and what to do about it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advice {
+    pub problem: String,
+    pub suggestion: String,
+}
+
+impl fmt::Display for Advice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "⚠ {}\n  → {}", self.problem, self.suggestion)
+    }
+}
+
+/// What the checks look at, gathered from the dataset.
+#[derive(Debug, Clone, Default)]
+struct StorageLayout {
+    shape: Vec<usize>,
+    element_size: usize,
+    chunk: Option<Vec<usize>>,
+    filters: Vec<PipelineFilter>,
+    num_allocated_chunks: Option<usize>,
+    storage_size: u64,
+    /// Shannon entropy of the first bytes of the data, 8 for random bytes.
+    bits_per_byte: Option<f64>,
+}
+
+/// Shannon entropy of `bytes`, in bits per byte.
+fn entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / bytes.len() as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn chunk_bytes(chunk: &[usize], element_size: usize) -> usize {
+    chunk.iter().product::<usize>() * element_size
+}
+
+/// `chunk` doubled (last dimension first) or halved (first dimension first) until it is
+/// around `target` bytes, without growing past `shape`.
+fn resized_chunk(
+    chunk: &[usize],
+    shape: &[usize],
+    element_size: usize,
+    target: usize,
+) -> Vec<usize> {
+    let mut chunk = chunk.to_vec();
+    let mut changed = true;
+    while changed && chunk_bytes(&chunk, element_size) < target {
+        changed = false;
+        for i in (0..chunk.len()).rev() {
+            let grown = (chunk[i] * 2).min(shape[i].max(chunk[i]));
+            if grown != chunk[i] && chunk_bytes(&chunk, element_size) < target {
+                chunk[i] = grown;
+                changed = true;
+            }
+        }
+    }
+    changed = true;
+    while changed && chunk_bytes(&chunk, element_size) > target {
+        changed = false;
+        for i in 0..chunk.len() {
+            if chunk[i] > 1 && chunk_bytes(&chunk, element_size) > target {
+                chunk[i] = chunk[i].div_ceil(2);
+                changed = true;
+            }
+        }
+    }
+    chunk
+}
+
+fn check(layout: &StorageLayout) -> Vec<Advice> {
+    let mut advice = vec![];
+    let data_size = (layout.shape.iter().product::<usize>() * layout.element_size) as u64;
+
+    if let Some(chunk) = &layout.chunk {
+        let bytes = chunk_bytes(chunk, layout.element_size);
+        let num_chunks: usize = chunk_map::grid_shape(&layout.shape, chunk).iter().product();
+        let suggested = |target| {
+            let resized = resized_chunk(chunk, &layout.shape, layout.element_size, target);
+            format!(
+                "{:?} ({})",
+                resized,
+                num_utils::file_size_fmt(chunk_bytes(&resized, layout.element_size) as u64)
+            )
+        };
+
+        if bytes < TINY_CHUNK_BYTES && num_chunks > 1 {
+            advice.push(Advice {
+                problem: format!(
+                    "Chunks of {} are tiny, the {} chunks cost more in index and overhead than they hold",
+                    num_utils::file_size_fmt(bytes as u64),
+                    num_utils::format_integer_with_separator(num_chunks as u64)
+                ),
+                suggestion: format!(
+                    "Use chunks of 10 kB to 1 MB, e.g. {}",
+                    suggested(TARGET_CHUNK_BYTES)
+                ),
+            });
+        }
+        if bytes > CHUNK_CACHE_BYTES {
+            advice.push(Advice {
+                problem: format!(
+                    "Chunks of {} don't fit in the default 1 MiB chunk cache, every partial read decodes a whole chunk again",
+                    num_utils::file_size_fmt(bytes as u64)
+                ),
+                suggestion: format!(
+                    "Use smaller chunks, e.g. {}, or raise the cache size when opening the dataset (H5Pset_chunk_cache)",
+                    suggested(TARGET_CHUNK_BYTES)
+                ),
+            });
+        }
+
+        // Reading `ds[i]` goes along the trailing dimensions, chunks one element wide there split every read
+        let narrow: Vec<usize> = (1..chunk.len())
+            .filter(|&i| chunk[i] == 1 && layout.shape[i] > 1)
+            .collect();
+        if !narrow.is_empty() && chunk[..narrow[0]].iter().any(|&len| len > 1) {
+            let mut widened = chunk.clone();
+            for &i in &narrow {
+                widened[i] = layout.shape[i];
+            }
+            let touched: usize = narrow.iter().map(|&i| layout.shape[i]).product();
+            let target = bytes.clamp(TINY_CHUNK_BYTES, TARGET_CHUNK_BYTES);
+            advice.push(Advice {
+                problem: format!(
+                    "Chunks are 1 element wide along dimension {}, so reading a slice like ds[i] touches {} chunks",
+                    narrow
+                        .iter()
+                        .map(|i| i.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    num_utils::format_integer_with_separator(touched as u64)
+                ),
+                suggestion: format!(
+                    "Chunk along the axes the data is read along, e.g. {:?}",
+                    resized_chunk(&widened, &layout.shape, layout.element_size, target)
+                ),
+            });
+        }
+
+        if let Some(num_allocated) = layout.num_allocated_chunks {
+            if num_allocated < num_chunks {
+                advice.push(Advice {
+                    problem: format!(
+                        "Only {} of {} chunks are allocated, {:.0}% of the dataset was never written and reads as the fill value",
+                        num_utils::format_integer_with_separator(num_allocated as u64),
+                        num_utils::format_integer_with_separator(num_chunks as u64),
+                        100.0 * (num_chunks - num_allocated) as f64 / num_chunks as f64
+                    ),
+                    suggestion: "Fine for sparse data, otherwise look for an interrupted write, or shrink the dataset to what was written".to_string(),
+                });
+            }
+        }
+    } else if layout.storage_size == 0 && data_size > 0 {
+        advice.push(Advice {
+            problem: "No storage is allocated, the dataset was never written".to_string(),
+            suggestion: "Write the data, or remove the dataset if it isn't needed".to_string(),
+        });
+    }
+
+    let compressed = layout.filters.iter().any(PipelineFilter::is_compression);
+    if let Some(bits_per_byte) = layout.bits_per_byte {
+        let estimated_ratio = 8.0 / bits_per_byte.max(0.1);
+        if !compressed
+            && data_size >= MIN_COMPRESSIBLE_BYTES
+            && estimated_ratio >= HIGHLY_COMPRESSIBLE_RATIO
+        {
+            advice.push(Advice {
+                problem: format!(
+                    "{} stored uncompressed, but its bytes carry {:.1} bits each, compression would likely shrink it {:.1}x",
+                    num_utils::file_size_fmt(data_size),
+                    bits_per_byte,
+                    estimated_ratio
+                ),
+                suggestion: if layout.chunk.is_some() {
+                    "Enable deflate (level 4) or Blosc with zstd, with shuffle in front".to_string()
+                } else {
+                    "Store it chunked with deflate (level 4) or Blosc with zstd, with shuffle in front"
+                        .to_string()
+                },
+            });
+        }
+    }
+    advice
+}
+
+/// Whether the in-memory bytes of the dataset are its data rather than pointers to it.
+fn has_inline_data(d: &Dataset) -> bool {
+    match d
+        .dtype()
+        .and_then(|dtype| h5_utils::datatype_descriptor(&dtype))
+    {
+        Ok(TypeDescriptor::VarLenArray(_))
+        | Ok(TypeDescriptor::VarLenAscii)
+        | Ok(TypeDescriptor::VarLenUnicode)
+        | Ok(TypeDescriptor::Reference(_)) => false,
+        Ok(_) => true,
+        // Opaque and bitfield data
+        Err(_) => true,
+    }
+}
+
+/// Checks of the chunking, filters and allocation of `d`, each with a suggestion.
+pub fn advise(d: &Dataset) -> Vec<Advice> {
+    let chunk = d.chunk();
+    let bits_per_byte = if has_inline_data(d) {
+        HexDump::read(d)
+            .ok()
+            .filter(|dump| !dump.bytes.is_empty())
+            .map(|dump| entropy(&dump.bytes))
+    } else {
+        None
+    };
+    let layout = StorageLayout {
+        shape: d.shape(),
+        element_size: d.dtype().map_or(0, |dtype| dtype.size()),
+        num_allocated_chunks: chunk.as_ref().and_then(|_| d.num_chunks()),
+        chunk,
+        filters: filters::read_pipeline(d).unwrap_or_default(),
+        storage_size: d.storage_size(),
+        bits_per_byte,
+    };
+    check(&layout)
+}
+
+fn advise_group(
+    group: &hdf5::Group,
+    path: &str,
+    report: &mut Vec<(String, Vec<Advice>)>,
+) -> hdf5::Result<usize> {
+    let mut num_datasets = 0;
+    for (name, dataset) in h5_utils::datasets(group)? {
+        num_datasets += 1;
+        let advice = advise(&dataset);
+        if !advice.is_empty() {
+            report.push((format!("{}/{}", path, name), advice));
+        }
+    }
+    for (name, child) in h5_utils::groups(group)? {
+        num_datasets += advise_group(&child, &format!("{}/{}", path, name), report)?;
+    }
+    Ok(num_datasets)
+}
+
+/// The advice for every dataset of the file at `path`, as printed by `--advise`.
+pub fn file_report(path: &Path) -> Result<String, Box<dyn Error>> {
+    let file = h5_utils::open_file(&path.to_path_buf())?;
+    let mut report = vec![];
+    let num_datasets = advise_group(&file, "", &mut report)?;
+
+    let mut text = format!("{}\n", path.display());
+    for (dataset_path, advice) in &report {
+        text.push_str(&format!("\n{}\n", dataset_path));
+        for advice in advice {
+            for line in advice.to_string().lines() {
+                text.push_str(&format!("  {}\n", line));
+            }
+        }
+    }
+    text.push_str(&format!(
+        "\n{} datasets checked, {} with advice\n",
+        num_datasets,
+        report.len()
+    ));
+    Ok(text)
+}
+
+/// The advice for the dataset at `dataset_path`, one line per problem and fix, empty if there is none.
+pub fn dataset_advice_from_path(
+    file_path: &Path,
+    dataset_path: &str,
+) -> Result<String, Box<dyn Error>> {
+    let file = h5_utils::open_file(&file_path.to_path_buf())?;
+    let d = file.dataset(dataset_path)?;
+    Ok(advise(&d)
+        .iter()
+        .map(|advice| advice.to_string())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Files to check for `path`: the file itself, or the HDF5 files found under a directory.
+pub fn files_to_check(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut entries = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    let mut files = vec![];
+    for entry in entries {
+        if entry.is_dir() {
+            files.extend(files_to_check(&entry)?);
+        } else if h5_utils::is_hdf5_file(&entry) {
+            files.push(entry);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advice() {
+        let dummy = h5_utils::dummy_file();
+        let file = hdf5::File::open(&dummy.path).unwrap();
+
+        let sparse = advise(&file.dataset("group1/sparse").unwrap());
+        assert_eq!(sparse.len(), 2);
+        assert!(sparse[0].problem.starts_with("Chunks of 400 B are tiny"));
+        assert!(sparse[0].suggestion.ends_with("e.g. [40, 40] (6.4 kB)"));
+        assert!(sparse[1]
+            .problem
+            .starts_with("Only 3 of 16 chunks are allocated, 81%"));
+
+        let steps = advise(&file.dataset("group1/steps").unwrap());
+        assert_eq!(steps.len(), 1);
+        assert!(steps[0].suggestion.starts_with("Store it chunked"));
+        assert!(advise(&file.dataset("group1/large_rand").unwrap()).is_empty());
+        // Only 2 of its 10 frames were written
+        let variable = advise(&file.dataset("variable").unwrap());
+        assert_eq!(variable.len(), 1);
+        assert!(variable[0].problem.starts_with("Only 2 of 10 chunks"));
+
+        let text = dataset_advice_from_path(&dummy.path, "group1/sparse").unwrap();
+        assert_eq!(text.lines().count(), 4);
+        assert!(text.starts_with("⚠ Chunks of 400 B are tiny"));
+        assert!(dataset_advice_from_path(&dummy.path, "group1/large_rand")
+            .unwrap()
+            .is_empty());
+
+        let report = file_report(&dummy.path).unwrap();
+        assert!(report.contains("\n/group1/sparse\n  ⚠ Chunks of 400 B are tiny"));
+
+        // Column chunks of a matrix, and chunks too large for the cache
+        let columns = check(&StorageLayout {
+            shape: vec![1000, 1000],
+            element_size: 8,
+            chunk: Some(vec![1000, 1]),
+            num_allocated_chunks: Some(1000),
+            ..Default::default()
+        });
+        assert_eq!(columns.len(), 2);
+        assert!(columns[1].problem.contains("touches 1_000 chunks"));
+        assert!(columns[1].suggestion.ends_with("e.g. [32, 32]"));
+        let large = check(&StorageLayout {
+            shape: vec![100, 1000, 1000],
+            element_size: 4,
+            chunk: Some(vec![10, 1000, 1000]),
+            num_allocated_chunks: Some(10),
+            ..Default::default()
+        });
+        assert_eq!(large.len(), 1);
+        assert!(large[0].suggestion.contains("[2, 125, 250]"));
+    }
+}
@@ -1,145 +1,1569 @@
+use crate::config;
+use crate::dim_scales::AxisScale;
 use crate::h5_utils;
-use crate::num_utils::{IsNan, MyToPrimitive, Summable};
+use crate::heatmap::{self, HeatmapData};
+use crate::hist_plot::HistogramSource;
+use crate::line_plot::LineData;
+use crate::num_utils::{self, IsNan, MyToPrimitive, Summable};
+use crate::references;
+use crate::string_stats::StringStats;
 use core::f64;
-use dtoa;
+use half::f16;
+use hdf5::plist::dataset_create::FillValue;
+use hdf5::types::{
+    CompoundField, CompoundType, EnumType, FloatSize, IntSize, TypeDescriptor, VarLenAscii,
+    VarLenUnicode,
+};
 #[allow(unused_imports)]
 use hdf5::{File, H5Type};
 use hdf5_metno::{self as hdf5, Dataset};
-use ndarray::{self, Array1};
-use num_traits::{self, ToPrimitive, Zero};
+use hdf5_metno_sys::h5d::H5Dread;
+use hdf5_metno_sys::h5p::H5P_DEFAULT;
+use hdf5_metno_sys::h5s::H5S_ALL;
+use hdf5_metno_sys::h5t::{hvl_t, H5T_str_t, H5Tget_strpad, H5Treclaim};
+use ndarray::{self, Array1, Array2};
+use ndarray_stats::interpolate::Linear;
+use ndarray_stats::{Quantile1dExt, QuantileExt};
+use noisy_float::types::{n64, N64};
+use num_complex::Complex;
+use num_traits::{self, Float, ToPrimitive, Zero};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::error::Error;
 use std::fmt::Display;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::vec;
 
-pub type HistogramData = Vec<(f32, u32)>;
+/// Values shown in the preview of enum datasets.
+const ENUM_PREVIEW_VALUES: usize = 20;
+
+/// Strings read at a time from string datasets.
+const STRING_CHUNK_SIZE: usize = 1 << 16;
+
+/// Elements listed one per line in the preview of array and opaque datasets.
+const PREVIEW_ELEMENTS: usize = 10;
+
+/// Values shown for each element of an array dataset.
+const PREVIEW_ARRAY_VALUES: usize = 8;
+
+/// Bytes shown in hex for each element of an opaque dataset.
+const PREVIEW_OPAQUE_BYTES: usize = 32;
+
+/// Datasets with more values than this get approximate quantiles.
+const MAX_EXACT_QUANTILE_VALUES: usize = 1 << 22;
+
+/// Datasets with more dimensions and values than this get their summary from a sample.
+const MAX_SUMMARY_VALUES: usize = 1 << 26;
+
+/// Key of the stats row saying which slice the heatmap shows, the summary rows come before it.
+const HEATMAP_SLICE_ROW: &str = "Heatmap slice";
+
+/// Values read at once for the summary of datasets with more dimensions.
+const SUMMARY_BLOCK_VALUES: usize = 1 << 20;
+
+/// Ways a dataset can be plotted below its info.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlotKind {
+    Histogram,
+    Line,
+    Heatmap,
+    Counts,
+}
+
+/// Everything needed to plot a dataset, each plot is only there if it makes sense for the data.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlotData {
+    pub histogram: Option<HistogramSource>,
+    pub line: Option<LineData>,
+    pub heatmap: Option<HeatmapData>,
+    /// Labelled counts, e.g. of each member of an enum.
+    pub counts: Option<Vec<(String, u64)>>,
+}
+
+impl PlotData {
+    pub fn available(&self) -> Vec<PlotKind> {
+        let mut kinds = vec![];
+        if self.histogram.is_some() {
+            kinds.push(PlotKind::Histogram);
+        }
+        if self.line.is_some() {
+            kinds.push(PlotKind::Line);
+        }
+        if self.heatmap.is_some() {
+            kinds.push(PlotKind::Heatmap);
+        }
+        if self.counts.is_some() {
+            kinds.push(PlotKind::Counts);
+        }
+        kinds
+    }
+}
+
+/// Stats and plots of one numeric field of a compound dataset.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FieldAnalysis {
+    /// Dotted path for fields of nested compounds, e.g. `position.x`.
+    pub name: String,
+    pub stats: Vec<(String, String)>,
+    pub plots: Arc<PlotData>,
+}
+
+/// What to analyse beyond the dataset itself, results for other options are out of date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct AnalysisOptions {
+    /// Leave numbers equal to the fill value out of the stats.
+    pub exclude_fill_values: bool,
+    /// Which slice of the leading dimensions the heatmap shows, counted in storage order.
+    pub heatmap_slice: usize,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum AnalysisResult {
-    Stats(Vec<(String, String)>, Option<HistogramData>),
+    Stats(Vec<(String, String)>, Arc<PlotData>),
+    Fields(Vec<FieldAnalysis>),
     NotAvailable,
     Failed(String),
 }
 
-fn compute_histogram(d: &Array1<f64>) -> Result<HistogramData, Box<dyn Error>> {
-    let n_bins = 30; // Number of bins
-    let min = d.iter().fold(f64::INFINITY, |acc, &x| f64::min(acc, x));
-    let max = d.iter().fold(f64::NEG_INFINITY, |acc, &x| f64::max(acc, x));
-    let bin_width = (max - min) / (n_bins - 1) as f64;
+/// The median and the configured percentiles.
+/// Above `MAX_EXACT_QUANTILE_VALUES` values they come from an evenly spaced sample.
+fn quantile_stats(values: &Array1<f64>) -> Vec<(String, String)> {
+    let mut info = vec![];
+    let stride = values.len().div_ceil(MAX_EXACT_QUANTILE_VALUES).max(1);
+    let mut sample: Array1<N64> = values
+        .iter()
+        .step_by(stride)
+        .filter(|v| !v.is_nan())
+        .map(|&v| n64(v))
+        .collect();
+    let percentiles = &config::get().percentiles;
+    let qs: Array1<N64> = std::iter::once(0.5)
+        .chain(percentiles.iter().map(|p| p / 100.0))
+        .map(n64)
+        .collect();
+    let Ok(quantiles) = sample.quantiles_mut(&qs, &Linear) else {
+        return info;
+    };
+    let approx = if stride > 1 { "≈ " } else { "" };
+    let fmt = |q: N64| format!("{}{}", approx, num_utils::float_fmt(q.raw()));
+    info.push(("Median".to_owned(), fmt(quantiles[0])));
+    for (p, &q) in percentiles.iter().zip(quantiles.iter().skip(1)) {
+        info.push((format!("P{}", p), fmt(q)));
+    }
+    info
+}
 
-    if !min.is_finite() || !max.is_finite() || bin_width <= 0.0 {
-        return Err(format!(
-            "Problem with histogram gen: min = {}, max = {}, bin_width = {}",
-            min, max, bin_width
-        )
-        .into());
+/// The fill value of `d`, if it has one and `exclude_fill_values` is set.
+fn fill_value_to_exclude<T: H5Type>(d: &Dataset, exclude_fill_values: bool) -> Option<T> {
+    if !exclude_fill_values {
+        return None;
+    }
+    let dcpl = d.dcpl().ok()?;
+    match dcpl.fill_value_defined() {
+        FillValue::Undefined => None,
+        FillValue::Default | FillValue::UserDefined => dcpl.fill_value_as::<T>(),
     }
+}
 
-    let mut counts = vec![0; n_bins];
-    for &value in d.iter() {
-        if !value.is_nan() {
-            let bin_index = ((value - min) / bin_width).floor() as usize;
-            if bin_index < n_bins {
-                counts[bin_index] += 1;
-            }
+fn analysis_1d<T>(
+    d: Arc<Dataset>,
+    exclude_fill_values: bool,
+) -> Result<AnalysisResult, Box<dyn Error>>
+where
+    T: H5Type + Summable + IsNan + Clone + Display + MyToPrimitive + PartialOrd,
+{
+    let v: Array1<T> = d.read_1d()?;
+    let is_float = matches!(d.dtype()?.to_descriptor()?, TypeDescriptor::Float(_));
+    let fill = fill_value_to_exclude::<T>(&d, exclude_fill_values);
+    let (mut info, mut plot_data) = stats_1d(&v, is_float, fill.as_ref());
+    let x_scale = AxisScale::read(&d, 0);
+    if let Some(scale) = &x_scale {
+        // Each value with its position on the scale, not only its index
+        if let Some(row) = info.iter_mut().find(|(k, _)| k == "Data preview") {
+            row.1 = element_preview(
+                v.iter()
+                    .enumerate()
+                    .map(|(i, x)| format!("{} = {}: {}", scale.name, scale.label(i as f64), x)),
+                v.len(),
+            );
         }
     }
-
-    // Convert to Vec<(bin_center, count)> as f32
-    let mut result = Vec::new();
-    for i in 0..n_bins {
-        let bin_center = min + (bin_width * (i as f64)) + (bin_width / 2.0);
-        let count = counts[i];
-        result.push((bin_center as f32, count));
+    if let Some(line) = plot_data.line.as_mut() {
+        line.x_scale = x_scale;
     }
-
-    Ok(result)
+    Ok(AnalysisResult::Stats(info, Arc::new(plot_data)))
 }
 
-fn analysis_1d<T>(d: Arc<Dataset>) -> Result<AnalysisResult, Box<dyn Error>>
+/// Summary stats, quantiles and counts of `v`, plus its histogram and line plot.
+/// Values equal to `fill` are left out of the stats and the histogram, and are gaps in the line.
+fn stats_1d<T>(v: &Array1<T>, is_float: bool, fill: Option<&T>) -> (Vec<(String, String)>, PlotData)
 where
-    T: H5Type + Summable + IsNan + Clone + Display + MyToPrimitive,
+    T: Summable + IsNan + Clone + Display + MyToPrimitive + PartialOrd,
 {
     let mut info: Vec<(String, String)> = Vec::new();
 
-    let v: Array1<T> = d.read_1d()?;
+    // Positions in `v` of the values the stats are about
+    let kept_indices: Option<Vec<usize>> =
+        fill.map(|fill| (0..v.len()).filter(|&i| v[i] != *fill).collect());
+    let kept: Option<Array1<T>> = kept_indices
+        .as_ref()
+        .map(|indices| indices.iter().map(|&i| v[i].clone()).collect());
+    let values = kept.as_ref().unwrap_or(v);
+    if let Some(fill) = fill {
+        info.push((
+            "Fill values excluded".to_owned(),
+            format!(
+                "{} equal to {}",
+                num_utils::format_integer_with_separator((v.len() - values.len()) as u64),
+                fill
+            ),
+        ));
+    }
+    let line = || {
+        let line_values: Vec<f64> = v
+            .iter()
+            .map(|x| match fill {
+                Some(fill) if x == fill => f64::NAN,
+                _ => x.my_to_f64().unwrap_or(f64::NAN),
+            })
+            .collect();
+        LineData::from_values(&line_values)
+    };
+    if values.is_empty() {
+        info.push(("Data preview".to_owned(), format!("{}", v)));
+        let plot_data = PlotData {
+            line: Some(line()),
+            ..Default::default()
+        };
+        return (info, plot_data);
+    }
 
-    let sum: T::AccumulatorType = v.iter().fold(T::AccumulatorType::zero(), |acc, x| {
+    let sum: T::AccumulatorType = values.iter().fold(T::AccumulatorType::zero(), |acc, x| {
         acc + x.to_owned().into()
     });
 
-    let mean: f64 = (sum.to_f64().unwrap_or(f64::NAN)) / (v.len() as f64);
+    let mean: f64 = (sum.to_f64().unwrap_or(f64::NAN)) / (values.len() as f64);
+
+    info.push(("Mean".to_owned(), num_utils::float_fmt(mean)));
+
+    let arr_f64: Array1<f64> = values.mapv(|x| x.my_to_f64().unwrap_or(f64::NAN));
+    let std: f64 = arr_f64.std(1.);
+    info.push(("Std".to_owned(), num_utils::float_fmt(std)));
+
+    if let (Ok(min_index), Ok(max_index)) = (arr_f64.argmin_skipnan(), arr_f64.argmax_skipnan()) {
+        for (name, index) in [("Min", min_index), ("Max", max_index)] {
+            let position = kept_indices
+                .as_ref()
+                .map_or(index, |indices| indices[index]);
+            info.push((
+                name.to_owned(),
+                format!("{} at [{}]", values[index], position),
+            ));
+        }
+    }
+    info.extend(quantile_stats(&arr_f64));
 
     info.push((
-        "Mean".to_owned(),
-        dtoa::Buffer::new().format(mean).to_string(),
+        "NaN count".to_owned(),
+        values.mapv(|x| x.my_is_nan() as u32).sum().to_string(),
     ));
+    if is_float {
+        let num_infinite = arr_f64.iter().filter(|x| x.is_infinite()).count();
+        info.push(("Infinity count".to_owned(), num_infinite.to_string()));
+    }
+    let num_zeros = arr_f64.iter().filter(|&&x| x == 0.0).count();
+    info.push(("Zero count".to_owned(), num_zeros.to_string()));
+    if !is_float {
+        let mut distinct = values.to_vec();
+        distinct.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        distinct.dedup();
+        info.push((
+            "Distinct values".to_owned(),
+            num_utils::format_integer_with_separator(distinct.len() as u64),
+        ));
+    }
+
+    info.push(("Data preview".to_owned(), format!("{}", v)));
+
+    let plot_data = PlotData {
+        histogram: arr_f64
+            .as_slice()
+            .and_then(|values| HistogramSource::new(values, !is_float)),
+        line: match fill {
+            Some(_) => Some(line()),
+            None => arr_f64.as_slice().map(LineData::from_values),
+        },
+        ..Default::default()
+    };
+
+    (info, plot_data)
+}
 
+/// Row telling that fill values were asked to be left out but are included, for the kind of data
+/// that isn't compared to the fill value yet. `None` if they weren't asked to be left out.
+fn fill_exclusion_unsupported(
+    d: &Dataset,
+    exclude_fill_values: bool,
+    kind: &str,
+) -> Option<(String, String)> {
+    let has_fill_value = d
+        .dcpl()
+        .is_ok_and(|dcpl| dcpl.fill_value_defined() != FillValue::Undefined);
+    (exclude_fill_values && has_fill_value).then(|| {
+        (
+            "Fill values excluded".to_owned(),
+            format!("Not supported for {}, all values are included", kind),
+        )
+    })
+}
+
+/// Magnitude, phase, real and imaginary parts of a complex dataset, each analysed like a compound field.
+fn analysis_complex<T>(
+    d: Arc<Dataset>,
+    exclude_fill_values: bool,
+) -> Result<AnalysisResult, Box<dyn Error>>
+where
+    T: H5Type + Float + Display,
+{
+    let values: Array1<Complex<T>> = Array1::from(d.read_raw::<Complex<T>>()?);
+    let preview = format!("{}", values);
+    let to_f64 = |x: T| x.to_f64().unwrap_or(f64::NAN);
+    let unsupported = fill_exclusion_unsupported(&d, exclude_fill_values, "complex numbers");
+    let components: [(&str, Array1<f64>); 4] = [
+        ("magnitude", values.mapv(|c| to_f64(c.norm()))),
+        ("phase", values.mapv(|c| to_f64(c.arg()))),
+        ("real", values.mapv(|c| to_f64(c.re))),
+        ("imaginary", values.mapv(|c| to_f64(c.im))),
+    ];
+    let fields = components
+        .into_iter()
+        .map(|(name, component)| {
+            let (mut stats, mut plots) = stats_1d(&component, true, None);
+            // The complex values say more than the values of just this part
+            if let Some(row) = stats.iter_mut().find(|(k, _)| k == "Data preview") {
+                row.1 = preview.clone();
+            }
+            stats.splice(0..0, unsupported.clone());
+            if d.ndim() != 1 {
+                plots.line = None;
+            }
+            FieldAnalysis {
+                name: name.to_owned(),
+                stats,
+                plots: Arc::new(plots),
+            }
+        })
+        .collect();
+    Ok(AnalysisResult::Fields(fields))
+}
+
+/// How often each member occurs and the first values by member name.
+/// `values` are the bits of the base integer, like `EnumMember::value`.
+fn enum_stats(values: &[u64], enum_type: &EnumType) -> (Vec<(String, String)>, PlotData) {
+    let mut counts: Vec<(String, u64)> = enum_type
+        .members
+        .iter()
+        .map(|m| (m.name.clone(), 0))
+        .collect();
+    let mut num_unknown = 0;
+    for value in values {
+        match enum_type.members.iter().position(|m| m.value == *value) {
+            Some(index) => counts[index].1 += 1,
+            None => num_unknown += 1,
+        }
+    }
+
+    let mut info = vec![(
+        "Member counts".to_owned(),
+        counts
+            .iter()
+            .map(|(name, count)| {
+                format!(
+                    "{}: {}",
+                    name,
+                    num_utils::format_integer_with_separator(*count)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )];
+    if num_unknown > 0 {
+        info.push((
+            "Values not in the enum".to_owned(),
+            num_utils::format_integer_with_separator(num_unknown),
+        ));
+        counts.push(("(other)".to_owned(), num_unknown));
+    }
+    let mut preview: Vec<String> = values
+        .iter()
+        .take(ENUM_PREVIEW_VALUES)
+        .map(|&v| h5_utils::enum_member_name(enum_type, v))
+        .collect();
+    if values.len() > ENUM_PREVIEW_VALUES {
+        preview.push("…".to_owned());
+    }
     info.push((
-        "NaN count".to_owned(),
-        v.mapv(|x| x.my_is_nan() as u32).sum().to_string(),
+        "Data preview".to_owned(),
+        format!("[{}]", preview.join(", ")),
     ));
 
-    let arr_f64: Array1<f64> = v.mapv(|x| x.my_to_f64().unwrap_or(f64::NAN));
-    let std: f64 = arr_f64.std(1.);
+    let plot_data = PlotData {
+        counts: Some(counts),
+        ..Default::default()
+    };
+    (info, plot_data)
+}
+
+fn analysis_enum(d: Arc<Dataset>, enum_type: &EnumType) -> Result<AnalysisResult, Box<dyn Error>> {
+    let values = h5_utils::read_enum_values(&d, enum_type)?;
+    let (info, plot_data) = enum_stats(&values, enum_type);
+    Ok(AnalysisResult::Stats(info, Arc::new(plot_data)))
+}
+
+/// Step along each dimension for the summary: the trailing ones so an index of the first
+/// dimension fits in a block, the first one so at most `MAX_SUMMARY_VALUES` are read.
+fn summary_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    let sampled = |strides: &[usize], i: usize| shape[i].div_ceil(strides[i]);
+    loop {
+        let row_len: usize = (1..shape.len()).map(|i| sampled(&strides, i)).product();
+        if row_len <= SUMMARY_BLOCK_VALUES {
+            strides[0] = (shape[0] * row_len).div_ceil(MAX_SUMMARY_VALUES).max(1);
+            return strides;
+        }
+        let widest = (1..shape.len())
+            .max_by_key(|&i| sampled(&strides, i))
+            .unwrap();
+        strides[widest] *= 2;
+    }
+}
+
+/// Mean, min and max of all values, read in blocks along the first dimension.
+/// Above `MAX_SUMMARY_VALUES` values, or `SUMMARY_BLOCK_VALUES` per index of the first
+/// dimension, only every n-th index is read.
+fn summary_nd<T>(d: &Dataset, fill: Option<&T>) -> Result<Vec<(String, String)>, Box<dyn Error>>
+where
+    T: H5Type + Clone + MyToPrimitive + PartialEq + Display,
+{
+    let shape = d.shape();
+    let strides = summary_strides(&shape);
+    let counts: Vec<usize> = shape
+        .iter()
+        .zip(&strides)
+        .map(|(len, stride)| len.div_ceil(*stride))
+        .collect();
+    let row_len = counts[1..].iter().product::<usize>().max(1);
+    let rows_per_block = (SUMMARY_BLOCK_VALUES / row_len).max(1);
+    let (mut sum, mut count, mut num_fill) = (0.0, 0usize, 0usize);
+    let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+    for first_row in (0..counts[0]).step_by(rows_per_block) {
+        let mut selection = vec![hdf5::SliceOrIndex::SliceCount {
+            start: first_row * strides[0],
+            step: strides[0],
+            count: rows_per_block.min(counts[0] - first_row),
+            block: 1,
+        }];
+        for i in 1..shape.len() {
+            selection.push(hdf5::SliceOrIndex::SliceCount {
+                start: 0,
+                step: strides[i],
+                count: counts[i],
+                block: 1,
+            });
+        }
+        let block: ndarray::ArrayD<T> = d.read_slice(hdf5::Hyperslab::from(selection))?;
+        for x in block.iter() {
+            if fill.is_some_and(|fill| x == fill) {
+                num_fill += 1;
+                continue;
+            }
+            let x = x.my_to_f64().unwrap_or(f64::NAN);
+            if !x.is_nan() {
+                sum += x;
+                count += 1;
+                min = min.min(x);
+                max = max.max(x);
+            }
+        }
+    }
+
+    let sampled = strides.iter().any(|&stride| stride > 1);
+    let approx = if sampled { "≈ " } else { "" };
+    let mut info = vec![];
+    if let Some(fill) = fill {
+        info.push((
+            "Fill values excluded".to_owned(),
+            format!(
+                "{}{} equal to {}",
+                approx,
+                num_utils::format_integer_with_separator(
+                    (num_fill as f64 * d.size() as f64 / counts.iter().product::<usize>() as f64)
+                        .round() as u64
+                ),
+                fill
+            ),
+        ));
+    }
+    if count > 0 {
+        info.push((
+            "Mean".to_owned(),
+            format!("{}{}", approx, num_utils::float_fmt(sum / count as f64)),
+        ));
+        info.push((
+            "Min".to_owned(),
+            format!("{}{}", approx, num_utils::float_fmt(min)),
+        ));
+        info.push((
+            "Max".to_owned(),
+            format!("{}{}", approx, num_utils::float_fmt(max)),
+        ));
+    }
+    if sampled {
+        info.push((
+            "Summary sampling".to_owned(),
+            format!("every {:?} indices along the dimensions", strides),
+        ));
+    }
+    Ok(info)
+}
+
+/// Number of 2D slices along the leading dimensions of a dataset of `shape`.
+pub fn num_heatmap_slices(shape: &[usize]) -> usize {
+    shape[..shape.len().saturating_sub(2)].iter().product()
+}
+
+/// The summary rows of the stats of a dataset with a heatmap, without those of the heatmap.
+pub fn summary_rows(stats: &[(String, String)]) -> &[(String, String)] {
+    let end = stats
+        .iter()
+        .position(|(key, _)| key == HEATMAP_SLICE_ROW)
+        .unwrap_or(stats.len());
+    &stats[..end]
+}
+
+/// Summary of all values and a heatmap of the last two dimensions, at the `heatmap_slice`
+/// of the leading ones in storage order. Large slices are read with a stride instead of in full.
+/// A `summary` from an earlier analysis is used instead of reading all values again.
+fn analysis_2d<T>(
+    d: Arc<Dataset>,
+    exclude_fill_values: bool,
+    heatmap_slice: usize,
+    summary: Option<Vec<(String, String)>>,
+) -> Result<AnalysisResult, Box<dyn Error>>
+where
+    T: H5Type + Clone + MyToPrimitive + PartialEq + Display,
+{
+    let fill = fill_value_to_exclude::<T>(&d, exclude_fill_values);
+    let shape = d.shape();
+    let ndim = shape.len();
+    let (ny, nx) = (shape[ndim - 2], shape[ndim - 1]);
+    let step = (
+        ny.div_ceil(heatmap::MAX_HEATMAP_SIDE),
+        nx.div_ceil(heatmap::MAX_HEATMAP_SIDE),
+    );
+
+    // The index along each leading dimension of the slice
+    let num_slices = num_heatmap_slices(&shape);
+    let heatmap_slice = heatmap_slice.min(num_slices.saturating_sub(1));
+    let mut leading = vec![0; ndim - 2];
+    let mut rest = heatmap_slice;
+    for (index, &len) in leading.iter_mut().zip(&shape[..ndim - 2]).rev() {
+        *index = rest % len;
+        rest /= len;
+    }
+
+    let mut selection: Vec<hdf5::SliceOrIndex> = leading
+        .iter()
+        .map(|&index| hdf5::SliceOrIndex::Index(index))
+        .collect();
+    for (len, step) in [(ny, step.0), (nx, step.1)] {
+        selection.push(hdf5::SliceOrIndex::SliceCount {
+            start: 0,
+            step,
+            count: len.div_ceil(step),
+            block: 1,
+        });
+    }
+    let v: Array2<T> = d.read_slice(hdf5::Hyperslab::from(selection))?;
+    let values = v.mapv(|x| match &fill {
+        Some(fill) if x == *fill => f64::NAN,
+        _ => x.my_to_f64().unwrap_or(f64::NAN),
+    });
+
+    let slice = format!(
+        "[{}:, :]",
+        leading
+            .iter()
+            .map(|index| format!("{}, ", index))
+            .collect::<String>()
+    );
+    let mut info = match summary {
+        Some(summary) => summary,
+        None => summary_nd(&d, fill.as_ref())?,
+    };
     info.push((
-        "Std".to_owned(),
-        dtoa::Buffer::new().format(std).to_string(),
+        HEATMAP_SLICE_ROW.to_owned(),
+        if num_slices > 1 {
+            format!("{} ({} of {})", slice, heatmap_slice + 1, num_slices)
+        } else {
+            slice.clone()
+        },
     ));
+    if step != (1, 1) {
+        info.push((
+            "Heatmap sampling".to_owned(),
+            format!("every {} rows, every {} columns", step.0, step.1),
+        ));
+    }
 
-    info.push(("Data preview".to_owned(), format!("{}", v)));
+    let mut heatmap = HeatmapData::new(values, (ny, nx), step, slice);
+    heatmap.scales = (AxisScale::read(&d, ndim - 2), AxisScale::read(&d, ndim - 1));
+    let plot_data = PlotData {
+        heatmap: Some(heatmap),
+        ..Default::default()
+    };
+    Ok(AnalysisResult::Stats(info, Arc::new(plot_data)))
+}
+
+/// 1D datasets get stats, datasets with more dimensions a summary and a heatmap.
+fn analysis_for<T>(
+    d: Arc<Dataset>,
+    options: AnalysisOptions,
+    summary: Option<Vec<(String, String)>>,
+) -> Result<AnalysisResult, Box<dyn Error>>
+where
+    T: H5Type + Summable + IsNan + Clone + Display + MyToPrimitive + PartialOrd,
+{
+    if d.ndim() == 1 {
+        analysis_1d::<T>(d, options.exclude_fill_values)
+    } else {
+        analysis_2d::<T>(
+            d,
+            options.exclude_fill_values,
+            options.heatmap_slice,
+            summary,
+        )
+    }
+}
+
+fn is_numeric(ty: &TypeDescriptor) -> bool {
+    matches!(
+        ty,
+        TypeDescriptor::Integer(_)
+            | TypeDescriptor::Unsigned(_)
+            | TypeDescriptor::Float(_)
+            | TypeDescriptor::Boolean
+            | TypeDescriptor::Enum(_)
+    )
+}
+
+/// The numeric and enum fields of `compound`, recursing into nested compounds.
+/// `None` if there are none, strings and arrays are left out.
+fn numeric_fields_only(compound: &CompoundType) -> Option<CompoundType> {
+    let fields: Vec<CompoundField> = compound
+        .fields
+        .iter()
+        .filter_map(|field| match &field.ty {
+            TypeDescriptor::Compound(nested) => Some(CompoundField {
+                ty: TypeDescriptor::Compound(numeric_fields_only(nested)?),
+                ..field.clone()
+            }),
+            ty if is_numeric(ty) => Some(field.clone()),
+            _ => None,
+        })
+        .collect();
+    (!fields.is_empty()).then_some(CompoundType {
+        fields,
+        size: compound.size,
+    })
+}
+
+/// Name, type and byte offset of every leaf field.
+pub fn flatten_fields(
+    compound: &CompoundType,
+    prefix: &str,
+    base_offset: usize,
+    leaves: &mut Vec<(String, TypeDescriptor, usize)>,
+) {
+    for field in &compound.fields {
+        let name = format!("{}{}", prefix, field.name);
+        let offset = base_offset + field.offset;
+        match &field.ty {
+            TypeDescriptor::Compound(nested) => {
+                flatten_fields(nested, &format!("{}.", name), offset, leaves)
+            }
+            ty => leaves.push((name, ty.clone(), offset)),
+        }
+    }
+}
+
+/// An unsigned integer of `size` bytes in native byte order.
+fn decode_bits(size: IntSize, bytes: &[u8]) -> u64 {
+    match size {
+        IntSize::U1 => bytes[0] as u64,
+        IntSize::U2 => u16::from_ne_bytes(bytes[..2].try_into().unwrap()) as u64,
+        IntSize::U4 => u32::from_ne_bytes(bytes[..4].try_into().unwrap()) as u64,
+        IntSize::U8 => u64::from_ne_bytes(bytes[..8].try_into().unwrap()),
+    }
+}
+
+/// A numeric value in native byte order, as f64.
+fn decode_f64(ty: &TypeDescriptor, bytes: &[u8]) -> f64 {
+    macro_rules! read {
+        ($t:ty) => {
+            <$t>::from_ne_bytes(bytes[..size_of::<$t>()].try_into().unwrap()) as f64
+        };
+    }
+    match ty {
+        TypeDescriptor::Integer(IntSize::U1) => read!(i8),
+        TypeDescriptor::Integer(IntSize::U2) => read!(i16),
+        TypeDescriptor::Integer(IntSize::U4) => read!(i32),
+        TypeDescriptor::Integer(IntSize::U8) => read!(i64),
+        TypeDescriptor::Unsigned(IntSize::U1) => read!(u8),
+        TypeDescriptor::Unsigned(IntSize::U2) => read!(u16),
+        TypeDescriptor::Unsigned(IntSize::U4) => read!(u32),
+        TypeDescriptor::Unsigned(IntSize::U8) => read!(u64),
+        TypeDescriptor::Float(FloatSize::U2) => f16::from_ne_bytes([bytes[0], bytes[1]]).to_f64(),
+        TypeDescriptor::Float(FloatSize::U4) => read!(f32),
+        TypeDescriptor::Float(FloatSize::U8) => read!(f64),
+        TypeDescriptor::Boolean => (bytes[0] != 0) as u8 as f64,
+        _ => f64::NAN,
+    }
+}
+
+/// A number, or the bits of an enum, stored with the C layout `layout`.
+fn decode_value(layout: &TypeDescriptor, bytes: &[u8]) -> f64 {
+    match layout {
+        TypeDescriptor::Enum(enum_type) => decode_bits(enum_type.size, bytes) as f64,
+        ty => decode_f64(ty, bytes),
+    }
+}
 
-    let hist = compute_histogram(&arr_f64).ok();
+/// Reads `selection`, or the whole dataset, as `mem_type`.
+/// For compounds HDF5 picks the fields out of the file type by name.
+/// Variable length data is freed again, leaving only the pointers to it.
+pub fn read_bytes(
+    d: &Dataset,
+    mem_type: &hdf5::Datatype,
+    selection: Option<hdf5::Selection>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    read_decoded(d, mem_type, selection, |bytes| bytes.to_vec())
+}
+
+/// Like `read_bytes`, but hands the bytes to `decode` before variable length data is freed,
+/// so it can still follow the pointers.
+fn read_decoded<R>(
+    d: &Dataset,
+    mem_type: &hdf5::Datatype,
+    selection: Option<hdf5::Selection>,
+    decode: impl FnOnce(&[u8]) -> R,
+) -> Result<R, Box<dyn Error>> {
+    let file_space = match selection {
+        Some(selection) => Some(d.space()?.select(selection)?),
+        None => None,
+    };
+    let mem_space = match &file_space {
+        Some(file_space) => hdf5::Dataspace::try_new(file_space.selection_size())?,
+        None => d.space()?,
+    };
+    let num_values = mem_space.selection_size();
+    let mut buf = vec![0u8; num_values * mem_type.size()];
+    let decoded = hdf5::sync::sync(|| unsafe {
+        let status = H5Dread(
+            d.id(),
+            mem_type.id(),
+            file_space.as_ref().map_or(H5S_ALL, |_| mem_space.id()),
+            file_space.as_ref().map_or(H5S_ALL, |s| s.id()),
+            H5P_DEFAULT,
+            buf.as_mut_ptr().cast(),
+        );
+        if status < 0 {
+            return None;
+        }
+        let decoded = decode(&buf);
+        H5Treclaim(
+            mem_type.id(),
+            mem_space.id(),
+            H5P_DEFAULT,
+            buf.as_mut_ptr().cast(),
+        );
+        Some(decoded)
+    });
+    decoded.ok_or_else(|| format!("Failed to read {}", d.name()).into())
+}
 
-    Ok(AnalysisResult::Stats(info, hist))
+/// Every numeric field of a compound dataset gets analysed as if it were a dataset of its own.
+fn analysis_compound(
+    d: Arc<Dataset>,
+    compound: &CompoundType,
+    exclude_fill_values: bool,
+) -> Result<AnalysisResult, Box<dyn Error>> {
+    let Some(numeric) = numeric_fields_only(compound) else {
+        return Ok(AnalysisResult::NotAvailable);
+    };
+    let layout = TypeDescriptor::Compound(numeric).to_c_repr();
+    let TypeDescriptor::Compound(layout_compound) = &layout else {
+        unreachable!("the C layout of a compound is a compound");
+    };
+    let mem_type = hdf5::Datatype::from_descriptor(&layout)?;
+    let bytes = read_bytes(&d, &mem_type, None)?;
+    let unsupported = fill_exclusion_unsupported(&d, exclude_fill_values, "compound fields");
+
+    let mut leaves = vec![];
+    flatten_fields(layout_compound, "", 0, &mut leaves);
+    let fields = leaves
+        .into_iter()
+        .map(|(name, ty, offset)| {
+            let elements = bytes.chunks_exact(layout_compound.size);
+            if let TypeDescriptor::Enum(enum_type) = &ty {
+                let values: Vec<u64> = elements
+                    .map(|element| decode_bits(enum_type.size, &element[offset..]))
+                    .collect();
+                let (mut stats, plots) = enum_stats(&values, enum_type);
+                stats.splice(0..0, unsupported.clone());
+                return FieldAnalysis {
+                    name,
+                    stats,
+                    plots: Arc::new(plots),
+                };
+            }
+            let values: Array1<f64> = bytes
+                .chunks_exact(layout_compound.size)
+                .map(|element| decode_f64(&ty, &element[offset..]))
+                .collect();
+            let is_float = matches!(ty, TypeDescriptor::Float(_));
+            let (mut stats, mut plots) = stats_1d(&values, is_float, None);
+            stats.splice(0..0, unsupported.clone());
+            if d.ndim() != 1 {
+                plots.line = None;
+            }
+            FieldAnalysis {
+                name,
+                stats,
+                plots: Arc::new(plots),
+            }
+        })
+        .collect();
+    Ok(AnalysisResult::Fields(fields))
 }
 
+/// `[i] text` lines for the first `PREVIEW_ELEMENTS` elements.
+fn element_preview(texts: impl Iterator<Item = String>, num_elements: usize) -> String {
+    let mut lines: Vec<String> = texts
+        .take(PREVIEW_ELEMENTS)
+        .enumerate()
+        .map(|(i, text)| format!("[{}] {}", i, text))
+        .collect();
+    if num_elements > PREVIEW_ELEMENTS {
+        lines.push(format!(
+            "… {} more",
+            num_utils::format_integer_with_separator((num_elements - PREVIEW_ELEMENTS) as u64)
+        ));
+    }
+    lines.join("\n")
+}
+
+/// The values of one array element, enum values by member name.
+fn array_text(values: &[f64], base: &TypeDescriptor) -> String {
+    let mut texts: Vec<String> = values
+        .iter()
+        .take(PREVIEW_ARRAY_VALUES)
+        .map(|&v| match base {
+            TypeDescriptor::Enum(enum_type) => h5_utils::enum_member_name(enum_type, v as u64),
+            TypeDescriptor::Boolean => (v != 0.0).to_string(),
+            _ => v.to_string(),
+        })
+        .collect();
+    if values.len() > PREVIEW_ARRAY_VALUES {
+        texts.push("…".to_owned());
+    }
+    format!("[{}]", texts.join(", "))
+}
+
+/// Stats of the values of every element together, with `preview` shown for the data.
+fn array_values_stats(
+    elements: &[Vec<f64>],
+    base: &TypeDescriptor,
+    preview: String,
+) -> (Vec<(String, String)>, PlotData) {
+    let (mut stats, mut plots) = match base {
+        TypeDescriptor::Enum(enum_type) => {
+            let values: Vec<u64> = elements.iter().flatten().map(|&v| v as u64).collect();
+            enum_stats(&values, enum_type)
+        }
+        _ => {
+            let values: Array1<f64> = elements.iter().flatten().copied().collect();
+            stats_1d(&values, matches!(base, TypeDescriptor::Float(_)), None)
+        }
+    };
+    if let Some(row) = stats.iter_mut().find(|(k, _)| k == "Data preview") {
+        row.1 = preview;
+    }
+    // Consecutive values come from different elements
+    plots.line = None;
+    (stats, plots)
+}
+
+/// Fixed size arrays of numbers or enums, each element shown with all of its values.
+fn analysis_fixed_array(
+    d: &Dataset,
+    base: &TypeDescriptor,
+    len: usize,
+) -> Result<AnalysisResult, Box<dyn Error>> {
+    if !is_numeric(base) || len == 0 {
+        return Ok(AnalysisResult::NotAvailable);
+    }
+    let layout = base.to_c_repr();
+    let mem_type = hdf5::Datatype::from_descriptor(&TypeDescriptor::FixedArray(
+        Box::new(layout.clone()),
+        len,
+    ))?;
+    let bytes = read_bytes(d, &mem_type, None)?;
+    let elements: Vec<Vec<f64>> = bytes
+        .chunks_exact(len * layout.size())
+        .map(|element| {
+            element
+                .chunks_exact(layout.size())
+                .map(|value| decode_value(&layout, value))
+                .collect()
+        })
+        .collect();
+    let preview = element_preview(
+        elements.iter().map(|values| array_text(values, base)),
+        elements.len(),
+    );
+    let (stats, plots) = array_values_stats(&elements, base, preview);
+    Ok(AnalysisResult::Stats(stats, Arc::new(plots)))
+}
+
+/// Variable length arrays of numbers or enums: the lengths, then the values.
+fn analysis_ragged(d: &Dataset, base: &TypeDescriptor) -> Result<AnalysisResult, Box<dyn Error>> {
+    if !is_numeric(base) {
+        return Ok(AnalysisResult::NotAvailable);
+    }
+    // Read with the base type as is, like fixed size arrays, so enums keep their bits
+    let layout = base.to_c_repr();
+    let mem_type =
+        hdf5::Datatype::from_descriptor(&TypeDescriptor::VarLenArray(Box::new(layout.clone())))?;
+    let elements: Vec<Vec<f64>> = read_decoded(d, &mem_type, None, |bytes| {
+        bytes
+            .chunks_exact(size_of::<hvl_t>())
+            .map(|element| {
+                let array = unsafe { element.as_ptr().cast::<hvl_t>().read_unaligned() };
+                if array.len == 0 || array.p.is_null() {
+                    return vec![];
+                }
+                let values = unsafe {
+                    std::slice::from_raw_parts(array.p.cast::<u8>(), array.len * layout.size())
+                };
+                values
+                    .chunks_exact(layout.size())
+                    .map(|value| decode_value(&layout, value))
+                    .collect()
+            })
+            .collect()
+    })?;
+    let preview = element_preview(
+        elements
+            .iter()
+            .map(|values| format!("(len {}) {}", values.len(), array_text(values, base))),
+        elements.len(),
+    );
+
+    let lengths: Array1<f64> = elements.iter().map(|values| values.len() as f64).collect();
+    let (mut length_stats, mut length_plots) = stats_1d(&lengths, false, None);
+    if let Some(row) = length_stats.iter_mut().find(|(k, _)| k == "Data preview") {
+        row.1 = preview.clone();
+    }
+    if d.ndim() != 1 {
+        length_plots.line = None;
+    }
+    let mut fields = vec![FieldAnalysis {
+        name: "lengths".to_owned(),
+        stats: length_stats,
+        plots: Arc::new(length_plots),
+    }];
+    if elements.iter().any(|values| !values.is_empty()) {
+        let (stats, plots) = array_values_stats(&elements, base, preview);
+        fields.push(FieldAnalysis {
+            name: "values".to_owned(),
+            stats,
+            plots: Arc::new(plots),
+        });
+    }
+    Ok(AnalysisResult::Fields(fields))
+}
+
+/// Space separated hex bytes, e.g. `de ad be ef`.
+fn hex_text(bytes: &[u8]) -> String {
+    let mut text: Vec<String> = bytes
+        .iter()
+        .take(PREVIEW_OPAQUE_BYTES)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if bytes.len() > PREVIEW_OPAQUE_BYTES {
+        text.push("…".to_owned());
+    }
+    text.join(" ")
+}
+
+/// Opaque and bitfield values have no meaning to us, their bytes are shown as stored.
+fn analysis_opaque(d: &Dataset, dtype: &hdf5::Datatype) -> Result<AnalysisResult, Box<dyn Error>> {
+    let bytes = read_bytes(d, dtype, None)?;
+    let elements = bytes.chunks_exact(dtype.size().max(1));
+    let mut info = vec![];
+    if let Some(tag) = h5_utils::opaque_tag(dtype) {
+        info.push(("Tag".to_owned(), tag));
+    }
+    info.push((
+        "Data preview".to_owned(),
+        element_preview(elements.clone().map(hex_text), d.size()),
+    ));
+    let distinct: HashSet<&[u8]> = elements.collect();
+    info.push((
+        "Distinct values".to_owned(),
+        num_utils::format_integer_with_separator(distinct.len() as u64),
+    ));
+    Ok(AnalysisResult::Stats(info, Arc::new(PlotData::default())))
+}
+
+/// Selections of whole rows along the first dimension, about `STRING_CHUNK_SIZE` values each.
+/// Scalars are read in one go.
+fn string_chunks(d: &Dataset) -> Vec<Option<hdf5::Selection>> {
+    let shape = d.shape();
+    if shape.is_empty() {
+        return vec![None];
+    }
+    let row_len = shape[1..].iter().product::<usize>().max(1);
+    let rows_per_chunk = (STRING_CHUNK_SIZE / row_len).max(1);
+    (0..shape[0])
+        .step_by(rows_per_chunk)
+        .map(|start| {
+            let mut selection: Vec<hdf5::SliceOrIndex> =
+                vec![(start..(start + rows_per_chunk).min(shape[0])).into()];
+            selection.extend(shape[1..].iter().map(|_| hdf5::SliceOrIndex::from(..)));
+            Some(hdf5::Hyperslab::from(selection).into())
+        })
+        .collect()
+}
+
+fn read_strings<T: H5Type>(
+    d: &Dataset,
+    selection: Option<hdf5::Selection>,
+) -> hdf5::Result<Vec<T>> {
+    match selection {
+        Some(selection) => Ok(d
+            .read_slice::<T, _, ndarray::IxDyn>(selection)?
+            .into_raw_vec_and_offset()
+            .0),
+        None => d.read_raw(),
+    }
+}
+
+/// Fixed length strings end at the first NUL, space padded ones also lose their trailing spaces.
+fn decode_fixed_string(bytes: &[u8], space_padded: bool) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let s = String::from_utf8_lossy(&bytes[..end]);
+    if space_padded {
+        s.trim_end_matches(' ').to_owned()
+    } else {
+        s.into_owned()
+    }
+}
+
+/// Preview, lengths and most frequent values of a string dataset, read a chunk at a time.
+fn analysis_strings(
+    d: Arc<Dataset>,
+    descriptor: &TypeDescriptor,
+) -> Result<AnalysisResult, Box<dyn Error>> {
+    let mut stats = StringStats::new(d.size());
+    let dtype = d.dtype()?;
+    let space_padded =
+        hdf5::sync::sync(|| unsafe { H5Tget_strpad(dtype.id()) == H5T_str_t::H5T_STR_SPACEPAD });
+    for selection in string_chunks(&d) {
+        match descriptor {
+            TypeDescriptor::VarLenAscii => {
+                for s in read_strings::<VarLenAscii>(&d, selection)? {
+                    stats.add(&s);
+                }
+            }
+            TypeDescriptor::VarLenUnicode => {
+                for s in read_strings::<VarLenUnicode>(&d, selection)? {
+                    stats.add(&s);
+                }
+            }
+            // Fixed length strings can't be converted to other lengths, so they are read as they are stored
+            _ => {
+                let bytes = read_bytes(&d, &dtype, selection)?;
+                for s in bytes.chunks_exact(dtype.size()) {
+                    stats.add(&decode_fixed_string(s, space_padded));
+                }
+            }
+        }
+    }
+    let (info, plot_data) = stats.finish();
+    Ok(AnalysisResult::Stats(info, plot_data))
+}
+
+/// Where each reference points, there is nothing to plot.
+fn analysis_references(d: &Dataset) -> Result<AnalysisResult, Box<dyn Error>> {
+    match references::read_references(d)? {
+        Some(targets) => Ok(AnalysisResult::Stats(
+            references::reference_stats(&targets),
+            Arc::new(PlotData::default()),
+        )),
+        None => Ok(AnalysisResult::NotAvailable),
+    }
+}
+
+/// Stats and plots of the dataset, as set by `options`.
 pub fn hdf5_dataset_analysis_from_path(
     file_path: &PathBuf,
     dataset_path: &str,
+    options: AnalysisOptions,
 ) -> Result<AnalysisResult, Box<dyn Error>> {
+    dataset_analysis(file_path, dataset_path, options, None)
+}
+
+/// Another heatmap slice of a dataset analysed before, with the `summary` rows of that
+/// analysis so only the slice is read.
+pub fn hdf5_heatmap_slice_from_path(
+    file_path: &PathBuf,
+    dataset_path: &str,
+    options: AnalysisOptions,
+    summary: Vec<(String, String)>,
+) -> Result<AnalysisResult, Box<dyn Error>> {
+    dataset_analysis(file_path, dataset_path, options, Some(summary))
+}
+
+fn dataset_analysis(
+    file_path: &PathBuf,
+    dataset_path: &str,
+    options: AnalysisOptions,
+    summary: Option<Vec<(String, String)>>,
+) -> Result<AnalysisResult, Box<dyn Error>> {
+    let exclude_fill_values = options.exclude_fill_values;
     let file = h5_utils::open_file(file_path)?;
     let dataset = file.dataset(dataset_path)?;
     let d = Arc::new(dataset);
 
     let dtype = d.dtype()?;
-    if d.ndim() != 1 || d.size() == 0 {
+    let descriptor = dtype.to_descriptor();
+    if let Ok(
+        descriptor @ (TypeDescriptor::FixedAscii(_)
+        | TypeDescriptor::FixedUnicode(_)
+        | TypeDescriptor::VarLenAscii
+        | TypeDescriptor::VarLenUnicode),
+    ) = &descriptor
+    {
+        if d.size() > 0 {
+            return analysis_strings(d, descriptor);
+        }
+    }
+    if references::reference_descriptor(&dtype).is_some() && d.size() > 0 {
+        return analysis_references(&d);
+    }
+    if d.size() > 0 {
+        match &descriptor {
+            Ok(TypeDescriptor::FixedArray(base, len)) => {
+                return analysis_fixed_array(&d, base, *len)
+            }
+            Ok(TypeDescriptor::VarLenArray(base)) => return analysis_ragged(&d, base),
+            Err(_) if h5_utils::raw_datatype_text(&dtype).is_some() => {
+                return analysis_opaque(&d, &dtype)
+            }
+            _ => {}
+        }
+    }
+
+    if d.ndim() == 0 || d.size() == 0 {
         log::info!(
-            "Dataset is not 1D or is empty: ndim: {}, size: {}",
+            "Dataset is scalar or empty: ndim: {}, size: {}",
             d.ndim(),
             d.size()
         );
         return Ok(AnalysisResult::NotAvailable);
     }
 
-    log::info!("Dataset dtype: {:?}", dtype.to_descriptor());
-    let result = if dtype.is::<f32>() {
-        analysis_1d::<f32>(d)
+    log::info!("Dataset dtype: {:?}", descriptor);
+    if dtype.is::<f32>() {
+        analysis_for::<f32>(d, options, summary)
     } else if dtype.is::<f64>() {
-        analysis_1d::<f64>(d)
+        analysis_for::<f64>(d, options, summary)
+    } else if dtype.is::<f16>() {
+        analysis_for::<f16>(d, options, summary)
     } else if dtype.is::<i8>() {
-        analysis_1d::<i8>(d)
+        analysis_for::<i8>(d, options, summary)
     } else if dtype.is::<u8>() {
-        analysis_1d::<u8>(d)
+        analysis_for::<u8>(d, options, summary)
     } else if dtype.is::<i16>() {
-        analysis_1d::<i16>(d)
+        analysis_for::<i16>(d, options, summary)
     } else if dtype.is::<u16>() {
-        analysis_1d::<u16>(d)
+        analysis_for::<u16>(d, options, summary)
     } else if dtype.is::<i32>() {
-        analysis_1d::<i32>(d)
+        analysis_for::<i32>(d, options, summary)
     } else if dtype.is::<u32>() {
-        analysis_1d::<u32>(d)
+        analysis_for::<u32>(d, options, summary)
     } else if dtype.is::<i64>() {
-        analysis_1d::<i64>(d)
+        analysis_for::<i64>(d, options, summary)
     } else if dtype.is::<u64>() {
-        analysis_1d::<u64>(d)
+        analysis_for::<u64>(d, options, summary)
     } else if dtype.is::<bool>() {
-        analysis_1d::<bool>(d)
+        analysis_for::<bool>(d, options, summary)
+    } else if dtype.is::<Complex<f32>>() {
+        analysis_complex::<f32>(d, exclude_fill_values)
+    } else if dtype.is::<Complex<f64>>() {
+        analysis_complex::<f64>(d, exclude_fill_values)
+    } else if let Ok(TypeDescriptor::Enum(enum_type)) = &descriptor {
+        analysis_enum(d, enum_type)
+    } else if let Ok(TypeDescriptor::Compound(compound)) = &descriptor {
+        analysis_compound(d, compound, exclude_fill_values)
     } else {
         Ok(AnalysisResult::NotAvailable)
-    };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compound_fields() {
+        let dummy = h5_utils::dummy_file();
+
+        let Ok(AnalysisResult::Fields(fields)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "group1/particles",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected per-field analysis of the particles");
+        };
+        // The string label is left out
+        let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["position.x", "position.y", "charge"]);
+        let stat = |field: &FieldAnalysis, key: &str| {
+            field
+                .stats
+                .iter()
+                .find(|(k, _)| k == key)
+                .unwrap()
+                .1
+                .clone()
+        };
+        assert_eq!(stat(&fields[1], "Min"), "-99 at [99]");
+        assert_eq!(stat(&fields[2], "Distinct values"), "3");
+        assert!(fields[0].plots.line.is_some());
+
+        let Ok(AnalysisResult::Fields(fields)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "group1/pixels",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected per-field analysis of the pixels");
+        };
+        assert_eq!(fields.len(), 53);
+        assert_eq!(fields[2].name, "color");
+        assert_eq!(stat(&fields[2], "Member counts"), "R: 2\nG: 2\nB: 2");
+        assert_eq!(stat(&fields[0], "Max"), "6 at [5]");
+        // The pixels are 2D, so there's nothing to draw a line of
+        assert!(fields[0].plots.line.is_none());
+        assert!(fields[0].plots.histogram.is_some());
+    }
+
+    #[test]
+    fn test_enum_dataset() {
+        let dummy = h5_utils::dummy_file();
+
+        let Ok(AnalysisResult::Stats(stats, plots)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "group1/colors",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected stats of the colors");
+        };
+        let row = |key: &str| stats.iter().find(|(k, _)| k == key).unwrap().1.clone();
+        assert_eq!(row("Member counts"), "R: 10\nG: 10\nB: 30");
+        assert!(row("Data preview").starts_with("[R, G, B, B, B, R,"));
+        assert_eq!(plots.available(), vec![PlotKind::Counts]);
+
+        let file = File::open(&dummy.path).unwrap();
+        let pixels = file.dataset("group1/pixels").unwrap();
+        assert_eq!(h5_utils::attributes_text(&pixels), "colors: [R, G, B]");
+        assert_eq!(
+            h5_utils::type_descriptor_to_text(
+                pixels
+                    .attr("colors")
+                    .unwrap()
+                    .dtype()
+                    .unwrap()
+                    .to_descriptor()
+                    .unwrap()
+            ),
+            "enum (uint8) { R = 1, G = 2, B = 3 }"
+        );
+    }
+
+    #[test]
+    fn test_half_and_complex_datasets() {
+        let dummy = h5_utils::dummy_file();
+
+        let Ok(AnalysisResult::Stats(stats, plots)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "group1/half_ds",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected stats of the halves");
+        };
+        let row = |stats: &[(String, String)], key: &str| {
+            stats.iter().find(|(k, _)| k == key).unwrap().1.clone()
+        };
+        assert_eq!(row(&stats, "Max"), "12.375 at [99]");
+        assert!(plots.histogram.is_some());
+
+        let Ok(AnalysisResult::Fields(fields)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "group1/complex_ds",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected per-part analysis of the complex numbers");
+        };
+        let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["magnitude", "phase", "real", "imaginary"]);
+        assert_eq!(
+            row(&fields[0].stats, "Mean"),
+            row(&fields[0].stats, "Median")
+        );
+        assert!(row(&fields[0].stats, "Data preview").starts_with("[1+0i, "));
+        assert!(row(&fields[1].stats, "Max").starts_with("3.1"));
+    }
+
+    #[test]
+    fn test_string_datasets() {
+        let dummy = h5_utils::dummy_file();
+        let stats = |dataset: &str| match hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            dataset,
+            AnalysisOptions::default(),
+        ) {
+            Ok(AnalysisResult::Stats(stats, _)) => stats,
+            other => panic!("Expected stats of {}, got {:?}", dataset, other),
+        };
+        let row = |stats: &[(String, String)], key: &str| {
+            stats.iter().find(|(k, _)| k == key).unwrap().1.clone()
+        };
+
+        // Fixed length and scalar
+        let fixed = stats("group1/string_dataset");
+        assert_eq!(row(&fixed, "Preview"), "\"asdfg\"");
+
+        let names = stats("group1/names");
+        assert_eq!(row(&names, "Empty strings"), "40");
+        assert_eq!(row(&names, "Distinct strings"), "4");
+        assert_eq!(row(&names, "Max length"), "6");
+        assert!(row(&names, "Most frequent").starts_with("80 × \"ada\""));
+    }
+
+    #[test]
+    fn test_array_and_opaque_datasets() {
+        let dummy = h5_utils::dummy_file();
+        let row = |stats: &[(String, String)], key: &str| {
+            stats.iter().find(|(k, _)| k == key).unwrap().1.clone()
+        };
+
+        let Ok(AnalysisResult::Fields(fields)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "group1/ragged",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected fields for the ragged dataset");
+        };
+        assert_eq!(fields[0].name, "lengths");
+        assert_eq!(row(&fields[0].stats, "Mean"), "2.0");
+        let preview = row(&fields[0].stats, "Data preview");
+        assert!(preview.starts_with("[0] (len 0) []\n[1] (len 1) [0]\n[2] (len 2) [0, 1]"));
+        assert!(preview.ends_with("… 10 more"));
+        assert_eq!(fields[1].name, "values");
+        assert!(row(&fields[1].stats, "Max").starts_with("3 at"));
+
+        // Negative members of a signed enum are matched by their bits, as in fixed size arrays
+        let Ok(AnalysisResult::Fields(fields)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "group1/ragged_steps",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected fields for the ragged enum dataset");
+        };
+        assert!(row(&fields[0].stats, "Data preview")
+            .starts_with("[0] (len 0) []\n[1] (len 1) [Stay]\n[2] (len 2) [Forward, Back]"));
+        assert_eq!(
+            row(&fields[1].stats, "Member counts"),
+            "Back: 5\nStay: 4\nForward: 4"
+        );
+        assert!(fields[1]
+            .stats
+            .iter()
+            .all(|(key, _)| key != "Values not in the enum"));
+
+        let Ok(AnalysisResult::Stats(vectors, plots)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "group1/vectors",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected stats for the fixed array dataset");
+        };
+        assert!(row(&vectors, "Data preview").starts_with("[0] [0, -0, 0.5]\n[1] [1, -1, 0.5]"));
+        assert!(plots.line.is_none());
+
+        let Ok(AnalysisResult::Stats(opaque, _)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "group1/opaque",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected stats for the opaque dataset");
+        };
+        assert_eq!(row(&opaque, "Tag"), "pixel checksum");
+        assert!(row(&opaque, "Data preview").starts_with("[0] 00 25 4a 6f\n"));
 
-    result
+        let file = File::open(&dummy.path).unwrap();
+        let text =
+            |name: &str| h5_utils::datatype_to_text(&file.dataset(name).unwrap().dtype().unwrap());
+        assert_eq!(
+            text("group1/opaque"),
+            "opaque (4 bytes, tag \"pixel checksum\")"
+        );
+        assert_eq!(text("group1/bitfield"), "bitfield (16 bits)");
+    }
+
+    #[test]
+    fn test_summary_strides() {
+        assert_eq!(summary_strides(&[10, 100, 100]), vec![1, 1, 1]);
+        // Rows too wide for a block are sampled too, not only the first dimension
+        assert_eq!(summary_strides(&[3, 10_000_000]), vec![1, 16]);
+        assert_eq!(summary_strides(&[4, 2000, 2000]), vec![1, 2, 2]);
+        assert_eq!(summary_strides(&[100_000, 1000]), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_fill_values_excluded() {
+        let dummy = h5_utils::dummy_file();
+        let stats = |dataset: &str, exclude: bool| match hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            dataset,
+            AnalysisOptions {
+                exclude_fill_values: exclude,
+                ..Default::default()
+            },
+        ) {
+            Ok(AnalysisResult::Stats(stats, plots)) => (stats, plots),
+            other => panic!("Expected stats of {}, got {:?}", dataset, other),
+        };
+        let row = |stats: &[(String, String)], key: &str| {
+            stats.iter().find(|(k, _)| k == key).unwrap().1.clone()
+        };
+
+        // Frames 2 to 9 were never written and read as 0, like the first pixel of each frame
+        let (all, _) = stats("variable", false);
+        assert_eq!(row(&all, "Mean"), "9909.9");
+        let (written, plots) = stats("variable", true);
+        assert_eq!(row(&written, "Fill values excluded"), "80_002 equal to 0");
+        assert_eq!(
+            row(&written, "Mean"),
+            num_utils::float_fmt(49_549.5 * 20_000.0 / 19_998.0)
+        );
+        assert_eq!(row(&written, "Min"), "1.0");
+        let heatmap = plots.heatmap.as_ref().unwrap();
+        assert!(heatmap.values[0].is_nan());
+
+        let (all, _) = stats("fill/resized", false);
+        assert_eq!(row(&all, "Min"), "-1 at [2]");
+        let (written, plots) = stats("fill/resized", true);
+        assert_eq!(row(&written, "Fill values excluded"), "6 equal to -1");
+        assert_eq!(row(&written, "Mean"), "4.0");
+        assert_eq!(row(&written, "Max"), "5 at [1]");
+        assert_eq!(plots.line.as_ref().unwrap().len, 8);
+
+        // Not compared to the fill value, which the stats say instead of silently including them
+        for dataset in ["group1/particles", "group1/complex_ds"] {
+            let Ok(AnalysisResult::Fields(fields)) = hdf5_dataset_analysis_from_path(
+                &dummy.path,
+                dataset,
+                AnalysisOptions {
+                    exclude_fill_values: true,
+                    ..Default::default()
+                },
+            ) else {
+                panic!("Expected fields of {}", dataset);
+            };
+            assert!(fields
+                .iter()
+                .all(|field| field.stats[0].0 == "Fill values excluded"
+                    && field.stats[0].1.starts_with("Not supported for ")));
+        }
+        let Ok(AnalysisResult::Fields(fields)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "group1/particles",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected fields of the particles");
+        };
+        assert!(fields[0]
+            .stats
+            .iter()
+            .all(|(key, _)| key != "Fill values excluded"));
+    }
+
+    #[test]
+    fn test_heatmap_slice() {
+        let dummy = h5_utils::dummy_file();
+        let heatmap = |heatmap_slice: usize| {
+            let options = AnalysisOptions {
+                heatmap_slice,
+                ..Default::default()
+            };
+            match hdf5_dataset_analysis_from_path(&dummy.path, "variable", options) {
+                Ok(AnalysisResult::Stats(stats, plots)) => {
+                    let row = stats.iter().find(|(k, _)| k == "Heatmap slice").unwrap();
+                    (row.1.clone(), plots.heatmap.clone().unwrap())
+                }
+                other => panic!("Expected stats, got {:?}", other),
+            }
+        };
+
+        assert_eq!(num_heatmap_slices(&[10, 100, 100]), 10);
+        assert_eq!(num_heatmap_slices(&[2, 3, 4, 5]), 6);
+        assert_eq!(num_heatmap_slices(&[100, 100]), 1);
+
+        let (row, written) = heatmap(1);
+        assert_eq!(row, "[1, :, :] (2 of 10)");
+        assert_eq!(written.slice, "[1, :, :]");
+        assert!(written.values.iter().any(|&v| v != 0.0));
+        // Frames after the second were never written
+        let (row, unwritten) = heatmap(2);
+        assert_eq!(row, "[2, :, :] (3 of 10)");
+        assert!(unwritten.values.iter().all(|&v| v == 0.0));
+        // Past the end is the last slice
+        assert_eq!(heatmap(99).0, "[9, :, :] (10 of 10)");
+
+        // Another slice keeps the summary it is given
+        let Ok(AnalysisResult::Stats(stats, _)) =
+            hdf5_dataset_analysis_from_path(&dummy.path, "variable", AnalysisOptions::default())
+        else {
+            panic!("Expected stats of the first slice");
+        };
+        let summary = summary_rows(&stats).to_vec();
+        assert_eq!(summary.last().unwrap().0, "Max");
+        let options = AnalysisOptions {
+            heatmap_slice: 2,
+            ..Default::default()
+        };
+        let Ok(AnalysisResult::Stats(slice_stats, plots)) =
+            hdf5_heatmap_slice_from_path(&dummy.path, "variable", options, summary.clone())
+        else {
+            panic!("Expected stats of the third slice");
+        };
+        assert_eq!(summary_rows(&slice_stats), summary);
+        assert_eq!(slice_stats[summary.len()].1, "[2, :, :] (3 of 10)");
+        assert_eq!(plots.heatmap.as_ref().unwrap().slice, "[2, :, :]");
+    }
 }
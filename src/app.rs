@@ -1,30 +1,22 @@
-use crate::analysis;
-use crate::analysis::AnalysisResult;
+use crate::command_palette::CommandPalette;
+use crate::config::{self, Config, HelpOnStartup};
 use crate::events;
+use crate::file_browser::FileBrowser;
+use crate::file_tab::{DatasetView, FileTab, ObjectInfo};
 use crate::h5_utils;
-use crate::num_utils;
-use crate::tree::TreeNode;
+use crate::keymap::Action;
+use crate::session::SessionStore;
 use crate::ui::ui;
 use crossterm::event::{MouseButton, MouseEventKind};
 use hdf5_metno as hdf5;
 use std::io::{stdout, Write};
 use std::time::Instant;
 
-use chrono::{DateTime, Local};
-use core::panic;
 use crossterm::event::{KeyCode, KeyModifiers};
-use dirs;
-use log;
-use nix::sys::wait::waitpid;
-use nix::unistd::{fork, ForkResult};
 use ratatui::layout::{Position, Rect};
-use std::collections::HashMap;
-use std::fs;
-use std::os::unix::fs::PermissionsExt;
+use std::ops::Range;
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use std::vec;
-use tokio;
+use std::sync::Arc;
 use tokio::sync::Semaphore;
 
 #[derive(Debug, Clone)]
@@ -39,12 +31,6 @@ impl PartialEq for Hdf5Object {
     }
 }
 
-#[derive(Debug)]
-enum AsyncDataAnalysis {
-    Loading,
-    Ready(analysis::AnalysisResult),
-}
-
 pub type NodeIdT = hdf5_metno_sys::h5i::hid_t;
 
 #[derive(Debug, PartialEq)]
@@ -56,359 +42,343 @@ pub enum AppFinishingState {
 pub enum KeyPressResult {
     Redraw,
     DontRedraw,
-    RunPostCommand(Option<String>, String),
+    RunPostCommand(Option<String>, PathBuf, String),
 }
 
 pub struct App {
     running: AppFinishingState,
-    pub h5_file_path: PathBuf,
-    pub tree_state: tui_tree_widget::TreeState<NodeIdT>,
-    pub tree_state_last_rendered_selected: Option<Vec<NodeIdT>>,
-    pub tree: Option<TreeNode<NodeIdT>>,
-    pub filtered_tree: Option<TreeNode<NodeIdT>>,
-    pub search_query_left: String,
-    pub search_query_right: String,
-    pub search_query_view_offset: u16,
+    pub tabs: Vec<FileTab>,
+    pub active_tab: usize,
+    pub file_browser: FileBrowser,
+    pub command_palette: CommandPalette,
+    pub session_store: SessionStore,
+    event_sender: Option<tokio::sync::mpsc::UnboundedSender<events::Event>>,
     pub mode: SelectionMode,
     pub show_logs: bool,
-    pub object_info_scroll_state: u16,
     last_object_info_area: Rect,
     pub last_object_info_table_area: Rect,
     pub object_info_row_keys: Vec<String>,
     last_tree_area: Rect,
     last_search_query_area: Rect,
     last_help_screen_area: Rect,
+    last_tab_bar_area: Rect,
+    pub last_file_browser_table_area: Rect,
+    pub last_command_palette_table_area: Rect,
+    pub tab_title_ranges: Vec<Range<u16>>,
     pub animation_state: u8,
-    node_id_to_analysis: Arc<Mutex<HashMap<NodeIdT, AsyncDataAnalysis>>>,
     pub help_screen_scroll_state: u16,
     process_semaphore: Arc<Semaphore>,
     pub last_time_had_analysis_tasks: Option<std::time::Instant>,
-    pub tree_width_percentage: u16,
     pub is_dragging_divider: bool,
     last_redraw_from_scroll: Instant,
-    pub hovered_node: Option<Vec<NodeIdT>>,
     pub last_click_time: Option<Instant>,
     pub last_click_position: Option<Position>,
-    pub copied_indicator: Option<(Vec<NodeIdT>, std::time::Instant)>,
     pub copied_object_info_indicator: Option<(String, std::time::Instant)>,
     pub hovered_object_info_key: Option<String>,
+    pub pending_mark: Option<PendingMark>,
+    /// Referenced paths waiting for the key that picks one.
+    pub pending_reference: Option<Vec<String>>,
+    pub last_heatmap_area: Rect,
+    /// Mouse position over the heatmap, to show the value under it.
+    pub hovered_heatmap_position: Option<Position>,
 }
 
+/// A mark command waiting for the letter that names the mark.
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub enum SelectionMode {
-    TreeBrowsing,
-    SearchQueryEditing,
-    ObjectInfoInspecting,
-    HelpScreen,
+pub enum PendingMark {
+    Set,
+    Jump,
 }
 
-fn get_text_for_dataset(tree_node: &TreeNode<NodeIdT>) -> Vec<(String, String)> {
-    let dataset = match tree_node.hdf5_object.as_ref() {
-        Some(Hdf5Object::Dataset(dataset)) => dataset,
-        _ => panic!("Expected a Dataset, found a Group or None"),
-    };
-
-    let shape = dataset.shape();
-    let datatype: String = dataset
-        .dtype()
-        .and_then(|dt| dt.to_descriptor())
-        .map(|desc| h5_utils::type_descriptor_to_text(desc))
-        .unwrap_or("unknown".to_string());
-
-    let space = dataset
-        .space()
-        .map(|s| format!("{:?}", s))
-        .unwrap_or("unknown".to_string());
-    let chunks = dataset.chunk();
-    let chunk_info = match chunks {
-        Some(chunks) => format!("Chunked ({:?})", chunks),
-        None => "Contiguous".to_string(),
-    };
-
-    // Get compression info
-    let compression = dataset.filters();
-    let compression_info = format!("Filter pipeline: {:?}", compression);
-
-    // Get storage size vs data size
-    let storage_size = dataset.storage_size();
-    let data_size = dataset.size() * dataset.dtype().map_or(0, |dt| dt.size());
-    let data_size: u64 = data_size.try_into().unwrap_or(0);
-    let compression_ratio = if storage_size > 0 {
-        data_size as f64 / storage_size as f64
+/// Key that picks the `index`th reference offered by `follow-reference`: 1-9, then a-z.
+pub fn reference_key(index: usize) -> char {
+    if index < 9 {
+        char::from(b'1' + index as u8)
     } else {
-        f64::NAN
-    };
-
-    let mut res = vec![];
-
-    res.push(("Path".to_string(), dataset.name().to_string()));
-    res.push(("Shape".to_string(), format!("{:?}", shape)));
-    res.push(("Space".to_string(), space));
-    res.push(("Chunk info".to_string(), chunk_info));
-    res.push(("Compression".to_string(), compression_info));
-    res.push((
-        "Storage size".to_string(),
-        format!(
-            "{} ({})",
-            num_utils::file_size_fmt(storage_size),
-            num_utils::file_size_fmt_no_scale(storage_size)
-        ),
-    ));
-    res.push((
-        "Data size".to_string(),
-        format!(
-            "{} ({})",
-            num_utils::file_size_fmt(data_size),
-            num_utils::file_size_fmt_no_scale(data_size)
-        ),
-    ));
-    res.push((
-        "Compression ratio".to_string(),
-        format!("{:.2}", compression_ratio),
-    ));
-    res.push(("Datatype".to_string(), datatype));
-    res
+        char::from(b'a' + (index - 9) as u8)
+    }
 }
 
-fn get_text_for_group(tree_node: &TreeNode<NodeIdT>) -> Vec<(String, String)> {
-    let group = match tree_node.hdf5_object.as_ref() {
-        Some(Hdf5Object::Group(group)) => group,
-        _ => panic!("Expected a Group, found a Dataset or None"),
-    };
-
-    let num_groups = group.groups().unwrap_or(vec![]).len();
-    let num_datasets = group.datasets().unwrap_or(vec![]).len();
-    let attrs = group.attr_names().unwrap_or(vec![]);
-    let num_attrs = attrs.len();
-
-    let mut res = vec![];
-    res.push(("Path".to_string(), group.name().to_string()));
-    res.push((
-        "Number of groups direct".to_string(),
-        num_groups.to_string(),
-    ));
-    res.push((
-        "Number of groups total".to_string(),
-        format!("{}", tree_node.recursive_num_groups),
-    ));
-    res.push((
-        "Number of datasets direct".to_string(),
-        num_datasets.to_string(),
-    ));
-    res.push((
-        "Number of datasets total".to_string(),
-        tree_node.recursive_num_datasets.to_string(),
-    ));
-    res.push(("Number of attributes".to_string(), num_attrs.to_string()));
-    res.push(("Attribute names".to_string(), format!("{:?}", attrs)));
-    res.push((
-        "Storage size".to_string(),
-        format!(
-            "{} ({})",
-            num_utils::file_size_fmt(tree_node.recursive_storage_data_size),
-            num_utils::file_size_fmt_no_scale(tree_node.recursive_storage_data_size)
-        ),
-    ));
-    res
+fn reference_key_index(key: char) -> Option<usize> {
+    match key {
+        '1'..='9' => Some(key as usize - '1' as usize),
+        'a'..='z' => Some(key as usize - 'a' as usize + 9),
+        _ => None,
+    }
 }
 
-impl App {
-    pub const NUM_ANALYSIS_PERMITS: usize = 64;
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SelectionMode {
+    TreeBrowsing,
+    SearchQueryEditing,
+    ObjectInfoInspecting,
+    HelpScreen,
+    FileBrowsing,
+    CommandPalette,
+}
 
-    pub fn new(h5_file_path: PathBuf) -> App {
-        let mut starting_mode = SelectionMode::HelpScreen;
+impl App {
+    pub fn new(h5_file_paths: Vec<PathBuf>, browse_dir: Option<PathBuf>) -> App {
+        let mut starting_mode = if h5_file_paths.is_empty() {
+            SelectionMode::FileBrowsing
+        } else {
+            SelectionMode::HelpScreen
+        };
 
+        let mut app_config_dir = None;
         if let Some(config_dir) = dirs::config_local_dir() {
-            let app_config_dir = config_dir.join("h5inspect");
-            if app_config_dir.exists() {
-                starting_mode = SelectionMode::TreeBrowsing;
+            let dir = config_dir.join("h5inspect");
+            if dir.exists() {
+                if starting_mode == SelectionMode::HelpScreen {
+                    starting_mode = SelectionMode::TreeBrowsing;
+                }
+                app_config_dir = Some(dir);
             } else {
+                starting_mode = SelectionMode::HelpScreen;
                 // create the config dir to mark that the user has run the app at least once
-                if let Err(e) = std::fs::create_dir(&app_config_dir) {
-                    log::error!("Failed to create config dir {:?}: {}", app_config_dir, e);
+                if let Err(e) = std::fs::create_dir(&dir) {
+                    log::error!("Failed to create config dir {:?}: {}", dir, e);
                 } else {
-                    log::info!("Created config dir {:?}", app_config_dir);
+                    log::info!("Created config dir {:?}", dir);
+                    app_config_dir = Some(dir);
                 }
             }
         }
 
+        config::init(Config::load(app_config_dir.as_deref()));
+        match config::get().show_help_on_startup {
+            HelpOnStartup::FirstRun => {}
+            HelpOnStartup::Always => starting_mode = SelectionMode::HelpScreen,
+            HelpOnStartup::Never if starting_mode == SelectionMode::HelpScreen => {
+                starting_mode = if h5_file_paths.is_empty() {
+                    SelectionMode::FileBrowsing
+                } else {
+                    SelectionMode::TreeBrowsing
+                };
+            }
+            HelpOnStartup::Never => {}
+        }
+
+        let mut session_store = SessionStore::load(app_config_dir.as_deref());
+        let tabs = h5_file_paths
+            .into_iter()
+            .map(|path| {
+                let mut tab = FileTab::new(path);
+                if let Some(session) = session_store.file_session(&tab.h5_file_path) {
+                    tab.restore_session(session.clone());
+                }
+                tab
+            })
+            .collect::<Vec<_>>();
+        for tab in tabs.iter().rev() {
+            session_store.add_recent_file(&tab.h5_file_path);
+        }
+
+        // Without any arguments, start by offering the recently opened files
+        let mut file_browser = FileBrowser::new(
+            browse_dir
+                .clone()
+                .or_else(|| std::env::current_dir().ok())
+                .unwrap_or_else(|| PathBuf::from(".")),
+        );
+        if tabs.is_empty() && browse_dir.is_none() && !session_store.recent_files.is_empty() {
+            file_browser.show_recent_files(&session_store.recent_files);
+        }
+
         App {
             running: AppFinishingState::Continue,
-            h5_file_path,
-            tree_state: tui_tree_widget::TreeState::default(),
-            tree_state_last_rendered_selected: None,
-            tree: None,
-            filtered_tree: None,
-            search_query_left: String::new(),
-            search_query_right: String::new(),
-            search_query_view_offset: 0,
+            tabs,
+            active_tab: 0,
+            file_browser,
+            command_palette: CommandPalette::new(),
+            session_store,
+            event_sender: None,
             mode: starting_mode,
-            show_logs: false, //cfg!(debug_assertions),
-            object_info_scroll_state: 0,
+            show_logs: config::get().show_logs,
             last_object_info_area: Rect::new(0, 0, 0, 0),
             last_object_info_table_area: Rect::new(0, 0, 0, 0),
             object_info_row_keys: Vec::new(),
             last_tree_area: Rect::new(0, 0, 0, 0),
             last_search_query_area: Rect::new(0, 0, 0, 0),
             last_help_screen_area: Rect::new(0, 0, 0, 0),
+            last_tab_bar_area: Rect::new(0, 0, 0, 0),
+            last_file_browser_table_area: Rect::new(0, 0, 0, 0),
+            last_command_palette_table_area: Rect::new(0, 0, 0, 0),
+            tab_title_ranges: Vec::new(),
             animation_state: 0,
-            node_id_to_analysis: Arc::new(Mutex::new(HashMap::new())),
             help_screen_scroll_state: 0,
-            process_semaphore: Arc::new(Semaphore::new(App::NUM_ANALYSIS_PERMITS)), // Limit to NUM_ANALYSIS_PERMITS concurrent processes
+            // Limit the number of concurrent analysis processes
+            process_semaphore: Arc::new(Semaphore::new(config::get().max_concurrent_analyses)),
             last_time_had_analysis_tasks: None,
-            tree_width_percentage: 50,
             is_dragging_divider: false,
             last_redraw_from_scroll: Instant::now(),
-            hovered_node: None,
             last_click_time: None,
             last_click_position: None,
-            copied_indicator: None,
             copied_object_info_indicator: None,
             hovered_object_info_key: None,
+            pending_mark: None,
+            pending_reference: None,
+            last_heatmap_area: Rect::new(0, 0, 0, 0),
+            hovered_heatmap_position: None,
         }
     }
 
-    fn tree_from_h5(h5_file: &hdf5::File) -> Result<TreeNode<NodeIdT>, std::io::Error> {
-        fn tree_from_group(group_name: &str, group: hdf5::Group) -> TreeNode<NodeIdT> {
-            // TODO: avoid circular walks
-            // The identifier for each TreeNode is the unmodified hdf5 group/dataset name.
-            // The name is the full path inside the hdf5 file.
-            // This allows us to retrieve the object later
-
-            let mut children: Vec<_> = h5_utils::groups(&group)
-                .unwrap_or(vec![])
-                .into_iter()
-                .map(|(name, child)| tree_from_group(&name, child))
-                .collect();
-
-            let datasets = h5_utils::datasets(&group).unwrap_or(vec![]);
-
-            for (dataset_name, dataset) in datasets.into_iter() {
-                let text = dataset_name.clone();
-                let node_id = dataset.id();
-                children.push(
-                    TreeNode::new(node_id, text, vec![])
-                        .set_storage_dataset_size(dataset.storage_size())
-                        .set_hdf5_object(Hdf5Object::Dataset(Arc::new(dataset))),
-                );
-            }
+    pub fn tab(&self) -> &FileTab {
+        &self.tabs[self.active_tab]
+    }
+
+    pub fn tab_mut(&mut self) -> &mut FileTab {
+        &mut self.tabs[self.active_tab]
+    }
 
-            TreeNode::new(group.id(), group_name, children)
-                .set_hdf5_object(Hdf5Object::Group(group))
+    fn select_tab(&mut self, index: usize) {
+        if index < self.tabs.len() && index != self.active_tab {
+            self.active_tab = index;
+            self.hovered_object_info_key = None;
+            log::debug!("Switched to tab {}: {:?}", index, self.tab().h5_file_path);
         }
-        // TODO anonymous datasets
+    }
 
-        let root_name = "/";
-        let root_group = h5_file.group(root_name).expect("Couldn't open root group");
-        Ok(tree_from_group(root_name, root_group))
+    fn next_tab(&mut self) {
+        self.select_tab((self.active_tab + 1) % self.tabs.len());
     }
 
-    pub fn get_num_active_data_analysis_tasks(&self) -> usize {
-        let info_dict = self.node_id_to_analysis.lock().unwrap();
-        info_dict
-            .values()
-            .filter(|&v| matches!(v, AsyncDataAnalysis::Loading))
-            .count()
+    fn prev_tab(&mut self) {
+        self.select_tab((self.active_tab + self.tabs.len() - 1) % self.tabs.len());
     }
 
-    pub fn get_text_for(
-        &self,
-        path: &[NodeIdT],
-    ) -> Option<(Vec<(String, String)>, Option<analysis::HistogramData>)> {
-        let tree = match self.tree.as_ref() {
-            Some(tree) => tree,
-            None => return None,
-        };
+    /// The mode to fall back to when leaving the help screen or the file browser.
+    fn default_mode(&self) -> SelectionMode {
+        if self.tabs.is_empty() {
+            SelectionMode::FileBrowsing
+        } else {
+            SelectionMode::TreeBrowsing
+        }
+    }
 
-        let tree_node = match tree.get_selected_node(path) {
-            Some(node) => node,
-            None => return None,
-        };
+    /// The mode whose layout is drawn, which is hidden under the command palette while it is open.
+    pub fn base_mode(&self) -> SelectionMode {
+        match self.mode {
+            SelectionMode::CommandPalette => self.command_palette.return_mode,
+            mode => mode,
+        }
+    }
 
-        let obj = match tree_node.hdf5_object.as_ref() {
-            Some(obj) => obj,
-            None => return None,
+    fn open_command_palette(&mut self) {
+        let return_mode = match self.mode {
+            SelectionMode::SearchQueryEditing => SelectionMode::TreeBrowsing,
+            SelectionMode::CommandPalette => return,
+            mode => mode,
         };
+        self.command_palette.open(return_mode);
+        self.mode = SelectionMode::CommandPalette;
+    }
 
-        match obj {
-            Hdf5Object::Dataset(_) => {
-                let mut info = get_text_for_dataset(&tree_node);
-
-                let key = tree_node.id().clone();
-
-                let mut stats_text: Vec<(String, String)> = vec![];
-                let mut hist_data: Option<analysis::HistogramData> = None;
+    fn run_command_palette_selection(&mut self) -> KeyPressResult {
+        let action = self.command_palette.selected_action();
+        self.mode = self.command_palette.return_mode;
+        match action {
+            Some(action) => {
+                log::debug!("Running {} from the command palette", action.name());
+                self.on_action(action)
+            }
+            None => KeyPressResult::Redraw,
+        }
+    }
 
-                let info_dict = self.node_id_to_analysis.lock().unwrap();
+    fn open_file_browser(&mut self) {
+        if let Some(dir) = self
+            .tabs
+            .get(self.active_tab)
+            .and_then(|tab| tab.h5_file_path.parent())
+            .filter(|dir| dir.is_dir())
+        {
+            if dir != self.file_browser.dir {
+                self.file_browser.change_dir(dir.to_path_buf());
+            }
+        }
+        self.file_browser.refresh_keeping_selection();
+        self.mode = SelectionMode::FileBrowsing;
+    }
 
-                if let Some(node_info) = info_dict.get(&key) {
-                    match node_info {
-                        AsyncDataAnalysis::Loading => {
-                            stats_text = vec![(
-                                "Stats".into(),
-                                "Loading".to_owned()
-                                    + &".".repeat((self.animation_state / 3 % 4).into()),
-                            )]
-                        }
-                        AsyncDataAnalysis::Ready(val) => match val {
-                            analysis::AnalysisResult::Failed(s) => {
-                                stats_text = vec![("Stats".into(), format!("Failed! ({})", s))];
-                            }
-                            analysis::AnalysisResult::NotAvailable => {
-                                stats_text = vec![("Stats".into(), "Not available".into())];
-                            }
-                            analysis::AnalysisResult::Stats(stats, h) => {
-                                stats_text = stats.to_vec();
-                                hist_data = h.to_owned();
-                            }
-                        },
-                    }
-                }
+    fn spawn_tree_load(&self, tab_index: usize, h5_file: hdf5::File) {
+        let Some(sender) = self.event_sender.clone() else {
+            return;
+        };
+        tokio::task::spawn_blocking(move || {
+            let tree = FileTab::tree_from_h5(&h5_file).expect("Failed to parse HDF5 structure");
+            let tree_update = events::Event::TreeUpdate(tab_index, tree);
+            sender.send(tree_update).unwrap();
+        });
+    }
 
-                info.extend(stats_text);
-
-                Some((info, hist_data))
-            }
-            Hdf5Object::Group(_) => {
-                let mut info = get_text_for_group(&tree_node);
-
-                // If this is the root group, add file info
-                if let Some(Hdf5Object::Group(group)) = tree_node.hdf5_object.as_ref() {
-                    if group.name() == "/" {
-                        let path = &self.h5_file_path;
-                        if let Ok(metadata) = fs::metadata(path) {
-                            if let Ok(modified) = metadata.modified() {
-                                // Convert SystemTime -> chrono::DateTime<Local>
-                                let datetime: DateTime<Local> = DateTime::<Local>::from(modified);
-                                info.push((
-                                    "File last modified".to_string(),
-                                    datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
-                                ));
-                            }
+    /// Open `path` in a new tab, or switch to its tab if it is already open.
+    fn open_file_in_tab(&mut self, path: PathBuf) {
+        let canonical = path.canonicalize().unwrap_or(path.clone());
+        if let Some(index) = self
+            .tabs
+            .iter()
+            .position(|tab| tab.h5_file_path.canonicalize().ok().as_ref() == Some(&canonical))
+        {
+            self.select_tab(index);
+            self.mode = SelectionMode::TreeBrowsing;
+            return;
+        }
 
-                            // Permissions
-                            let perms = metadata.permissions();
-                            #[cfg(unix)]
-                            {
-                                info.push((
-                                    "File permissions".to_string(),
-                                    format!("{:o}", perms.mode()),
-                                ));
-                            }
-                        }
-                    }
+        match h5_utils::open_file(&path) {
+            Ok(h5_file) => {
+                log::info!("Opening {:?} in a new tab", path);
+                let mut tab = FileTab::new(path);
+                if let Some(session) = self.session_store.file_session(&tab.h5_file_path) {
+                    tab.restore_session(session.clone());
                 }
+                self.tabs.push(tab);
+                let tab_index = self.tabs.len() - 1;
+                self.spawn_tree_load(tab_index, h5_file);
+                self.select_tab(tab_index);
+                self.save_session();
+                self.mode = SelectionMode::TreeBrowsing;
+            }
+            Err(e) => {
+                log::error!("Failed to open {:?}: {}", path, e);
+                self.file_browser.status = Some(format!("Couldn't open {:?}: {}", path, e));
+            }
+        }
+    }
 
-                Some((info, None))
+    /// Remember the open files and where the user is in each of them.
+    fn save_session(&mut self) {
+        for tab in self.tabs.iter().rev() {
+            self.session_store.add_recent_file(&tab.h5_file_path);
+        }
+        // Keep the active file at the top of the recent files
+        if let Some(tab) = self.tabs.get(self.active_tab) {
+            self.session_store.add_recent_file(&tab.h5_file_path);
+        }
+        for tab in &self.tabs {
+            if let Some(session) = tab.session() {
+                self.session_store
+                    .set_file_session(&tab.h5_file_path, session);
             }
         }
+        self.session_store.save();
+    }
+
+    pub fn get_num_active_data_analysis_tasks(&self) -> usize {
+        self.tabs
+            .iter()
+            .map(|tab| tab.get_num_active_data_analysis_tasks())
+            .sum()
+    }
+
+    pub fn get_text_for(&self, path: &[NodeIdT]) -> Option<ObjectInfo> {
+        self.tab().get_text_for(path, self.animation_state)
     }
 
     fn copy_to_clipboard(&self, text: &str) {
         use crossterm::{clipboard::CopyToClipboard, QueueableCommand};
-        use std::io::Write;
         let mut stdout = std::io::stdout();
-        if let Err(e) = stdout.queue(CopyToClipboard::to_clipboard_from(text.to_string())) {
+        if let Err(e) = stdout.queue(CopyToClipboard::to_clipboard_from(text)) {
             log::error!("Failed to copy to clipboard via crossterm: {:?}", e);
         } else if let Err(e) = stdout.flush() {
             log::error!("Failed to flush stdout after clipboard copy: {:?}", e);
@@ -440,6 +410,37 @@ impl App {
             return;
         }
 
+        if self.last_tab_bar_area.contains(position) {
+            if let Some(index) = self
+                .tab_title_ranges
+                .iter()
+                .position(|range| range.contains(&column))
+            {
+                self.select_tab(index);
+                if self.mode == SelectionMode::FileBrowsing {
+                    self.mode = SelectionMode::TreeBrowsing;
+                }
+            }
+            return;
+        }
+
+        if self.mode == SelectionMode::FileBrowsing {
+            let table_area = self.last_file_browser_table_area;
+            if table_area.contains(position) {
+                let clicked_row_idx =
+                    (row - table_area.y) as usize + self.file_browser.table_state.offset();
+                if clicked_row_idx < self.file_browser.entries.len() {
+                    self.file_browser.table_state.select(Some(clicked_row_idx));
+                    if is_double_click {
+                        if let Some(path) = self.file_browser.activate_selected() {
+                            self.open_file_in_tab(path);
+                        }
+                    }
+                }
+            }
+            return;
+        }
+
         // Check if clicking on the divider between tree and object info
         let divider_column = self.last_tree_area.right();
         if column == divider_column || column == divider_column.saturating_sub(1) {
@@ -450,28 +451,19 @@ impl App {
         if self.last_tree_area.contains(position) {
             self.mode = SelectionMode::TreeBrowsing;
 
-            if let Some(id) = self.tree_state.rendered_at(position) {
+            let tab = self.tab_mut();
+            if let Some(id) = tab.tree_state.rendered_at(position) {
                 let arg = id.to_vec();
                 if is_double_click {
                     // Double click: copy path to clipboard using OSC 52
-                    let path_str = self
-                        .tree
-                        .as_ref()
-                        .and_then(|tree| tree.get_selected_node(&arg))
-                        .and_then(|node| match &node.hdf5_object {
-                            Some(Hdf5Object::Dataset(dataset)) => Some(dataset.name().to_string()),
-                            Some(Hdf5Object::Group(group)) => Some(group.name().to_string()),
-                            _ => None,
-                        });
-
-                    if let Some(path) = path_str {
+                    if let Some(path) = tab.object_name_at(&arg) {
+                        tab.copied_indicator = Some((arg, std::time::Instant::now()));
                         self.copy_to_clipboard(&path);
-                        self.copied_indicator = Some((arg.clone(), std::time::Instant::now()));
                     }
                 } else {
                     // Single click: toggle and select
-                    self.tree_state.toggle(arg.clone());
-                    self.tree_state.select(arg);
+                    tab.tree_state.toggle(arg.clone());
+                    tab.tree_state.select(arg);
                 }
             }
             return;
@@ -487,9 +479,9 @@ impl App {
                     || row == area.y
                     || row == area.y + area.height.saturating_sub(1);
 
-                let selected = self.tree_state.selected().to_vec();
+                let selected = self.tab().tree_state.selected().to_vec();
                 if !selected.is_empty() {
-                    if let Some((info, _histogram_data)) = self.get_text_for(&selected) {
+                    if let Some((info, _plot_data)) = self.get_text_for(&selected) {
                         if is_on_border {
                             // Double click on border: copy all text
                             let mut text_to_copy = String::new();
@@ -503,7 +495,7 @@ impl App {
                             let table_area = self.last_object_info_table_area;
                             if table_area.contains(position) {
                                 let clicked_row_idx = (row - table_area.y) as usize
-                                    + self.object_info_scroll_state as usize;
+                                    + self.tab().object_info_scroll_state as usize;
                                 if let Some(k) = self.object_info_row_keys.get(clicked_row_idx) {
                                     if let Some(v) =
                                         info.iter().find(|(key, _)| key == k).map(|(_, val)| val)
@@ -526,56 +518,132 @@ impl App {
         }
     }
 
-    fn on_keypress_tree_mode(&mut self, keycode: crossterm::event::KeyCode) -> KeyPressResult {
-        match keycode {
-            KeyCode::Left => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.key_left();
+    fn on_action_tree_mode(&mut self, action: Action) -> KeyPressResult {
+        let tab = &mut self.tabs[self.active_tab];
+        let has_tree = tab.filtered_tree.is_some();
+        tab.selection_change_is_step = matches!(
+            action,
+            Action::MoveUp | Action::MoveDown | Action::NextItem | Action::PrevItem
+        );
+        match action {
+            Action::MoveLeft if has_tree => {
+                tab.tree_state.key_left();
+            }
+            Action::MoveUp if has_tree => {
+                tab.tree_state.key_up();
+            }
+            Action::MoveDown if has_tree => {
+                tab.tree_state.key_down();
+            }
+            Action::HistoryBack if has_tree => {
+                tab.history_back();
+            }
+            Action::HistoryForward if has_tree => {
+                tab.history_forward();
+            }
+            Action::SetMark if has_tree => {
+                self.pending_mark = Some(PendingMark::Set);
+            }
+            Action::JumpToMark if has_tree => {
+                self.pending_mark = Some(PendingMark::Jump);
+            }
+            Action::FollowReference if has_tree => {
+                let mut paths = tab.referenced_paths();
+                match paths.len() {
+                    0 => tab.set_status("No references to follow".to_string()),
+                    1 => tab.jump_to_object(&paths.remove(0)),
+                    _ => self.pending_reference = Some(paths),
                 }
             }
-            KeyCode::Char('h') => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.key_left();
-                }
+            Action::CyclePlot => {
+                tab.cycle_plot();
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.key_up();
-                }
+            Action::CycleColormap => {
+                tab.colormap = tab.colormap.next();
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.key_down();
-                }
+            Action::ZoomIn => {
+                tab.zoom_plot(0.5);
             }
-            KeyCode::Right | KeyCode::Char('l') => {
-                self.mode = SelectionMode::ObjectInfoInspecting;
+            Action::ZoomOut => {
+                tab.zoom_plot(2.0);
             }
-            KeyCode::Home => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.select_first();
+            Action::PanLeft => {
+                tab.pan_plot(-0.25);
+            }
+            Action::PanRight => {
+                tab.pan_plot(0.25);
+            }
+            Action::MoreBins => {
+                tab.histogram_settings.scale_bins(1.5);
+            }
+            Action::FewerBins => {
+                tab.histogram_settings.scale_bins(1.0 / 1.5);
+            }
+            Action::ToggleLogCounts => {
+                tab.histogram_settings.log_counts = !tab.histogram_settings.log_counts;
+            }
+            Action::CycleHistogramClip => {
+                tab.histogram_settings.cycle_clip();
+            }
+            Action::NextField => {
+                tab.select_field(1);
+            }
+            Action::ToggleHexView => {
+                tab.toggle_dataset_view(DatasetView::Hex);
+            }
+            Action::ToggleChunkMap => {
+                tab.toggle_dataset_view(DatasetView::Chunks);
+            }
+            Action::ToggleFillValues => {
+                let status = if tab.toggle_exclude_fill_values(&self.process_semaphore) {
+                    "Fill values are left out of the stats"
+                } else {
+                    "Fill values are included in the stats"
+                };
+                tab.set_status(status.to_string());
+            }
+            Action::PrevField => {
+                tab.select_field(-1);
+            }
+            Action::NextSlice | Action::PrevSlice => {
+                let offset = if action == Action::NextSlice { 1 } else { -1 };
+                match tab.select_heatmap_slice(offset, &self.process_semaphore) {
+                    Some(1) => tab.set_status("The dataset has a single heatmap slice".to_string()),
+                    Some(_) => {}
+                    None => {
+                        tab.set_status("Select a dataset to pick its heatmap slice".to_string())
+                    }
                 }
             }
-            KeyCode::End => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.select_last();
+            Action::YankHistogram => match tab.selected_histogram() {
+                Some(histogram) => {
+                    let num_bins = histogram.counts.len();
+                    self.copy_to_clipboard(&histogram.to_tsv());
+                    self.tab_mut()
+                        .set_status(format!("Copied {} histogram bins", num_bins));
                 }
+                None => tab.set_status("No histogram to copy".to_string()),
+            },
+            Action::MoveRight => {
+                self.mode = SelectionMode::ObjectInfoInspecting;
+            }
+            Action::GoToTop if has_tree => {
+                tab.tree_state.select_first();
             }
-            KeyCode::Char('i') => {
+            Action::GoToBottom if has_tree => {
+                tab.tree_state.select_last();
+            }
+            Action::RunPostCommand => {
                 let post_cmd = std::env::var("H5INSPECT_POST").ok();
-                let last_path = self
-                    .tree
-                    .as_ref()
-                    .and_then(|tree| tree.get_selected_node(self.tree_state.selected()))
-                    .and_then(|node| match &node.hdf5_object {
-                        Some(Hdf5Object::Dataset(dataset)) => Some(dataset.name().to_string()),
-                        Some(Hdf5Object::Group(group)) => Some(group.name().to_string()),
-                        _ => None,
-                    });
+                let last_path = tab.object_name_at(tab.tree_state.selected());
 
                 match last_path {
                     Some(p) => {
-                        return KeyPressResult::RunPostCommand(post_cmd, p);
+                        return KeyPressResult::RunPostCommand(
+                            post_cmd,
+                            tab.h5_file_path.clone(),
+                            p,
+                        );
                     }
                     None => {
                         log::debug!(
@@ -584,262 +652,281 @@ impl App {
                     }
                 }
             }
-            KeyCode::Enter => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.toggle_selected();
-                }
+            Action::ToggleNode if has_tree => {
+                tab.tree_state.toggle_selected();
             }
-            KeyCode::Char('c') => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.toggle_selected();
-                }
+            Action::NextItem if has_tree => {
+                tab.tree_state
+                    .select_relative(|x| x.map_or(0, |current| current.saturating_add(1)));
             }
-            KeyCode::Tab => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state
-                        .select_relative(|x| x.map_or(0, |current| current.saturating_add(1)));
-                }
+            Action::PrevItem if has_tree => {
+                tab.tree_state
+                    .select_relative(|x| x.map_or(0, |current| current.saturating_sub(1)));
             }
-            KeyCode::BackTab => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state
-                        .select_relative(|x| x.map_or(0, |current| current.saturating_sub(1)));
-                }
+            Action::ExpandAll => {
+                tab.open_all_tree_nodes();
             }
-            KeyCode::Char('f') => {
-                self.open_all_tree_nodes();
-            }
-            KeyCode::Char('y') => {
-                let last_path = self
-                    .tree
-                    .as_ref()
-                    .and_then(|tree| tree.get_selected_node(self.tree_state.selected()))
-                    .and_then(|node| match &node.hdf5_object {
-                        Some(Hdf5Object::Dataset(dataset)) => Some(dataset.name().to_string()),
-                        Some(Hdf5Object::Group(group)) => Some(group.name().to_string()),
-                        _ => None,
-                    });
-
-                if let Some(path) = last_path {
-                    self.copy_to_clipboard(&path);
-                    self.copied_indicator = Some((
-                        self.tree_state.selected().to_vec(),
+            Action::YankPath => {
+                if let Some(path) = tab.object_name_at(tab.tree_state.selected()) {
+                    tab.copied_indicator = Some((
+                        tab.tree_state.selected().to_vec(),
                         std::time::Instant::now(),
                     ));
+                    self.copy_to_clipboard(&path);
                 }
             }
-            KeyCode::Char('g') => {
-                if self.filtered_tree.is_some() {
-                    // it's a lot easier to go the first one this way than to use "gg" like in vim
-                    self.tree_state.select_first();
-                }
-            }
-            KeyCode::Char('G') => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.select_last();
-                }
-            }
-            KeyCode::Char('L') => {
+            Action::ToggleLogs => {
                 self.show_logs = !self.show_logs;
             }
-            KeyCode::Char('?') => {
+            Action::ShowHelp => {
                 self.mode = SelectionMode::HelpScreen;
             }
-            KeyCode::PageDown => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.select_relative(|current| {
-                        current.map_or(0, |current| current.saturating_add(50))
-                    });
-                }
+            Action::NextTab => {
+                self.next_tab();
             }
-            KeyCode::PageUp => {
-                if self.filtered_tree.is_some() {
-                    self.tree_state.select_relative(|current| {
-                        current.map_or(0, |current| current.saturating_sub(50))
-                    });
-                }
+            Action::PrevTab => {
+                self.prev_tab();
+            }
+            Action::OpenFileBrowser => {
+                self.open_file_browser();
+            }
+            Action::PageDown if has_tree => {
+                tab.tree_state.select_relative(|current| {
+                    current.map_or(0, |current| current.saturating_add(50))
+                });
+            }
+            Action::PageUp if has_tree => {
+                tab.tree_state.select_relative(|current| {
+                    current.map_or(0, |current| current.saturating_sub(50))
+                });
+            }
+            Action::Search => {
+                self.mode = SelectionMode::SearchQueryEditing;
+            }
+            Action::Quit => {
+                self.running = AppFinishingState::Quit;
             }
             _ => {}
         }
         KeyPressResult::Redraw
     }
 
-    pub fn search_query_and_cursor(&self) -> (String, u16) {
-        let rev_right: String = self.search_query_right.chars().rev().collect();
-        let text = self.search_query_left.clone() + &rev_right;
-        let cursor_pos = self.search_query_left.len();
-
-        (text, cursor_pos.try_into().unwrap())
-    }
-
-    fn on_keypress_search_mode(&mut self, key: crossterm::event::KeyEvent) -> KeyPressResult {
+    fn on_keypress_search_mode(
+        &mut self,
+        key: crossterm::event::KeyEvent,
+        action: Option<Action>,
+    ) -> KeyPressResult {
         let keycode = key.code;
         let mut refresh_filtered_tree = true;
+        let tab = self.tab_mut();
+        // Text editing keys are fixed, anything else goes through the keymap
         let result = match keycode {
-            KeyCode::Char(to_insert) => {
-                self.search_query_left.push(to_insert);
+            KeyCode::Char(to_insert)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                tab.search_query_left.push(to_insert);
                 KeyPressResult::Redraw
             }
             KeyCode::Left => {
                 if key.modifiers.contains(KeyModifiers::CONTROL) {
                     // Move cursor to start of previous word
-                    while let Some(c) = self.search_query_left.pop() {
-                        self.search_query_right.push(c);
+                    while let Some(c) = tab.search_query_left.pop() {
+                        tab.search_query_right.push(c);
                         if c.is_whitespace() {
                             break;
                         }
                     }
-                } else {
-                    self.search_query_left
-                        .pop()
-                        .map(|c| self.search_query_right.push(c));
+                } else if let Some(c) = tab.search_query_left.pop() {
+                    tab.search_query_right.push(c);
                 }
                 KeyPressResult::Redraw
             }
             KeyCode::Right => {
                 if key.modifiers.contains(KeyModifiers::CONTROL) {
                     // Move cursor to start of next word
-                    while let Some(c) = self.search_query_right.pop() {
-                        self.search_query_left.push(c);
+                    while let Some(c) = tab.search_query_right.pop() {
+                        tab.search_query_left.push(c);
                         if c.is_whitespace() {
                             break;
                         }
                     }
-                } else {
-                    self.search_query_right
-                        .pop()
-                        .map(|c| self.search_query_left.push(c));
+                } else if let Some(c) = tab.search_query_right.pop() {
+                    tab.search_query_left.push(c);
                 }
                 KeyPressResult::Redraw
             }
             KeyCode::Home => {
-                self.search_query_right
-                    .extend(self.search_query_left.drain(..).rev());
+                tab.search_query_right
+                    .extend(tab.search_query_left.drain(..).rev());
                 KeyPressResult::Redraw
             }
             KeyCode::End => {
-                self.search_query_left
-                    .extend(self.search_query_right.drain(..).rev());
+                tab.search_query_left
+                    .extend(tab.search_query_right.drain(..).rev());
                 KeyPressResult::Redraw
             }
             KeyCode::Backspace => {
-                self.search_query_left.pop();
+                tab.search_query_left.pop();
                 KeyPressResult::Redraw
             }
             KeyCode::Delete => {
-                self.search_query_right.pop();
+                tab.search_query_right.pop();
                 KeyPressResult::Redraw
             }
-            other => {
+            _ => {
                 refresh_filtered_tree = false;
-                self.on_keypress_tree_mode(other)
+                match action {
+                    Some(Action::Back | Action::ToggleNode | Action::Search) => {
+                        self.mode = SelectionMode::TreeBrowsing;
+                        KeyPressResult::Redraw
+                    }
+                    Some(other) => self.on_action(other),
+                    None => KeyPressResult::Redraw,
+                }
             }
         };
         if refresh_filtered_tree {
-            self.update_filtered_tree();
+            self.tab_mut().update_filtered_tree();
         }
         result
     }
 
-    fn on_keypress_object_info_mode(
-        &mut self,
-        keycode: crossterm::event::KeyCode,
-    ) -> KeyPressResult {
-        match keycode {
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.object_info_scroll_state = self.object_info_scroll_state.saturating_sub(1);
+    fn on_action_object_info_mode(&mut self, action: Action) -> KeyPressResult {
+        let tab = &mut self.tabs[self.active_tab];
+        match action {
+            Action::MoveUp => {
+                tab.object_info_scroll_state = tab.object_info_scroll_state.saturating_sub(1);
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.object_info_scroll_state = self.object_info_scroll_state.saturating_add(1);
+            Action::MoveDown => {
+                tab.object_info_scroll_state = tab.object_info_scroll_state.saturating_add(1);
             }
-            KeyCode::Left | KeyCode::Char('h') => {
+            Action::MoveLeft | Action::Back => {
                 self.mode = SelectionMode::TreeBrowsing;
             }
-            KeyCode::PageDown => {
+            Action::PageDown => {
                 // This gets clamped when the ui figures out how many lines we have
-                self.object_info_scroll_state = self.object_info_scroll_state.saturating_add(50);
-            }
-            KeyCode::PageUp => {
-                self.object_info_scroll_state = self.object_info_scroll_state.saturating_sub(50);
-            }
-            KeyCode::End => {
-                self.object_info_scroll_state = u16::MAX;
-            }
-            KeyCode::Home => {
-                self.object_info_scroll_state = 0;
-            }
-            KeyCode::Char('?') => {
-                self.mode = SelectionMode::HelpScreen;
-            }
-            KeyCode::Char('L') => {
-                self.show_logs = !self.show_logs;
-            }
-            KeyCode::Char('i') => {
-                return self.on_keypress_tree_mode(keycode);
+                tab.object_info_scroll_state = tab.object_info_scroll_state.saturating_add(50);
+            }
+            Action::PageUp => {
+                tab.object_info_scroll_state = tab.object_info_scroll_state.saturating_sub(50);
+            }
+            Action::GoToBottom => {
+                tab.object_info_scroll_state = u16::MAX;
+            }
+            Action::GoToTop => {
+                tab.object_info_scroll_state = 0;
+            }
+            Action::RunPostCommand
+            | Action::HistoryBack
+            | Action::HistoryForward
+            | Action::SetMark
+            | Action::JumpToMark
+            | Action::FollowReference
+            | Action::CyclePlot
+            | Action::CycleColormap
+            | Action::ZoomIn
+            | Action::ZoomOut
+            | Action::PanLeft
+            | Action::PanRight
+            | Action::MoreBins
+            | Action::FewerBins
+            | Action::ToggleLogCounts
+            | Action::CycleHistogramClip
+            | Action::NextField
+            | Action::PrevField
+            | Action::NextSlice
+            | Action::PrevSlice
+            | Action::ToggleHexView
+            | Action::ToggleChunkMap
+            | Action::ToggleFillValues
+            | Action::YankHistogram
+            | Action::NextTab
+            | Action::PrevTab
+            | Action::OpenFileBrowser
+            | Action::ShowHelp
+            | Action::ToggleLogs
+            | Action::Search
+            | Action::Quit => {
+                return self.on_action_tree_mode(action);
             }
             _ => {}
         };
         KeyPressResult::Redraw
     }
 
-    fn on_keypress_help_screen_mode(&mut self, keycode: crossterm::event::KeyCode) {
-        match keycode {
-            KeyCode::Up | KeyCode::Char('k') => {
+    fn on_action_help_screen_mode(&mut self, action: Action) {
+        match action {
+            Action::MoveUp => {
                 self.help_screen_scroll_state = self.help_screen_scroll_state.saturating_sub(1);
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Action::MoveDown => {
                 self.help_screen_scroll_state = self.help_screen_scroll_state.saturating_add(1);
             }
-            KeyCode::PageDown => {
+            Action::PageDown => {
                 self.help_screen_scroll_state = self.help_screen_scroll_state.saturating_add(50);
             }
-            KeyCode::PageUp => {
+            Action::PageUp => {
                 self.help_screen_scroll_state = self.help_screen_scroll_state.saturating_sub(50);
             }
-            KeyCode::End => {
+            Action::GoToBottom => {
                 self.help_screen_scroll_state = u16::MAX;
             }
-            KeyCode::Home => {
+            Action::GoToTop => {
                 self.help_screen_scroll_state = 0;
             }
+            Action::Back | Action::ToggleNode | Action::Quit | Action::ShowHelp => {
+                self.mode = self.default_mode();
+            }
             _ => {}
         };
     }
 
-    fn update_filtered_tree(&mut self) {
-        let query = &self.search_query_and_cursor().0;
-        match &self.tree {
-            Some(tree) => {
-                self.filtered_tree = tree.filter(query);
-                self.update_selected_tree_item();
-            }
-            None => {
-                self.filtered_tree = None;
+    fn on_action_file_browser_mode(&mut self, action: Action) {
+        let browser = &mut self.file_browser;
+        match action {
+            Action::MoveUp | Action::PrevItem => browser.select_relative(-1),
+            Action::MoveDown | Action::NextItem => browser.select_relative(1),
+            Action::PageUp => browser.select_relative(-50),
+            Action::PageDown => browser.select_relative(50),
+            Action::GoToTop => browser.select_first(),
+            Action::GoToBottom => browser.select_last(),
+            Action::MoveLeft | Action::ParentDir => browser.go_to_parent(),
+            Action::ToggleNode | Action::MoveRight => {
+                if let Some(path) = browser.activate_selected() {
+                    self.open_file_in_tab(path);
+                }
             }
-        }
-    }
-
-    fn update_selected_tree_item(&mut self) {
-        match &self.filtered_tree {
-            Some(filtered_tree) => {
-                let nothing_selected = self.tree_state.selected().is_empty();
-                let selected_item = filtered_tree.get_selected_node(&self.tree_state.selected());
-                let selected_item_is_in_tree = selected_item.is_some();
-                let selected_item_is_direct_match =
-                    selected_item.map_or(false, |t| t.is_direct_match);
-
-                if nothing_selected || !selected_item_is_in_tree || !selected_item_is_direct_match {
-                    let first_match = filtered_tree.path_to_first_match();
-                    self.tree_state.select(first_match.clone());
-                    for i in 0..first_match.len() {
-                        self.tree_state.open(first_match[0..i].to_vec());
-                    }
+            Action::Refresh => browser.refresh_keeping_selection(),
+            Action::RecentFiles => {
+                if browser.showing_recent_files {
+                    browser.refresh();
+                } else {
+                    browser.show_recent_files(&self.session_store.recent_files);
                 }
-                self.tree_state.scroll_selected_into_view();
             }
-            None => {
-                self.tree_state.select(vec![]);
+            Action::Back | Action::OpenFileBrowser if !self.tabs.is_empty() => {
+                self.mode = SelectionMode::TreeBrowsing;
+            }
+            Action::NextTab if !self.tabs.is_empty() => {
+                self.next_tab();
+                self.mode = SelectionMode::TreeBrowsing;
+            }
+            Action::PrevTab if !self.tabs.is_empty() => {
+                self.prev_tab();
+                self.mode = SelectionMode::TreeBrowsing;
+            }
+            Action::ShowHelp => {
+                self.mode = SelectionMode::HelpScreen;
             }
+            Action::ToggleLogs => {
+                self.show_logs = !self.show_logs;
+            }
+            Action::Quit => {
+                self.running = AppFinishingState::Quit;
+            }
+            _ => {}
         }
     }
 
@@ -863,134 +950,46 @@ impl App {
         self.last_help_screen_area = area;
     }
 
-    fn open_all_tree_nodes(&mut self) {
-        if let Some(tree) = &self.tree {
-            let mut to_visit = vec![(tree, vec![tree.id()])];
-            while let Some((current, id_path)) = to_visit.pop() {
-                self.tree_state.open(id_path.clone());
-                to_visit.extend(current.children().iter().map(|c| {
-                    let mut id_path = id_path.clone();
-                    id_path.push(c.id());
-                    (c, id_path)
-                }));
-            }
-        }
+    pub fn set_last_tab_bar_area(&mut self, area: Rect) {
+        self.last_tab_bar_area = area;
     }
 
-    fn start_analysis_task(&self, tree_node: &TreeNode<NodeIdT>) {
-        if let Some(Hdf5Object::Dataset(d)) = &tree_node.hdf5_object {
-            let key = tree_node.id().clone();
-            {
-                let mut info_dict = self.node_id_to_analysis.lock().unwrap();
-                if info_dict.get(&key).is_some() {
-                    // already being processed or done
-                    return;
+    pub async fn run(mut self) -> Result<AppFinishingState, Box<dyn std::error::Error>> {
+        // A file that can't be opened loses its tab, the others still open
+        let mut h5_files = vec![];
+        let mut failures = vec![];
+        self.tabs
+            .retain(|tab| match h5_utils::open_file(&tab.h5_file_path) {
+                Ok(h5_file) => {
+                    h5_files.push(h5_file);
+                    true
                 }
-                info_dict.insert(key.clone(), AsyncDataAnalysis::Loading);
-            }
-
-            let thread_arc: Arc<Mutex<HashMap<NodeIdT, AsyncDataAnalysis>>> =
-                Arc::clone(&self.node_id_to_analysis);
-            let semaphore = Arc::clone(&self.process_semaphore);
-
-            // Get the file path and dataset path to pass to the worker process
-            let file_path = self.h5_file_path.to_string_lossy().to_string();
-            let dataset_path = d.name().to_string();
-
-            // Spawn analysis in a completely separate process
-            // Ideally it would just happpen in a separate thread, but the hdf5 read operation uses a processs wide lock
-            // so when we want to read basic stats of the dataset, or another dataset at the same time, we are forced to wait
-            // ideally the hdf5 library would support finer grained locking, but until then we have to do this workaround.
-            // Even if I open the file anew in the new thread, we still get blocked by the process wide lock.
-            tokio::spawn(async move {
-                // Acquire semaphore permit to limit concurrent processes
-                // This will wait until a permit becomes available
-                let _permit = semaphore
-                    .acquire()
-                    .await
-                    .expect("Semaphore should not be closed");
-
-                log::debug!("Forking analysis process for dataset {}", &dataset_path);
-
-                let (tx, rx) = ipc_channel::ipc::channel::<AnalysisResult>()
-                    .expect("Failed to create ipc-channel");
-
-                let file_path_buf = std::path::PathBuf::from(&file_path);
-                let dataset_path_clone = dataset_path.clone();
-
-                let result = tokio::task::spawn_blocking(move || {
-                    match unsafe { fork() } {
-                        Ok(ForkResult::Parent { child }) => {
-                            // Drop parent's copy of sender
-                            drop(tx);
-
-                            // Wait for the child to send back the analysis results
-                            let msg_res = rx.recv();
-
-                            // Reap the child process to avoid zombie processes
-                            let _ = waitpid(child, None);
-
-                            match msg_res {
-                                Ok(analysis) => Ok(analysis),
-                                Err(e) => Err(format!("IPC receive failed: {:?}", e)),
-                            }
-                        }
-                        Ok(ForkResult::Child) => {
-                            // Disable logging in the child process to avoid locks and output corruption
-                            log::set_max_level(log::LevelFilter::Off);
-
-                            // Drop child's copy of receiver
-                            drop(rx);
-
-                            // Perform the analysis
-                            let res = crate::analysis::hdf5_dataset_analysis_from_path(
-                                &file_path_buf,
-                                &dataset_path_clone,
-                            );
-
-                            let processed_analysis = match res {
-                                Ok(analysis) => analysis,
-                                Err(e) => AnalysisResult::Failed(e.to_string()),
-                            };
-
-                            // Send back the results
-                            let _ = tx.send(processed_analysis);
-
-                            // Exit immediately to prevent child from running any other logic
-                            std::process::exit(0);
-                        }
-                        Err(e) => Err(format!("Fork failed: {}", e)),
-                    }
-                })
-                .await;
-
-                let processed_analysis = match result {
-                    Ok(Ok(analysis)) => analysis,
-                    Ok(Err(err_msg)) => AnalysisResult::Failed(err_msg),
-                    Err(join_err) => AnalysisResult::Failed(format!("Task panic: {}", join_err)),
-                };
-
-                if let Ok(mut info_dict) = thread_arc.lock() {
-                    info_dict.insert(key, AsyncDataAnalysis::Ready(processed_analysis));
+                Err(e) => {
+                    log::error!("Failed to open {:?}: {}", tab.h5_file_path, e);
+                    failures.push(format!("Couldn't open {:?}: {}", tab.h5_file_path, e));
+                    false
                 }
-
-                // The permit is automatically dropped here, releasing the semaphore slot
             });
+        self.active_tab = self.active_tab.min(self.tabs.len().saturating_sub(1));
+        if !failures.is_empty() {
+            let message = failures.join(", ");
+            match self.tabs.get_mut(self.active_tab) {
+                Some(tab) => tab.set_status(message),
+                None => {
+                    self.file_browser.status = Some(message);
+                    self.mode = SelectionMode::FileBrowsing;
+                }
+            }
         }
-    }
-
-    pub async fn run(mut self) -> Result<AppFinishingState, Box<dyn std::error::Error>> {
-        let h5_file = h5_utils::open_file(&self.h5_file_path)?;
 
         let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
         let mut events = events::EventHandler::new(receiver);
+        self.event_sender = Some(sender);
 
-        // Spawn a task to load the HDF5 file structure since it might be slow
-        tokio::task::spawn_blocking(move || {
-            let tree = App::tree_from_h5(&h5_file).expect("Failed to parse HDF5 structure");
-            let tree_update = events::Event::TreeUpdate(tree);
-            sender.clone().send(tree_update).unwrap();
-        });
+        // Spawn a task per file to load the HDF5 file structure since it might be slow
+        for (tab_index, h5_file) in h5_files.into_iter().enumerate() {
+            self.spawn_tree_load(tab_index, h5_file);
+        }
 
         let mut redraw = true;
         let mut terminal = ratatui::init();
@@ -1001,25 +1000,16 @@ impl App {
         )?;
 
         while self.running == AppFinishingState::Continue {
-            if let Some(last_selected) = &self.tree_state_last_rendered_selected {
-                if last_selected != self.tree_state.selected() {
-                    // if the selected node has changed, reset the scroll state
-                    self.object_info_scroll_state = 0;
-
-                    let path_to_selected_node = self.tree_state.selected();
-                    if let Some(tree_node) = self
-                        .tree
-                        .as_ref()
-                        .unwrap()
-                        .get_selected_node(path_to_selected_node)
-                    {
-                        self.start_analysis_task(tree_node);
-                    }
-                }
+            let semaphore = Arc::clone(&self.process_semaphore);
+            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                tab.on_selection_maybe_changed(&semaphore);
             }
             if redraw {
                 terminal.draw(|frame| ui(frame, &mut self))?;
-                self.tree_state_last_rendered_selected = Some(self.tree_state.selected().to_vec());
+                if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                    tab.tree_state_last_rendered_selected =
+                        Some(tab.tree_state.selected().to_vec());
+                }
             }
 
             redraw = match events.next_event() {
@@ -1031,7 +1021,7 @@ impl App {
                     match self.handle_keypress(key) {
                         KeyPressResult::Redraw => true,
                         KeyPressResult::DontRedraw => false,
-                        KeyPressResult::RunPostCommand(post_cmd, ds_path) => {
+                        KeyPressResult::RunPostCommand(post_cmd, h5_file_path, ds_path) => {
                             // Pause the TUI: disable raw mode and leave alternate screen
                             crossterm::execute!(
                                 std::io::stdout(),
@@ -1041,7 +1031,7 @@ impl App {
                             ratatui::restore();
 
                             // Run the post command
-                            let h5_file_path_str = self.h5_file_path.to_string_lossy().to_string();
+                            let h5_file_path_str = h5_file_path.to_string_lossy().to_string();
                             match &post_cmd {
                                 Some(cmd) => {
                                     println!(
@@ -1094,10 +1084,10 @@ impl App {
                 events::Event::Mouse(mouse) => self.handle_mouse(mouse),
                 events::Event::Paste(text) => self.handle_paste(text),
                 events::Event::Resize => true,
-                events::Event::TreeUpdate(tree) => {
-                    self.tree = Some(tree);
-                    self.open_all_tree_nodes();
-                    self.update_filtered_tree();
+                events::Event::TreeUpdate(tab_index, tree) => {
+                    if let Some(tab) = self.tabs.get_mut(tab_index) {
+                        tab.set_tree(tree);
+                    }
                     true
                 }
             }
@@ -1109,9 +1099,94 @@ impl App {
             crossterm::event::DisableBracketedPaste
         )?;
         ratatui::restore();
+        self.save_session();
         Ok(self.running)
     }
 
+    fn on_keypress_command_palette_mode(
+        &mut self,
+        key: crossterm::event::KeyEvent,
+        action: Option<Action>,
+    ) -> KeyPressResult {
+        let palette = &mut self.command_palette;
+        match key.code {
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                palette.push_char(c);
+            }
+            KeyCode::Backspace => palette.pop_char(),
+            KeyCode::Up | KeyCode::BackTab => palette.select_relative(-1),
+            KeyCode::Down | KeyCode::Tab => palette.select_relative(1),
+            KeyCode::Enter => return self.run_command_palette_selection(),
+            KeyCode::Esc => self.mode = self.command_palette.return_mode,
+            _ => match action {
+                Some(Action::MoveUp | Action::PrevItem) => palette.select_relative(-1),
+                Some(Action::MoveDown | Action::NextItem) => palette.select_relative(1),
+                Some(Action::Back | Action::CommandPalette) => {
+                    self.mode = self.command_palette.return_mode;
+                }
+                _ => {}
+            },
+        }
+        KeyPressResult::Redraw
+    }
+
+    /// Finish `m<letter>` or `'<letter>`, any other key cancels.
+    fn on_mark_name(&mut self, pending_mark: PendingMark, keycode: KeyCode) {
+        let KeyCode::Char(name) = keycode else {
+            return;
+        };
+        if !name.is_ascii_alphabetic() {
+            return;
+        }
+        let tab = self.tab_mut();
+        match pending_mark {
+            PendingMark::Set => tab.set_mark(name),
+            PendingMark::Jump => {
+                tab.jump_to_mark(name);
+                self.mode = SelectionMode::TreeBrowsing;
+            }
+        }
+    }
+
+    /// Finish `follow-reference` when there are several targets, any other key cancels.
+    fn on_reference_choice(&mut self, paths: Vec<String>, keycode: KeyCode) {
+        let KeyCode::Char(key) = keycode else {
+            return;
+        };
+        let Some(path) = reference_key_index(key).and_then(|i| paths.get(i)) else {
+            return;
+        };
+        self.tab_mut().jump_to_object(path);
+        self.mode = SelectionMode::TreeBrowsing;
+    }
+
+    /// Run `action` in the current mode.
+    fn on_action(&mut self, action: Action) -> KeyPressResult {
+        if action == Action::CommandPalette {
+            self.open_command_palette();
+            return KeyPressResult::Redraw;
+        }
+        match self.mode {
+            SelectionMode::TreeBrowsing | SelectionMode::SearchQueryEditing => {
+                self.on_action_tree_mode(action)
+            }
+            SelectionMode::ObjectInfoInspecting => self.on_action_object_info_mode(action),
+            SelectionMode::HelpScreen => {
+                self.on_action_help_screen_mode(action);
+                KeyPressResult::Redraw
+            }
+            SelectionMode::FileBrowsing => {
+                self.on_action_file_browser_mode(action);
+                KeyPressResult::Redraw
+            }
+            SelectionMode::CommandPalette => KeyPressResult::Redraw,
+        }
+    }
+
     fn handle_keypress(&mut self, key: crossterm::event::KeyEvent) -> KeyPressResult {
         if key.kind == crossterm::event::KeyEventKind::Press {
             // log::debug!("{:?}",  key);
@@ -1121,46 +1196,25 @@ impl App {
                 return KeyPressResult::Redraw;
             }
 
-            return match self.mode {
-                SelectionMode::TreeBrowsing => match key.code {
-                    KeyCode::Char('q') => {
-                        self.running = AppFinishingState::Quit;
-                        KeyPressResult::Redraw
-                    }
-                    KeyCode::Char('/') => {
-                        self.mode = SelectionMode::SearchQueryEditing;
-                        KeyPressResult::Redraw
-                    }
-                    other => self.on_keypress_tree_mode(other),
-                },
-                SelectionMode::SearchQueryEditing => match key.code {
-                    KeyCode::Esc | KeyCode::Enter => {
-                        self.mode = SelectionMode::TreeBrowsing;
-                        KeyPressResult::Redraw
-                    }
-                    _ => self.on_keypress_search_mode(key),
-                },
-                SelectionMode::ObjectInfoInspecting => match key.code {
-                    KeyCode::Char('q') => {
-                        self.running = AppFinishingState::Quit;
-                        KeyPressResult::Redraw
-                    }
-                    KeyCode::Char('/') => {
-                        self.mode = SelectionMode::SearchQueryEditing;
-                        KeyPressResult::Redraw
-                    }
-                    other => self.on_keypress_object_info_mode(other),
-                },
-                SelectionMode::HelpScreen => match key.code {
-                    KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('?') => {
-                        self.mode = SelectionMode::TreeBrowsing;
-                        KeyPressResult::Redraw
-                    }
-                    other => {
-                        self.on_keypress_help_screen_mode(other);
-                        KeyPressResult::Redraw
-                    }
-                },
+            if let Some(pending_mark) = self.pending_mark.take() {
+                self.on_mark_name(pending_mark, key.code);
+                return KeyPressResult::Redraw;
+            }
+            if let Some(paths) = self.pending_reference.take() {
+                self.on_reference_choice(paths, key.code);
+                return KeyPressResult::Redraw;
+            }
+
+            let action = config::get().keymap.action_for(&key);
+            return match (self.mode, action) {
+                (SelectionMode::SearchQueryEditing, action) => {
+                    self.on_keypress_search_mode(key, action)
+                }
+                (SelectionMode::CommandPalette, action) => {
+                    self.on_keypress_command_palette_mode(key, action)
+                }
+                (_, None) => KeyPressResult::Redraw,
+                (_, Some(action)) => self.on_action(action),
             };
         }
         KeyPressResult::DontRedraw
@@ -1169,15 +1223,24 @@ impl App {
     fn handle_mouse(&mut self, mouse: crossterm::event::MouseEvent) -> bool {
         log::debug!("mouse event: {:?}", mouse);
 
-        // Update hovered node
         let position = Position::new(mouse.column, mouse.row);
-        let new_hover = if self.last_tree_area.contains(position) {
-            self.tree_state.rendered_at(position).map(|id| id.to_vec())
+        if self.mode == SelectionMode::CommandPalette {
+            return self.handle_mouse_command_palette(mouse);
+        }
+        if self.mode == SelectionMode::FileBrowsing || self.tabs.is_empty() {
+            return self.handle_mouse_file_browser(mouse);
+        }
+
+        // Update hovered node
+        let last_tree_area = self.last_tree_area;
+        let tab = self.tab_mut();
+        let new_hover = if last_tree_area.contains(position) {
+            tab.tree_state.rendered_at(position).map(|id| id.to_vec())
         } else {
             None
         };
-        let hover_changed = self.hovered_node != new_hover;
-        self.hovered_node = new_hover;
+        let hover_changed = tab.hovered_node != new_hover;
+        tab.hovered_node = new_hover;
 
         let mut hovered_key = None;
         if self.last_object_info_area.contains(position) {
@@ -1191,7 +1254,7 @@ impl App {
                 let table_area = self.last_object_info_table_area;
                 if table_area.contains(position) {
                     let clicked_row_idx = (mouse.row - table_area.y) as usize
-                        + self.object_info_scroll_state as usize;
+                        + self.tab().object_info_scroll_state as usize;
                     hovered_key = self.object_info_row_keys.get(clicked_row_idx).cloned();
                 }
             }
@@ -1199,7 +1262,14 @@ impl App {
         let obj_info_hover_changed = self.hovered_object_info_key != hovered_key;
         self.hovered_object_info_key = hovered_key;
 
-        let needs_redraw = hover_changed || obj_info_hover_changed;
+        let heatmap_hover = self
+            .last_heatmap_area
+            .contains(position)
+            .then_some(position);
+        let heatmap_hover_changed = self.hovered_heatmap_position != heatmap_hover;
+        self.hovered_heatmap_position = heatmap_hover;
+
+        let needs_redraw = hover_changed || obj_info_hover_changed || heatmap_hover_changed;
 
         let match_result = match mouse.kind {
             MouseEventKind::Down(MouseButton::Left) => {
@@ -1221,7 +1291,7 @@ impl App {
                         let new_percentage =
                             ((mouse.column as u32 * 100) / total_width as u32) as u16;
                         // Clamp between 10% and 90% to keep both panels usable
-                        self.tree_width_percentage = new_percentage.clamp(10, 90);
+                        self.tab_mut().tree_width_percentage = new_percentage.clamp(10, 90);
                     }
                 }
                 true
@@ -1244,25 +1314,22 @@ impl App {
                     return true;
                 }
 
-                if self
-                    .last_object_info_area
-                    .contains(Position::new(mouse.column, mouse.row))
-                {
+                let last_object_info_area = self.last_object_info_area;
+                let last_tree_area = self.last_tree_area;
+                let tab = self.tab_mut();
+                if last_object_info_area.contains(Position::new(mouse.column, mouse.row)) {
                     if is_scroll_down {
-                        self.object_info_scroll_state =
-                            self.object_info_scroll_state.saturating_add(1);
+                        tab.object_info_scroll_state =
+                            tab.object_info_scroll_state.saturating_add(1);
                     } else {
-                        self.object_info_scroll_state =
-                            self.object_info_scroll_state.saturating_sub(1);
+                        tab.object_info_scroll_state =
+                            tab.object_info_scroll_state.saturating_sub(1);
                     }
-                } else if self
-                    .last_tree_area
-                    .contains(Position::new(mouse.column, mouse.row))
-                {
+                } else if last_tree_area.contains(Position::new(mouse.column, mouse.row)) {
                     if is_scroll_down {
-                        self.tree_state.scroll_down(1);
+                        tab.tree_state.scroll_down(1);
                     } else {
-                        self.tree_state.scroll_up(1);
+                        tab.tree_state.scroll_up(1);
                     }
                 }
 
@@ -1280,14 +1347,64 @@ impl App {
         needs_redraw || match_result
     }
 
+    fn handle_mouse_command_palette(&mut self, mouse: crossterm::event::MouseEvent) -> bool {
+        let table_area = self.last_command_palette_table_area;
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if table_area.contains(Position::new(mouse.column, mouse.row)) {
+                    let clicked_row_idx = (mouse.row - table_area.y) as usize
+                        + self.command_palette.table_state.offset();
+                    if clicked_row_idx < self.command_palette.entries().len() {
+                        // Running needs Enter, so a post command can take over the terminal
+                        self.command_palette
+                            .table_state
+                            .select(Some(clicked_row_idx));
+                    }
+                } else {
+                    // Clicking outside the palette closes it
+                    self.mode = self.command_palette.return_mode;
+                }
+                true
+            }
+            MouseEventKind::ScrollDown => {
+                self.command_palette.select_relative(1);
+                true
+            }
+            MouseEventKind::ScrollUp => {
+                self.command_palette.select_relative(-1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn handle_mouse_file_browser(&mut self, mouse: crossterm::event::MouseEvent) -> bool {
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.on_click(mouse.column, mouse.row);
+                true
+            }
+            MouseEventKind::ScrollDown => {
+                self.file_browser.select_relative(1);
+                true
+            }
+            MouseEventKind::ScrollUp => {
+                self.file_browser.select_relative(-1);
+                true
+            }
+            _ => false,
+        }
+    }
+
     fn handle_paste(&mut self, text: String) -> bool {
         if self.mode == SelectionMode::SearchQueryEditing {
+            let tab = self.tab_mut();
             for c in text.chars() {
                 if !c.is_control() {
-                    self.search_query_left.push(c);
+                    tab.search_query_left.push(c);
                 }
             }
-            self.update_filtered_tree();
+            tab.update_filtered_tree();
             true
         } else {
             false
@@ -1297,24 +1414,21 @@ impl App {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::analysis::AnalysisResult;
     use crate::h5_utils;
-    use std::path::PathBuf;
+    use nix::sys::wait::waitpid;
+    use nix::unistd::{fork, ForkResult};
 
     #[test]
     fn test_fork_analysis() {
-        // Ensure the dummy file exists
-        let path = PathBuf::from("dummy.h5");
-        if !path.exists() {
-            h5_utils::generate_dummy_file().unwrap();
-        }
+        let dummy = h5_utils::dummy_file();
 
         // Run the fork and channel analysis manually
         let (tx, rx) =
             ipc_channel::ipc::channel::<AnalysisResult>().expect("Failed to create ipc-channel");
 
         let dataset_path = "sums_of_bernoulli".to_string();
-        let file_path = path.clone();
+        let file_path = dummy.path.clone();
 
         match unsafe { fork() } {
             Ok(ForkResult::Parent { child }) => {
@@ -1334,8 +1448,11 @@ mod tests {
             Ok(ForkResult::Child) => {
                 log::set_max_level(log::LevelFilter::Off);
                 drop(rx);
-                let res =
-                    crate::analysis::hdf5_dataset_analysis_from_path(&file_path, &dataset_path);
+                let res = crate::analysis::hdf5_dataset_analysis_from_path(
+                    &file_path,
+                    &dataset_path,
+                    crate::analysis::AnalysisOptions::default(),
+                );
                 let processed_analysis = match res {
                     Ok(analysis) => analysis,
                     Err(e) => AnalysisResult::Failed(e.to_string()),
@@ -0,0 +1,390 @@
+use std::error::Error;
+
+use hdf5_metno::Dataset;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+
+use crate::filters;
+use crate::num_utils;
+
+/// Chunks listed one per line, the grid and totals still cover all of them.
+const MAX_LISTED_CHUNKS: usize = 10_000;
+
+/// Size of the allocation grid, each cell covers a block of chunks when there are more.
+const MAX_GRID_ROWS: usize = 32;
+const MAX_GRID_COLUMNS: usize = 64;
+
+/// One allocated chunk, as stored in the chunk index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredChunk {
+    /// Logical position of the first element of the chunk.
+    pub offset: Vec<u64>,
+    pub addr: u64,
+    pub size: u64,
+    /// Bit `i` is set when filter `i` of the pipeline was skipped for this chunk.
+    pub filter_mask: u32,
+}
+
+/// Where the chunks of a dataset are in the file and how well each of them compressed.
+#[derive(Debug, Clone)]
+pub struct ChunkMap {
+    pub chunk_shape: Vec<usize>,
+    /// Number of chunks along each dimension.
+    pub grid_shape: Vec<usize>,
+    /// Size of a chunk before filtering.
+    pub chunk_bytes: u64,
+    pub num_allocated: usize,
+    pub stored_bytes: u64,
+    /// Uncompressed over stored size of each allocated chunk, sorted.
+    pub ratios: Vec<f64>,
+    /// The first allocated chunks, in the order of the chunk index.
+    pub chunks: Vec<StoredChunk>,
+    pub filter_names: Vec<String>,
+    /// Chunks skipping at least one filter.
+    pub num_partially_filtered: usize,
+    grid: GridLayout,
+    /// Allocated chunks in each cell of the grid, row major.
+    allocated_per_cell: Vec<usize>,
+}
+
+/// Chunks laid out on rows and columns, then downsampled to at most
+/// `MAX_GRID_ROWS` x `MAX_GRID_COLUMNS` cells.
+/// Columns are the last dimension of the chunk grid, 1D datasets wrap.
+#[derive(Debug, Clone, Copy)]
+struct GridLayout {
+    num_chunks: usize,
+    rows: usize,
+    columns: usize,
+    cell_rows: usize,
+    cell_columns: usize,
+}
+
+impl GridLayout {
+    fn new(grid_shape: &[usize]) -> GridLayout {
+        let num_chunks: usize = grid_shape.iter().product();
+        let columns = match grid_shape {
+            [] => 1,
+            [_] => num_chunks.clamp(1, MAX_GRID_COLUMNS),
+            [.., last] => (*last).max(1),
+        };
+        let rows = num_chunks.div_ceil(columns);
+        GridLayout {
+            num_chunks,
+            rows,
+            columns,
+            cell_rows: rows.min(MAX_GRID_ROWS),
+            cell_columns: columns.min(MAX_GRID_COLUMNS),
+        }
+    }
+
+    fn cell_of(&self, linear_index: usize) -> usize {
+        let row = linear_index / self.columns;
+        let column = linear_index % self.columns;
+        (row * self.cell_rows / self.rows) * self.cell_columns
+            + column * self.cell_columns / self.columns
+    }
+
+    /// Rows (or columns) whose cell is `cell`, out of `total` shown as `cells`.
+    fn span(cell: usize, total: usize, cells: usize) -> std::ops::Range<usize> {
+        (cell * total).div_ceil(cells)..((cell + 1) * total).div_ceil(cells)
+    }
+
+    /// Number of chunks a cell covers, the last row of a wrapped 1D dataset may be short.
+    fn cell_capacity(&self, cell_row: usize, cell_column: usize) -> usize {
+        let rows = Self::span(cell_row, self.rows, self.cell_rows);
+        let columns = Self::span(cell_column, self.columns, self.cell_columns);
+        let mut capacity = rows.len() * columns.len();
+        let last_row = self.rows - 1;
+        if rows.contains(&last_row) {
+            let last_row_len = self.num_chunks - last_row * self.columns;
+            capacity -= columns.filter(|&column| column >= last_row_len).count();
+        }
+        capacity
+    }
+}
+
+/// Styles for the parts of the chunk map.
+pub struct ChunkStyles {
+    pub text: Style,
+    pub dim: Style,
+    pub allocated: Style,
+    /// Chunks that skipped filters or didn't compress.
+    pub warning: Style,
+}
+
+/// Number of chunks needed to cover `shape`, allocated or not.
+pub fn grid_shape(shape: &[usize], chunk_shape: &[usize]) -> Vec<usize> {
+    shape
+        .iter()
+        .zip(chunk_shape)
+        .map(|(&len, &chunk_len)| len.div_ceil(chunk_len.max(1)))
+        .collect()
+}
+
+impl ChunkMap {
+    pub fn read(d: &Dataset) -> Result<ChunkMap, Box<dyn Error>> {
+        let chunk_shape = d
+            .chunk()
+            .ok_or_else(|| format!("{:?} layout, the dataset is not chunked", d.layout()))?;
+        let grid_shape = grid_shape(&d.shape(), &chunk_shape);
+        let grid = GridLayout::new(&grid_shape);
+        let chunk_bytes = (chunk_shape.iter().product::<usize>() * d.dtype()?.size()) as u64;
+        let filter_names = filters::read_pipeline(d)?
+            .iter()
+            .map(|filter| filter.display_name())
+            .collect();
+
+        let mut chunk_map = ChunkMap {
+            chunk_shape: chunk_shape.clone(),
+            grid_shape: grid_shape.clone(),
+            chunk_bytes,
+            num_allocated: 0,
+            stored_bytes: 0,
+            ratios: vec![],
+            chunks: vec![],
+            filter_names,
+            num_partially_filtered: 0,
+            grid,
+            allocated_per_cell: vec![0; grid.cell_rows * grid.cell_columns],
+        };
+        d.chunks_visit(|chunk| {
+            let linear_index = chunk.offset.iter().zip(&chunk_shape).zip(&grid_shape).fold(
+                0,
+                |index, ((&offset, &chunk_len), &num_chunks)| {
+                    index * num_chunks + offset as usize / chunk_len
+                },
+            );
+            if linear_index < grid.num_chunks {
+                chunk_map.allocated_per_cell[grid.cell_of(linear_index)] += 1;
+            }
+            chunk_map.num_allocated += 1;
+            chunk_map.stored_bytes += chunk.size;
+            chunk_map
+                .ratios
+                .push(chunk_bytes as f64 / chunk.size.max(1) as f64);
+            if chunk.filter_mask != 0 {
+                chunk_map.num_partially_filtered += 1;
+            }
+            if chunk_map.chunks.len() < MAX_LISTED_CHUNKS {
+                chunk_map.chunks.push(StoredChunk {
+                    offset: chunk.offset.to_vec(),
+                    addr: chunk.addr,
+                    size: chunk.size,
+                    filter_mask: chunk.filter_mask,
+                });
+            }
+            0
+        })?;
+        chunk_map.ratios.sort_by(f64::total_cmp);
+        Ok(chunk_map)
+    }
+
+    /// Names of the filters `chunk` skipped, e.g. `deflate`.
+    fn skipped_filters(&self, chunk: &StoredChunk) -> Vec<String> {
+        (0..32)
+            .filter(|i| chunk.filter_mask & (1 << i) != 0)
+            .map(|i| {
+                self.filter_names
+                    .get(i)
+                    .cloned()
+                    .unwrap_or(format!("filter {}", i + 1))
+            })
+            .collect()
+    }
+
+    /// Whether `chunk` is worth pointing out: skipped filters, or filters that didn't help.
+    fn is_poor(&self, chunk: &StoredChunk) -> bool {
+        chunk.filter_mask != 0 || (!self.filter_names.is_empty() && chunk.size >= self.chunk_bytes)
+    }
+
+    /// A summary, the allocation grid, then one line per chunk.
+    pub fn lines(&self, styles: &ChunkStyles) -> Vec<Line<'static>> {
+        let num_chunks = self.grid.num_chunks;
+        let mut lines = vec![
+            Line::styled(
+                format!(
+                    "Chunk shape {:?} ({} unfiltered), grid of {:?} chunks",
+                    self.chunk_shape,
+                    num_utils::file_size_fmt(self.chunk_bytes),
+                    self.grid_shape
+                ),
+                styles.text,
+            ),
+            Line::styled(
+                format!(
+                    "Allocated: {} of {} chunks ({:.1}%)",
+                    num_utils::format_integer_with_separator(self.num_allocated as u64),
+                    num_utils::format_integer_with_separator(num_chunks as u64),
+                    100.0 * self.num_allocated as f64 / num_chunks.max(1) as f64
+                ),
+                styles.text,
+            ),
+        ];
+        if let (Some(min), Some(max)) = (self.ratios.first(), self.ratios.last()) {
+            lines.push(Line::styled(
+                format!(
+                    "Stored: {}, ratio {:.2} overall, per chunk min {:.2} / median {:.2} / max {:.2}",
+                    num_utils::file_size_fmt(self.stored_bytes),
+                    (self.num_allocated as u64 * self.chunk_bytes) as f64
+                        / self.stored_bytes.max(1) as f64,
+                    min,
+                    num_utils::percentile_of_sorted(&self.ratios, 50.0),
+                    max
+                ),
+                styles.text,
+            ));
+        }
+        if self.num_partially_filtered > 0 {
+            lines.push(Line::styled(
+                format!(
+                    "{} chunks skipped some of their filters",
+                    self.num_partially_filtered
+                ),
+                styles.warning,
+            ));
+        }
+
+        lines.push(Line::default());
+        let chunks_per_cell = num_chunks.div_ceil(self.allocated_per_cell.len().max(1));
+        lines.push(Line::styled(
+            if chunks_per_cell > 1 {
+                format!(
+                    "Allocation (██ all, ▒▒ some, · none of the ~{} chunks in each cell)",
+                    chunks_per_cell
+                )
+            } else {
+                "Allocation (██ allocated, · not allocated)".to_string()
+            },
+            styles.dim,
+        ));
+        for cell_row in 0..self.grid.cell_rows {
+            let mut spans = vec![];
+            for cell_column in 0..self.grid.cell_columns {
+                let capacity = self.grid.cell_capacity(cell_row, cell_column);
+                let allocated =
+                    self.allocated_per_cell[cell_row * self.grid.cell_columns + cell_column];
+                spans.push(match allocated {
+                    _ if capacity == 0 => Span::raw("  "),
+                    0 => Span::styled(" ·", styles.dim),
+                    n if n >= capacity => Span::styled("██", styles.allocated),
+                    _ => Span::styled("▒▒", styles.allocated),
+                });
+            }
+            lines.push(Line::from(spans));
+        }
+
+        lines.push(Line::default());
+        let offset_width = self
+            .chunks
+            .iter()
+            .map(|chunk| format!("{:?}", chunk.offset).len())
+            .max()
+            .unwrap_or(0)
+            .max("Offset".len());
+        lines.push(Line::styled(
+            format!(
+                "{:>6}  {:<offset_width$}  {:>12}  {:>10}  {:>6}  Filters",
+                "#", "Offset", "Address", "Stored", "Ratio"
+            ),
+            styles.dim,
+        ));
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            let skipped = self.skipped_filters(chunk);
+            let filters_text = if self.filter_names.is_empty() {
+                "-".to_string()
+            } else if skipped.is_empty() {
+                "all".to_string()
+            } else {
+                format!("skipped {}", skipped.join(", "))
+            };
+            lines.push(Line::styled(
+                format!(
+                    "{:>6}  {:<offset_width$}  {:>#12x}  {:>10}  {:>6.2}  {}",
+                    i,
+                    format!("{:?}", chunk.offset),
+                    chunk.addr,
+                    num_utils::file_size_fmt(chunk.size),
+                    self.chunk_bytes as f64 / chunk.size.max(1) as f64,
+                    filters_text
+                ),
+                if self.is_poor(chunk) {
+                    styles.warning
+                } else {
+                    styles.text
+                },
+            ));
+        }
+        if self.num_allocated > self.chunks.len() {
+            lines.push(Line::styled(
+                format!(
+                    "… {} more chunks",
+                    num_utils::format_integer_with_separator(
+                        (self.num_allocated - self.chunks.len()) as u64
+                    )
+                ),
+                styles.dim,
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::h5_utils;
+    use hdf5_metno::File;
+
+    fn text(line: &Line) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_map() {
+        let dummy = h5_utils::dummy_file();
+        let file = File::open(&dummy.path).unwrap();
+        let styles = ChunkStyles {
+            text: Style::new(),
+            dim: Style::new(),
+            allocated: Style::new(),
+            warning: Style::new(),
+        };
+
+        let sparse = ChunkMap::read(&file.dataset("group1/sparse").unwrap()).unwrap();
+        assert_eq!(sparse.grid_shape, vec![4, 4]);
+        assert_eq!(sparse.num_allocated, 3);
+        assert_eq!(sparse.chunks[0].offset, vec![0, 0]);
+        assert_eq!(sparse.chunks[2].offset, vec![30, 30]);
+        let lines: Vec<String> = sparse.lines(&styles).iter().map(text).collect();
+        assert_eq!(lines[1], "Allocated: 3 of 16 chunks (18.8%)");
+        let grid_start = lines
+            .iter()
+            .position(|l| l.starts_with("Allocation"))
+            .unwrap();
+        assert_eq!(lines[grid_start + 1], "████ · ·");
+        assert_eq!(lines[grid_start + 2], " · · · ·");
+        assert_eq!(lines[grid_start + 4], " · · ·██");
+        assert!(lines.last().unwrap().ends_with("all"));
+
+        // 1D datasets wrap, with cells covering several chunks past the grid size
+        let layout = GridLayout::new(&[1000]);
+        assert_eq!((layout.rows, layout.columns), (16, 64));
+        assert_eq!(layout.cell_capacity(15, 0), 1);
+        assert_eq!(layout.cell_capacity(15, 63), 0);
+        let layout = GridLayout::new(&[100, 200]);
+        assert_eq!((layout.cell_rows, layout.cell_columns), (32, 64));
+        assert_eq!(
+            (0..32)
+                .flat_map(|r| (0..64).map(move |c| (r, c)))
+                .map(|(r, c)| layout.cell_capacity(r, c))
+                .sum::<usize>(),
+            20_000
+        );
+
+        let contiguous = ChunkMap::read(&file.dataset("group1/bool_ds").unwrap());
+        assert!(contiguous.is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::app::SelectionMode;
+use crate::keymap::Action;
+
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use ratatui::widgets::TableState;
+
+pub struct PaletteEntry {
+    pub action: Action,
+    pub matching_indices: Vec<usize>,
+}
+
+/// Fuzzy searchable list of every action, so features can be found without knowing their keys.
+pub struct CommandPalette {
+    pub query: String,
+    pub table_state: TableState,
+    /// The mode the palette was opened from, the chosen action runs there.
+    pub return_mode: SelectionMode,
+}
+
+impl CommandPalette {
+    pub fn new() -> CommandPalette {
+        CommandPalette {
+            query: String::new(),
+            table_state: TableState::default().with_selected(0),
+            return_mode: SelectionMode::TreeBrowsing,
+        }
+    }
+
+    pub fn open(&mut self, return_mode: SelectionMode) {
+        self.query.clear();
+        self.table_state = TableState::default().with_selected(0);
+        self.return_mode = return_mode;
+    }
+
+    /// Actions matching the query, best match first.
+    pub fn entries(&self) -> Vec<PaletteEntry> {
+        let actions = Action::ALL
+            .into_iter()
+            .filter(|action| *action != Action::CommandPalette);
+        if self.query.is_empty() {
+            return actions
+                .map(|action| PaletteEntry {
+                    action,
+                    matching_indices: vec![],
+                })
+                .collect();
+        }
+
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let mut scored: Vec<(i64, PaletteEntry)> = actions
+            .filter_map(|action| {
+                let (score, matching_indices) =
+                    matcher.fuzzy_indices(action.description(), &self.query)?;
+                Some((
+                    score,
+                    PaletteEntry {
+                        action,
+                        matching_indices,
+                    },
+                ))
+            })
+            .collect();
+        // Stable sort keeps the help screen order for equal scores
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.table_state.select(Some(0));
+    }
+
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.table_state.select(Some(0));
+    }
+
+    pub fn select_relative(&mut self, delta: isize) {
+        let num_entries = self.entries().len();
+        if num_entries == 0 {
+            return;
+        }
+        let current = self.table_state.selected().unwrap_or(0) as isize;
+        let selected = (current + delta).rem_euclid(num_entries as isize);
+        self.table_state.select(Some(selected as usize));
+    }
+
+    pub fn selected_action(&self) -> Option<Action> {
+        let selected = self.table_state.selected()?;
+        self.entries().get(selected).map(|entry| entry.action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_fuzzy_search() {
+        let mut palette = CommandPalette::new();
+        assert_eq!(palette.entries().len(), Action::ALL.len() - 1);
+
+        for c in "yank".chars() {
+            palette.push_char(c);
+        }
+        assert_eq!(palette.selected_action(), Some(Action::YankPath));
+        let entries = palette.entries();
+        assert_eq!(entries[0].matching_indices, vec![0, 1, 2, 3]);
+
+        palette.open(SelectionMode::FileBrowsing);
+        for c in "xqzv".chars() {
+            palette.push_char(c);
+        }
+        assert!(palette.entries().is_empty());
+        assert_eq!(palette.selected_action(), None);
+    }
+}
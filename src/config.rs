@@ -0,0 +1,400 @@
+use crate::keymap::{Action, KeyBinding, Keymap};
+use ratatui::style::palette::material;
+use ratatui::style::Color;
+use std::path::Path;
+use std::sync::OnceLock;
+use toml_edit::{DocumentMut, Item};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The active configuration, or the defaults if none was loaded.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+pub fn init(config: Config) {
+    if CONFIG.set(config).is_err() {
+        log::warn!("Config was already initialized, ignoring reload");
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HelpOnStartup {
+    FirstRun,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub text: Color,
+    pub accent: (u8, u8, u8),
+    pub border_highlight: Color,
+    pub border_default: Color,
+    pub log_border: Color,
+    pub search_match: Color,
+    pub no_matches: Color,
+    pub key_binding: Color,
+    pub hover_bg: Color,
+    pub hover_fg: Color,
+    pub copied_bg: Color,
+    pub copied_fg: Color,
+    pub success: Color,
+    pub plot_axis: (u8, u8, u8),
+    /// Border and title of the help screen, the title is shaded from this.
+    pub help_title: (u8, u8, u8),
+    /// Names to type, like `$H5INSPECT_POST` in the help screen.
+    pub code_fg: Color,
+    pub code_bg: Color,
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            text: Color::White,
+            accent: (0xfc, 0x4c, 0xb4),
+            border_highlight: Color::Red,
+            border_default: Color::White,
+            log_border: Color::Blue,
+            search_match: Color::Red,
+            no_matches: Color::Red,
+            key_binding: Color::Yellow,
+            hover_bg: Color::Gray,
+            hover_fg: Color::Black,
+            copied_bg: Color::Rgb(46, 125, 50),
+            copied_fg: Color::White,
+            success: Color::Green,
+            plot_axis: (0xff, 0xff, 0xff),
+            help_title: (0xff, 0, 0),
+            code_fg: material::BLUE_GRAY.c200,
+            code_bg: material::GRAY.c800,
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            text: Color::Black,
+            accent: (0xb0, 0x1c, 0x7c),
+            border_highlight: Color::Red,
+            border_default: Color::DarkGray,
+            log_border: Color::Blue,
+            search_match: Color::Red,
+            no_matches: Color::LightRed,
+            key_binding: Color::Rgb(0x8a, 0x5a, 0x00),
+            hover_bg: Color::Gray,
+            hover_fg: Color::Black,
+            copied_bg: Color::Rgb(46, 125, 50),
+            copied_fg: Color::White,
+            success: Color::Rgb(0x1b, 0x5e, 0x20),
+            plot_axis: (0, 0, 0),
+            help_title: (0xc6, 0x28, 0x28),
+            code_fg: material::BLUE_GRAY.c800,
+            code_bg: material::GRAY.c200,
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        Theme {
+            text: Color::White,
+            accent: (0xff, 0xd7, 0x00),
+            border_highlight: Color::LightRed,
+            border_default: Color::White,
+            log_border: Color::LightBlue,
+            search_match: Color::LightRed,
+            no_matches: Color::Red,
+            key_binding: Color::LightCyan,
+            hover_bg: Color::White,
+            hover_fg: Color::Black,
+            copied_bg: Color::Green,
+            copied_fg: Color::Black,
+            success: Color::LightGreen,
+            plot_axis: (0xff, 0xff, 0xff),
+            help_title: (0xff, 0x55, 0x55),
+            code_fg: Color::Black,
+            code_bg: Color::White,
+        }
+    }
+
+    pub fn accent_color(&self) -> Color {
+        let (r, g, b) = self.accent;
+        Color::Rgb(r, g, b)
+    }
+
+    pub fn help_title_color(&self) -> Color {
+        let (r, g, b) = self.help_title;
+        Color::Rgb(r, g, b)
+    }
+}
+
+/// Settings read from `config.toml` in the config dir.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub theme: Theme,
+    pub show_help_on_startup: HelpOnStartup,
+    pub tree_width_percentage: u16,
+    pub show_logs: bool,
+    /// `None` prints floats with the shortest representation that round-trips.
+    pub float_significant_digits: Option<usize>,
+    pub thousands_separator: String,
+    pub histogram_bins: usize,
+    /// Percentiles shown in the dataset stats, besides the median.
+    pub percentiles: Vec<f64>,
+    pub max_concurrent_analyses: usize,
+    pub keymap: Keymap,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            theme: Theme::dark(),
+            show_help_on_startup: HelpOnStartup::FirstRun,
+            tree_width_percentage: 50,
+            show_logs: false,
+            float_significant_digits: None,
+            thousands_separator: "_".to_string(),
+            histogram_bins: 30,
+            percentiles: vec![5.0, 25.0, 75.0, 95.0],
+            max_concurrent_analyses: 64,
+            keymap: Keymap::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(config_dir: Option<&Path>) -> Config {
+        let Some(config_path) = config_dir.map(|dir| dir.join(CONFIG_FILE_NAME)) else {
+            return Config::default();
+        };
+        match std::fs::read_to_string(&config_path) {
+            Ok(contents) => {
+                log::info!("Loading config from {:?}", config_path);
+                Config::parse(&contents)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
+            Err(e) => {
+                log::error!("Failed to read config {:?}: {}", config_path, e);
+                Config::default()
+            }
+        }
+    }
+
+    /// Parse a config, keeping the default for anything missing or invalid.
+    pub fn parse(contents: &str) -> Config {
+        let mut config = Config::default();
+        let doc = match contents.parse::<DocumentMut>() {
+            Ok(doc) => doc,
+            Err(e) => {
+                log::error!("Ignoring invalid config: {}", e);
+                return config;
+            }
+        };
+
+        if let Some(name) = get_str(doc.get("theme"), "theme") {
+            config.theme = match name {
+                "dark" => Theme::dark(),
+                "light" => Theme::light(),
+                "high-contrast" => Theme::high_contrast(),
+                _ => {
+                    log::warn!(
+                        "Unknown theme {:?}, expected dark, light or high-contrast",
+                        name
+                    );
+                    Theme::dark()
+                }
+            };
+        }
+        if let Some(accent) = get_str(doc.get("accent_color"), "accent_color") {
+            match parse_hex_color(accent) {
+                Some(rgb) => config.theme.accent = rgb,
+                None => log::warn!("Invalid accent_color {:?}, expected #rrggbb", accent),
+            }
+        }
+        if let Some(value) = get_str(doc.get("show_help_on_startup"), "show_help_on_startup") {
+            config.show_help_on_startup = match value {
+                "first-run" => HelpOnStartup::FirstRun,
+                "always" => HelpOnStartup::Always,
+                "never" => HelpOnStartup::Never,
+                _ => {
+                    log::warn!(
+                        "Unknown show_help_on_startup {:?}, expected first-run, always or never",
+                        value
+                    );
+                    HelpOnStartup::FirstRun
+                }
+            };
+        }
+
+        let layout = doc.get("layout");
+        if let Some(width) = get_int(layout, "tree_width_percentage", 10, 90) {
+            config.tree_width_percentage = width as u16;
+        }
+        if let Some(show_logs) = layout
+            .and_then(|t| t.get("show_logs"))
+            .and_then(Item::as_bool)
+        {
+            config.show_logs = show_logs;
+        }
+
+        let format = doc.get("format");
+        if let Some(digits) = get_int(format, "float_significant_digits", 0, 17) {
+            config.float_significant_digits = (digits > 0).then_some(digits as usize);
+        }
+        if let Some(separator) = get_str(
+            format.and_then(|t| t.get("thousands_separator")),
+            "thousands_separator",
+        ) {
+            config.thousands_separator = separator.to_string();
+        }
+
+        let analysis = doc.get("analysis");
+        if let Some(bins) = get_int(analysis, "histogram_bins", 2, 1000) {
+            config.histogram_bins = bins as usize;
+        }
+        if let Some(percentiles) = analysis.and_then(|t| t.get("percentiles")) {
+            match parse_percentiles(percentiles) {
+                Some(percentiles) => config.percentiles = percentiles,
+                None => {
+                    log::warn!("Config key percentiles should be a list of numbers from 0 to 100")
+                }
+            }
+        }
+        if let Some(permits) = get_int(analysis, "max_concurrent_analyses", 1, 1024) {
+            config.max_concurrent_analyses = permits as usize;
+        }
+
+        if let Some(keys) = doc.get("keys").and_then(Item::as_table_like) {
+            config.keymap = parse_keymap(keys);
+        }
+
+        config
+    }
+}
+
+/// A `preset` to start from, then `action-name = "key"` or `["key", ...]` to rebind actions.
+fn parse_keymap(keys: &dyn toml_edit::TableLike) -> Keymap {
+    let mut keymap = Keymap::default();
+    if let Some(preset) = get_str(keys.get("preset"), "preset") {
+        match Keymap::preset(preset) {
+            Some(preset) => keymap = preset,
+            None => log::warn!("Unknown key preset {:?}, expected vim or emacs", preset),
+        }
+    }
+
+    for (name, item) in keys.iter().filter(|(name, _)| *name != "preset") {
+        let Some(action) = Action::from_name(name) else {
+            log::warn!("Unknown action {:?} in [keys]", name);
+            continue;
+        };
+        let key_texts: Vec<&str> = match item.as_array() {
+            Some(array) => array.iter().filter_map(|v| v.as_str()).collect(),
+            None => item.as_str().into_iter().collect(),
+        };
+        let bindings: Vec<KeyBinding> = key_texts
+            .into_iter()
+            .filter_map(|text| {
+                let binding = KeyBinding::parse(text);
+                if binding.is_none() {
+                    log::warn!("Invalid key {:?} for action {}", text, name);
+                }
+                binding
+            })
+            .collect();
+        keymap.bind(action, bindings);
+    }
+    keymap
+}
+
+fn parse_percentiles(item: &Item) -> Option<Vec<f64>> {
+    item.as_array()?
+        .iter()
+        .map(|value| {
+            let p = value
+                .as_float()
+                .or_else(|| value.as_integer().map(|i| i as f64))?;
+            (0.0..=100.0).contains(&p).then_some(p)
+        })
+        .collect()
+}
+
+fn get_str<'a>(item: Option<&'a Item>, key: &str) -> Option<&'a str> {
+    let item = item?;
+    let value = item.as_str();
+    if value.is_none() {
+        log::warn!("Config key {} should be a string", key);
+    }
+    value
+}
+
+fn get_int(table: Option<&Item>, key: &str, min: i64, max: i64) -> Option<i64> {
+    let item = table?.get(key)?;
+    match item.as_integer() {
+        Some(value) if (min..=max).contains(&value) => Some(value),
+        _ => {
+            log::warn!(
+                "Config key {} should be an integer from {} to {}",
+                key,
+                min,
+                max
+            );
+            None
+        }
+    }
+}
+
+fn parse_hex_color(text: &str) -> Option<(u8, u8, u8)> {
+    let hex = text.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            r##"
+            theme = "light"
+            accent_color = "#102030"
+            show_help_on_startup = "never"
+
+            [layout]
+            tree_width_percentage = 35
+            show_logs = true
+
+            [format]
+            float_significant_digits = 4
+
+            [analysis]
+            histogram_bins = 500000
+            percentiles = [1, 99.5]
+            max_concurrent_analyses = 8
+
+            [keys]
+            preset = "emacs"
+            quit = ["ctrl-q", "q"]
+            "##,
+        );
+        assert_eq!(config.theme.text, Theme::light().text);
+        assert_eq!(config.theme.accent, (0x10, 0x20, 0x30));
+        assert_eq!(config.show_help_on_startup, HelpOnStartup::Never);
+        assert_eq!(config.tree_width_percentage, 35);
+        assert!(config.show_logs);
+        assert_eq!(config.float_significant_digits, Some(4));
+        assert_eq!(config.thousands_separator, "_");
+        // Out of range values fall back to the default
+        assert_eq!(config.histogram_bins, 30);
+        assert_eq!(config.percentiles, vec![1.0, 99.5]);
+        assert_eq!(config.max_concurrent_analyses, 8);
+        assert_eq!(config.keymap.keys_text(Action::MoveDown), "↓,Ctrl+n");
+        assert_eq!(config.keymap.keys_text(Action::Quit), "Ctrl+q,q");
+
+        assert_eq!(Config::parse("not [valid toml"), Config::default());
+    }
+}
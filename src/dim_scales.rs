@@ -0,0 +1,270 @@
+use hdf5::types::{
+    FixedAscii, FixedUnicode, TypeDescriptor, VarLenArray, VarLenAscii, VarLenUnicode,
+};
+use hdf5::{H5Type, ObjectReference1, ReferencedObject};
+use hdf5_metno::{self as hdf5, Dataset};
+use serde::{Deserialize, Serialize};
+
+use crate::num_utils;
+
+/// Values of a scale kept for labelling an axis, longer scales are sampled.
+const MAX_SCALE_VALUES: usize = 1 << 16;
+
+/// One entry of the `REFERENCE_LIST` of a scale: a dataset it is attached to, and along which dimension.
+#[derive(H5Type, Clone, Debug)]
+#[repr(C)]
+pub struct ScaleReference {
+    pub dataset: ObjectReference1,
+    pub dimension: i32,
+}
+
+/// Value of a string attribute, `None` if it is missing or not a string.
+fn string_attr(location: &hdf5::Location, name: &str) -> Option<String> {
+    if !location.attr_names().ok()?.iter().any(|n| n == name) {
+        return None;
+    }
+    let attr = location.attr(name).ok()?;
+    let text = match attr.dtype().ok()?.to_descriptor().ok()? {
+        TypeDescriptor::FixedAscii(_) => attr.read_scalar::<FixedAscii<256>>().ok()?.to_string(),
+        TypeDescriptor::FixedUnicode(_) => {
+            attr.read_scalar::<FixedUnicode<1024>>().ok()?.to_string()
+        }
+        TypeDescriptor::VarLenAscii => attr.read_scalar::<VarLenAscii>().ok()?.to_string(),
+        TypeDescriptor::VarLenUnicode => attr.read_scalar::<VarLenUnicode>().ok()?.to_string(),
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// Whether `d` is a dimension scale, which other datasets use to label their axes.
+pub fn is_dimension_scale(d: &Dataset) -> bool {
+    string_attr(d, "CLASS").is_some_and(|class| class == "DIMENSION_SCALE")
+}
+
+/// The `NAME` of a scale, or its path when it has none.
+fn scale_name(scale: &Dataset) -> String {
+    string_attr(scale, "NAME")
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| scale.name())
+}
+
+/// The scales attached to each dimension of `d`, from its `DIMENSION_LIST`.
+/// Empty if it has none.
+pub fn attached_scales(d: &Dataset) -> Vec<Vec<Dataset>> {
+    if !d
+        .attr_names()
+        .is_ok_and(|names| names.iter().any(|n| n == "DIMENSION_LIST"))
+    {
+        return vec![];
+    }
+    let Ok(lists) = d
+        .attr("DIMENSION_LIST")
+        .and_then(|attr| attr.read_raw::<VarLenArray<ObjectReference1>>())
+    else {
+        return vec![];
+    };
+    lists
+        .iter()
+        .map(|list| {
+            list.iter()
+                .filter_map(|reference| match d.dereference(reference) {
+                    Ok(ReferencedObject::Dataset(scale)) => Some(scale),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Datasets a scale is attached to, with the dimension, from its `REFERENCE_LIST`.
+fn attached_to(scale: &Dataset) -> Vec<(String, i32)> {
+    let Ok(references) = scale
+        .attr("REFERENCE_LIST")
+        .and_then(|attr| attr.read_raw::<ScaleReference>())
+    else {
+        return vec![];
+    };
+    references
+        .iter()
+        .filter_map(|reference| match scale.dereference(&reference.dataset) {
+            Ok(ReferencedObject::Dataset(d)) => Some((d.name(), reference.dimension)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Rows for the object info: the scales of each dimension, or what a scale is attached to.
+pub fn scale_rows(d: &Dataset) -> Vec<(String, String)> {
+    let mut rows = vec![];
+    if is_dimension_scale(d) {
+        let attached: Vec<String> = attached_to(d)
+            .iter()
+            .map(|(path, dimension)| format!("{} [{}]", path, dimension))
+            .collect();
+        rows.push((
+            "Dimension scale".to_string(),
+            if attached.is_empty() {
+                format!("\"{}\", not attached to any dataset", scale_name(d))
+            } else {
+                format!("\"{}\", labels {}", scale_name(d), attached.join(", "))
+            },
+        ));
+    }
+    let scales = attached_scales(d);
+    if scales.iter().any(|scales| !scales.is_empty()) {
+        let lines: Vec<String> = scales
+            .iter()
+            .enumerate()
+            .map(|(dimension, scales)| {
+                let names: Vec<String> = scales
+                    .iter()
+                    .map(|scale| format!("\"{}\" → {}", scale_name(scale), scale.name()))
+                    .collect();
+                if names.is_empty() {
+                    format!("[{}] none", dimension)
+                } else {
+                    format!("[{}] {}", dimension, names.join(", "))
+                }
+            })
+            .collect();
+        rows.push(("Dimension scales".to_string(), lines.join("\n")));
+    }
+    rows
+}
+
+/// Values of a dimension scale, used to label an axis instead of the index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AxisScale {
+    pub name: String,
+    /// Number of indices between the values kept.
+    pub step: usize,
+    pub values: Vec<f64>,
+}
+
+/// Every `step`-th value of the 1D dataset `d`, only those are read.
+fn read_every(d: &Dataset, step: usize) -> hdf5::Result<Vec<f64>> {
+    let selection = hdf5::SliceOrIndex::SliceCount {
+        start: 0,
+        step,
+        count: d.size().div_ceil(step),
+        block: 1,
+    };
+    Ok(d.read_slice_1d::<f64, _>(selection)?.to_vec())
+}
+
+impl AxisScale {
+    /// The first numeric scale attached to `dimension` of `d` with a value for every index.
+    pub fn read(d: &Dataset, dimension: usize) -> Option<AxisScale> {
+        let len = *d.shape().get(dimension)?;
+        let scales = attached_scales(d);
+        scales.get(dimension)?.iter().find_map(|scale| {
+            let numeric = matches!(
+                scale.dtype().ok()?.to_descriptor().ok()?,
+                TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) | TypeDescriptor::Float(_)
+            );
+            if !numeric || scale.shape() != [len] {
+                return None;
+            }
+            let step = len.div_ceil(MAX_SCALE_VALUES).max(1);
+            Some(AxisScale {
+                name: scale_name(scale),
+                step,
+                values: read_every(scale, step).ok()?,
+            })
+        })
+    }
+
+    /// Value at `index`, interpolated between the values kept.
+    pub fn value_at(&self, index: f64) -> Option<f64> {
+        let position = index / self.step as f64;
+        if position < 0.0 || self.values.is_empty() {
+            return None;
+        }
+        let i = (position.floor() as usize).min(self.values.len() - 1);
+        let next = self.values.get(i + 1).copied().unwrap_or(self.values[i]);
+        let t = (position - i as f64).min(1.0);
+        Some(self.values[i] + t * (next - self.values[i]))
+    }
+
+    /// Axis label for `index`, the index itself past the end of the scale.
+    pub fn label(&self, index: f64) -> String {
+        match self.value_at(index) {
+            Some(value) if index < (self.values.len() * self.step) as f64 => {
+                num_utils::float_fmt(value)
+            }
+            _ => num_utils::large_int_fmt(index.max(0.0) as u64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{hdf5_dataset_analysis_from_path, AnalysisOptions, AnalysisResult};
+    use crate::h5_utils;
+
+    #[test]
+    fn test_dimension_scales() {
+        let dummy = h5_utils::dummy_file();
+        let file = hdf5::File::open(&dummy.path).unwrap();
+
+        let time = file.dataset("grid/time").unwrap();
+        let temperature = file.dataset("grid/temperature").unwrap();
+        assert!(is_dimension_scale(&time));
+        assert!(!is_dimension_scale(&temperature));
+        assert_eq!(
+            scale_rows(&time),
+            vec![(
+                "Dimension scale".to_string(),
+                "\"time (s)\", labels /grid/temperature [0], /grid/signal [0]".to_string()
+            )]
+        );
+        assert_eq!(
+            scale_rows(&temperature),
+            vec![(
+                "Dimension scales".to_string(),
+                "[0] \"time (s)\" → /grid/time\n[1] \"x\" → /grid/x".to_string()
+            )]
+        );
+        assert!(scale_rows(&file.dataset("variable").unwrap()).is_empty());
+
+        let x = AxisScale::read(&temperature, 1).unwrap();
+        assert_eq!(x.name, "x");
+        assert_eq!(x.value_at(2.0), Some(120.0));
+        assert_eq!(x.value_at(2.5), Some(125.0));
+        assert_eq!(x.label(3.0), "130.0");
+        assert_eq!(x.label(25.0), "25");
+        assert_eq!(
+            read_every(&file.dataset("grid/x").unwrap(), 3).unwrap(),
+            vec![100.0, 130.0, 160.0, 190.0, 220.0, 250.0, 280.0]
+        );
+        let time = AxisScale::read(&file.dataset("grid/signal").unwrap(), 0).unwrap();
+        assert_eq!(
+            time.values,
+            (0..10).map(|i| i as f64 * 0.5).collect::<Vec<_>>()
+        );
+
+        let Ok(AnalysisResult::Stats(_, plots)) = hdf5_dataset_analysis_from_path(
+            &dummy.path,
+            "grid/temperature",
+            AnalysisOptions::default(),
+        ) else {
+            panic!("Expected a heatmap of the temperature");
+        };
+        let heatmap = plots.heatmap.as_ref().unwrap();
+        assert_eq!(heatmap.position_text((2, 3)), "[time (s) = 1.0, x = 130.0]");
+        assert_eq!(
+            heatmap.scales_text(),
+            "rows: time (s) 0.0…4.5, columns: x 100.0…290.0"
+        );
+        let Ok(AnalysisResult::Stats(stats, plots)) =
+            hdf5_dataset_analysis_from_path(&dummy.path, "grid/signal", AnalysisOptions::default())
+        else {
+            panic!("Expected stats of the signal");
+        };
+        let line = plots.line.as_ref().unwrap();
+        assert_eq!(line.x_scale.as_ref().unwrap().name, "time (s)");
+        let (_, preview) = stats.iter().find(|(k, _)| k == "Data preview").unwrap();
+        assert!(preview.starts_with("[0] time (s) = 0.0: 0\n[1] time (s) = 0.5: 1\n"));
+    }
+}
@@ -12,7 +12,7 @@ pub enum Event {
     Paste(String),
     AnimationTick,
     Resize,
-    TreeUpdate(TreeNode<NodeIdT>),
+    TreeUpdate(usize, TreeNode<NodeIdT>),
 }
 
 #[allow(dead_code)]
@@ -37,7 +37,7 @@ impl EventHandler {
             }
         } else {
             while let Ok(ev) = self.receiver.try_recv() {
-                if matches!(ev, Event::TreeUpdate(_)) {
+                if matches!(ev, Event::TreeUpdate(..)) {
                     return ev;
                 }
             }
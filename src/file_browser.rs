@@ -0,0 +1,239 @@
+use crate::h5_utils;
+
+use chrono::{DateTime, Local};
+use ratatui::widgets::TableState;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BrowserEntryKind {
+    ParentDir,
+    Dir,
+    Hdf5File,
+}
+
+#[derive(Debug, Clone)]
+pub struct BrowserEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub kind: BrowserEntryKind,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl BrowserEntry {
+    pub fn modified_text(&self) -> String {
+        self.modified
+            .map(|m| {
+                DateTime::<Local>::from(m)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Lists the subdirectories and HDF5 files of a directory so one can be picked and opened.
+pub struct FileBrowser {
+    pub dir: PathBuf,
+    pub entries: Vec<BrowserEntry>,
+    pub table_state: TableState,
+    pub status: Option<String>,
+    pub showing_recent_files: bool,
+}
+
+impl FileBrowser {
+    pub fn new(dir: PathBuf) -> FileBrowser {
+        let mut browser = FileBrowser {
+            dir: dir.canonicalize().unwrap_or(dir),
+            entries: vec![],
+            table_state: TableState::default(),
+            status: None,
+            showing_recent_files: false,
+        };
+        browser.refresh();
+        browser
+    }
+
+    pub fn refresh(&mut self) {
+        self.entries = list_dir(&self.dir);
+        self.status = None;
+        self.showing_recent_files = false;
+        self.table_state
+            .select((!self.entries.is_empty()).then_some(0));
+        *self.table_state.offset_mut() = 0;
+    }
+
+    /// List the recently opened files that still exist instead of the directory contents.
+    pub fn show_recent_files(&mut self, recent_files: &[PathBuf]) {
+        self.entries = recent_files
+            .iter()
+            .filter_map(|path| {
+                let metadata = fs::metadata(path).ok()?;
+                Some(BrowserEntry {
+                    path: path.clone(),
+                    name: path.to_string_lossy().to_string(),
+                    kind: BrowserEntryKind::Hdf5File,
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                })
+            })
+            .collect();
+        self.status = None;
+        self.showing_recent_files = true;
+        self.table_state
+            .select((!self.entries.is_empty()).then_some(0));
+        *self.table_state.offset_mut() = 0;
+    }
+
+    /// Re-list the directory (files may have appeared since) without losing the selected entry.
+    pub fn refresh_keeping_selection(&mut self) {
+        if self.showing_recent_files {
+            return;
+        }
+        let selected_path = self.selected().map(|e| e.path.clone());
+        self.refresh();
+        if let Some(i) = selected_path.and_then(|p| self.entries.iter().position(|e| e.path == p)) {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    pub fn selected(&self) -> Option<&BrowserEntry> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.entries.get(i))
+    }
+
+    pub fn select_relative(&mut self, delta: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let current = self.table_state.selected().unwrap_or(0) as isize;
+        let last = self.entries.len() as isize - 1;
+        self.table_state
+            .select(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    pub fn select_first(&mut self) {
+        self.select_relative(isize::MIN / 2);
+    }
+
+    pub fn select_last(&mut self) {
+        self.select_relative(isize::MAX / 2);
+    }
+
+    pub fn change_dir(&mut self, dir: PathBuf) {
+        let previous_dir = std::mem::replace(&mut self.dir, dir);
+        self.refresh();
+        // When going up, keep the directory we came from selected
+        if let Some(i) = self.entries.iter().position(|e| e.path == previous_dir) {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    pub fn go_to_parent(&mut self) {
+        if self.showing_recent_files {
+            // Back out of the recent files to the directory listing
+            self.refresh();
+        } else if let Some(parent) = self.dir.parent() {
+            self.change_dir(parent.to_path_buf());
+        }
+    }
+
+    /// Enter the selected directory, or return the selected HDF5 file so it can be opened.
+    pub fn activate_selected(&mut self) -> Option<PathBuf> {
+        let entry = self.selected()?.clone();
+        match entry.kind {
+            BrowserEntryKind::ParentDir => {
+                self.go_to_parent();
+                None
+            }
+            BrowserEntryKind::Dir => {
+                self.change_dir(entry.path);
+                None
+            }
+            BrowserEntryKind::Hdf5File => Some(entry.path),
+        }
+    }
+}
+
+fn list_dir(dir: &Path) -> Vec<BrowserEntry> {
+    let mut dirs = vec![];
+    let mut files = vec![];
+
+    match fs::read_dir(dir) {
+        Ok(read_dir) => {
+            for entry in read_dir.flatten() {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with('.') {
+                    continue;
+                }
+                // Follow symlinks so linked directories and files are listed too
+                let Ok(metadata) = fs::metadata(&path) else {
+                    continue;
+                };
+                let kind = if metadata.is_dir() {
+                    BrowserEntryKind::Dir
+                } else if metadata.is_file() && h5_utils::is_hdf5_file(&path) {
+                    BrowserEntryKind::Hdf5File
+                } else {
+                    continue;
+                };
+                let browser_entry = BrowserEntry {
+                    path,
+                    name,
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                    kind,
+                };
+                match browser_entry.kind {
+                    BrowserEntryKind::Dir => dirs.push(browser_entry),
+                    _ => files.push(browser_entry),
+                }
+            }
+        }
+        Err(e) => {
+            log::error!("Failed to read directory {:?}: {}", dir, e);
+        }
+    }
+
+    dirs.sort_by(|a, b| a.name.cmp(&b.name));
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let parent = dir.parent().map(|parent| BrowserEntry {
+        path: parent.to_path_buf(),
+        name: "..".to_string(),
+        kind: BrowserEntryKind::ParentDir,
+        size: 0,
+        modified: None,
+    });
+
+    parent.into_iter().chain(dirs).chain(files).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_dir_detects_hdf5_by_signature() {
+        let dir = std::env::temp_dir().join(format!("h5inspect_browser_{}", std::process::id()));
+        fs::create_dir_all(dir.join("subdir")).unwrap();
+
+        // Signature after a 512 byte userblock, with a misleading extension
+        let mut with_userblock = vec![0u8; 512];
+        with_userblock.extend_from_slice(b"\x89HDF\r\n\x1a\n");
+        fs::write(dir.join("data.bin"), &with_userblock).unwrap();
+        fs::write(dir.join("not_hdf5.h5"), b"just some text").unwrap();
+
+        let entries = list_dir(&dir);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["..", "subdir", "data.bin"]);
+        assert_eq!(entries[2].kind, BrowserEntryKind::Hdf5File);
+        assert_eq!(entries[2].size, 520);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
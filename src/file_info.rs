@@ -0,0 +1,229 @@
+use std::os::raw::c_int;
+use std::path::Path;
+
+use hdf5::file::FileSpaceStrategy;
+use hdf5_metno as hdf5;
+use hdf5_metno_sys::h5f::{H5F_info2_t, H5F_libver_t, H5Fget_info2};
+use hdf5_metno_sys::h5p::H5Pget_libver_bounds;
+
+use crate::num_utils;
+
+/// Format of the superblock and the file-wide storage settings, read from the file itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileFormat {
+    pub superblock_version: u32,
+    pub superblock_size: u64,
+    pub superblock_extension_size: u64,
+    /// Lowest and highest format the library may use for new objects, `H5F_libver_t` values.
+    pub libver_bounds: Option<(c_int, c_int)>,
+    pub userblock_size: u64,
+    pub file_space_strategy: FileSpaceStrategy,
+    pub file_space_page_size: u64,
+    pub free_space: u64,
+    /// Size of the file as HDF5 addresses it.
+    pub image_size: u64,
+}
+
+/// The release whose format a `H5F_libver_t` value stands for.
+fn libver_name(libver: c_int) -> String {
+    match libver {
+        0 => "earliest".to_string(),
+        1 => "1.8".to_string(),
+        2 => "1.10".to_string(),
+        3 => "1.12".to_string(),
+        4 => "1.14".to_string(),
+        5 => "2.0".to_string(),
+        other => format!("unknown ({})", other),
+    }
+}
+
+/// The oldest library that can read a superblock of `version`.
+fn superblock_reader(version: u32) -> &'static str {
+    match version {
+        0 | 1 => "any HDF5 1.x",
+        2 => "HDF5 1.8 and later",
+        3 => "HDF5 1.10 and later",
+        _ => "a newer HDF5",
+    }
+}
+
+fn strategy_text(strategy: &FileSpaceStrategy, page_size: u64) -> String {
+    match strategy {
+        FileSpaceStrategy::FreeSpaceManager {
+            paged,
+            persist,
+            threshold,
+        } => {
+            let mut parts = vec![];
+            if *paged {
+                parts.push(format!(
+                    "paged, {} pages",
+                    num_utils::file_size_fmt(page_size)
+                ));
+            }
+            parts.push(if *persist {
+                "persistent".to_string()
+            } else {
+                "not persistent".to_string()
+            });
+            parts.push(format!(
+                "threshold {}",
+                num_utils::file_size_fmt(*threshold)
+            ));
+            format!("free-space managers ({})", parts.join(", "))
+        }
+        FileSpaceStrategy::PageAggregation => "page aggregation".to_string(),
+        FileSpaceStrategy::None => "none, space is only allocated at the end".to_string(),
+    }
+}
+
+fn size_text(size: u64) -> String {
+    format!(
+        "{} ({})",
+        num_utils::file_size_fmt(size),
+        num_utils::file_size_fmt_no_scale(size)
+    )
+}
+
+impl FileFormat {
+    pub fn read(file: &hdf5::File) -> hdf5::Result<FileFormat> {
+        let fcpl = file.fcpl()?;
+        let fapl = file.fapl()?;
+        let (info, libver_bounds) = hdf5::sync::sync(|| unsafe {
+            let mut info = H5F_info2_t::default();
+            if H5Fget_info2(file.id(), &mut info) < 0 {
+                return Err(hdf5::Error::from("could not read the file info"));
+            }
+            // Read as plain ints, newer libraries know versions the enum doesn't have
+            let (mut low, mut high): (c_int, c_int) = (0, 0);
+            let status = H5Pget_libver_bounds(
+                fapl.id(),
+                (&mut low as *mut c_int).cast::<H5F_libver_t>(),
+                (&mut high as *mut c_int).cast::<H5F_libver_t>(),
+            );
+            Ok((info, (status >= 0).then_some((low, high))))
+        })?;
+        Ok(FileFormat {
+            superblock_version: info.super_.version,
+            superblock_size: info.super_.super_size,
+            superblock_extension_size: info.super_.super_ext_size,
+            libver_bounds,
+            userblock_size: fcpl.userblock(),
+            file_space_strategy: fcpl.file_space_strategy(),
+            file_space_page_size: fcpl.file_space_page_size(),
+            free_space: file.free_space(),
+            image_size: file.size(),
+        })
+    }
+
+    /// Rows for the root group, `raw_data_size` being the storage of all its datasets.
+    pub fn rows(&self, raw_data_size: u64, disk_size: Option<u64>) -> Vec<(String, String)> {
+        let mut rows = vec![(
+            "Superblock".to_string(),
+            format!(
+                "version {}, readable by {}, {}{}",
+                self.superblock_version,
+                superblock_reader(self.superblock_version),
+                num_utils::file_size_fmt(self.superblock_size),
+                if self.superblock_extension_size > 0 {
+                    format!(
+                        " + {} extension",
+                        num_utils::file_size_fmt(self.superblock_extension_size)
+                    )
+                } else {
+                    String::new()
+                }
+            ),
+        )];
+        if let Some((low, high)) = self.libver_bounds {
+            rows.push((
+                "Library version bounds".to_string(),
+                format!("{} to {}", libver_name(low), libver_name(high)),
+            ));
+        }
+        rows.push(("Userblock size".to_string(), size_text(self.userblock_size)));
+        rows.push((
+            "File space strategy".to_string(),
+            strategy_text(&self.file_space_strategy, self.file_space_page_size),
+        ));
+        rows.push(("Free space".to_string(), size_text(self.free_space)));
+        let metadata_size = self
+            .image_size
+            .saturating_sub(self.userblock_size + raw_data_size + self.free_space);
+        rows.push((
+            "Metadata / raw data".to_string(),
+            format!(
+                "{} / {}",
+                num_utils::file_size_fmt(metadata_size),
+                num_utils::file_size_fmt(raw_data_size)
+            ),
+        ));
+        rows.push((
+            "File image / on disk".to_string(),
+            match disk_size {
+                Some(disk_size) => {
+                    format!("{} / {}", size_text(self.image_size), size_text(disk_size))
+                }
+                None => size_text(self.image_size),
+            },
+        ));
+        rows
+    }
+}
+
+/// Rows with the file format details of `file` at `path`, and the library reading it.
+pub fn file_rows(file: &hdf5::File, path: &Path, raw_data_size: u64) -> Vec<(String, String)> {
+    let mut rows = match FileFormat::read(file) {
+        Ok(format) => format.rows(
+            raw_data_size,
+            std::fs::metadata(path).ok().map(|metadata| metadata.len()),
+        ),
+        Err(e) => vec![("File format".to_string(), format!("unreadable ({})", e))],
+    };
+    let (major, minor, release) = hdf5::library_version();
+    rows.push((
+        "HDF5 library".to_string(),
+        format!("{}.{}.{}", major, minor, release),
+    ));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::h5_utils;
+
+    #[test]
+    fn test_file_format() {
+        let dummy = h5_utils::dummy_file();
+        let path = &dummy.path;
+        let file = hdf5::File::open(path).unwrap();
+
+        let format = FileFormat::read(&file).unwrap();
+        assert_eq!(format.userblock_size, 512);
+        let disk_size = std::fs::metadata(path).unwrap().len();
+        assert_eq!(format.image_size, disk_size);
+
+        let rows = file_rows(&file, path, 1000);
+        let row = |key: &str| {
+            rows.iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.clone())
+                .unwrap()
+        };
+        assert!(row("Superblock").starts_with(&format!(
+            "version {}, readable by",
+            format.superblock_version
+        )));
+        assert!(row("Library version bounds").contains(" to "));
+        assert_eq!(row("Userblock size"), "512 B (512 B)");
+        assert!(row("File space strategy").starts_with("free-space managers"));
+        assert!(row("Metadata / raw data").ends_with(" / 1000 B"));
+        let image_text = size_text(disk_size);
+        assert_eq!(
+            row("File image / on disk"),
+            format!("{} / {}", image_text, image_text)
+        );
+        assert_eq!(row("HDF5 library").split('.').count(), 3);
+    }
+}
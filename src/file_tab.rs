@@ -0,0 +1,1081 @@
+use crate::advisor;
+use crate::analysis;
+use crate::analysis::{AnalysisOptions, AnalysisResult, PlotData, PlotKind};
+use crate::app::{Hdf5Object, NodeIdT};
+use crate::chunk_map::{self, ChunkMap};
+use crate::config;
+use crate::dim_scales;
+use crate::file_info;
+use crate::fill_value;
+use crate::filters;
+use crate::h5_utils;
+use crate::heatmap::Colormap;
+use crate::hexdump::HexDump;
+use crate::hist_plot::{Histogram, HistogramSettings};
+use crate::history::NavigationHistory;
+use crate::line_plot::XRange;
+use crate::num_utils;
+use crate::object_header;
+use crate::references;
+use crate::session::FileSession;
+use crate::tree::TreeNode;
+use hdf5_metno as hdf5;
+
+use chrono::{DateTime, Local};
+use nix::sys::wait::waitpid;
+use nix::unistd::{fork, ForkResult};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+
+#[derive(Debug)]
+pub enum AsyncDataAnalysis {
+    Loading,
+    Ready(analysis::AnalysisResult),
+}
+
+/// References offered by `follow-reference`, one per key from 1-9 and a-z.
+pub const MAX_FOLLOWED_REFERENCES: usize = 35;
+
+pub type ObjectInfo = (Vec<(String, String)>, Arc<PlotData>);
+
+/// Analyses by node and the options they were started with, so heatmap slices and fill value
+/// settings already seen aren't analysed again.
+type AnalysisMap = HashMap<(NodeIdT, AnalysisOptions), AsyncDataAnalysis>;
+
+/// Errors are kept so an unreadable dataset isn't read again on every frame.
+pub type SharedHexDump = Arc<Result<HexDump, String>>;
+pub type SharedChunkMap = Arc<Result<ChunkMap, String>>;
+type SharedInfoRows = Arc<Vec<(String, String)>>;
+
+/// What the object info panel shows for datasets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DatasetView {
+    #[default]
+    Info,
+    /// The raw bytes.
+    Hex,
+    /// Where each chunk is stored.
+    Chunks,
+}
+
+/// Everything that belongs to one opened file: its tree, selection and search state.
+pub struct FileTab {
+    pub h5_file_path: PathBuf,
+    pub tree_state: tui_tree_widget::TreeState<NodeIdT>,
+    pub tree_state_last_rendered_selected: Option<Vec<NodeIdT>>,
+    pub tree: Option<TreeNode<NodeIdT>>,
+    pub filtered_tree: Option<TreeNode<NodeIdT>>,
+    pub search_query_left: String,
+    pub search_query_right: String,
+    pub search_query_view_offset: u16,
+    pub object_info_scroll_state: u16,
+    node_id_to_analysis: Arc<Mutex<AnalysisMap>>,
+    /// Whether numbers equal to the fill value are left out of the stats.
+    exclude_fill_values: bool,
+    pub hovered_node: Option<Vec<NodeIdT>>,
+    pub copied_indicator: Option<(Vec<NodeIdT>, std::time::Instant)>,
+    pub tree_width_percentage: u16,
+    /// Session to restore once the tree has loaded.
+    pending_session: Option<FileSession>,
+    history: NavigationHistory<Vec<NodeIdT>>,
+    /// Set while jumping through the history so the jump itself isn't recorded.
+    navigating_history: bool,
+    /// Whether the next selection change is a single step up or down the tree.
+    pub selection_change_is_step: bool,
+    /// Named marks, stored as node names so they can be persisted.
+    pub marks: BTreeMap<char, Vec<String>>,
+    pub status_message: Option<(String, std::time::Instant)>,
+    /// Preferred plot, used whenever the selected dataset has it.
+    pub plot_kind: PlotKind,
+    pub plot_x_range: XRange,
+    pub colormap: Colormap,
+    pub histogram_settings: HistogramSettings,
+    /// Field of a compound dataset whose stats are shown.
+    pub field_index: usize,
+    /// Slice along the leading dimensions shown in the heatmap, in storage order.
+    heatmap_slice: usize,
+    pub dataset_view: DatasetView,
+    /// Hex dump of the dataset at the path, read once per selection.
+    hex_dump: Option<(Vec<NodeIdT>, SharedHexDump)>,
+    /// Chunk map of the dataset at the path, read once per selection.
+    chunk_map: Option<(Vec<NodeIdT>, SharedChunkMap)>,
+    /// Info rows of each object shown so far, read once as the file doesn't change.
+    info_rows: Mutex<HashMap<NodeIdT, SharedInfoRows>>,
+    /// Layout advice of each dataset analysed so far, empty when there is none.
+    advice: Arc<Mutex<HashMap<NodeIdT, String>>>,
+}
+
+/// `["/", "group1", "ds"]` -> `/group1/ds`
+fn join_text_path(text_path: &[String]) -> String {
+    let joined = text_path
+        .iter()
+        .skip(1)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("/");
+    format!("/{}", joined)
+}
+
+fn get_text_for_dataset(tree_node: &TreeNode<NodeIdT>) -> Vec<(String, String)> {
+    let dataset = match tree_node.hdf5_object.as_ref() {
+        Some(Hdf5Object::Dataset(dataset)) => dataset,
+        _ => panic!("Expected a Dataset, found a Group or None"),
+    };
+
+    let shape = dataset.shape();
+    let datatype: String = dataset
+        .dtype()
+        .map(|dt| h5_utils::datatype_to_text(&dt))
+        .unwrap_or("unknown".to_string());
+
+    let space = dataset
+        .space()
+        .map(|s| format!("{:?}", s))
+        .unwrap_or("unknown".to_string());
+    let chunks = dataset.chunk();
+    let chunk_info = match chunks {
+        Some(chunks) => {
+            let num_chunks: usize = chunk_map::grid_shape(&shape, &chunks).iter().product();
+            match dataset.num_chunks() {
+                Some(num_allocated) => format!(
+                    "Chunked ({:?}), {} of {} chunks allocated",
+                    chunks, num_allocated, num_chunks
+                ),
+                None => format!("Chunked ({:?})", chunks),
+            }
+        }
+        None => "Contiguous".to_string(),
+    };
+
+    // Get storage size vs data size
+    let storage_size = dataset.storage_size();
+    let data_size = dataset.size() * dataset.dtype().map_or(0, |dt| dt.size());
+    let data_size: u64 = data_size.try_into().unwrap_or(0);
+    let compression_ratio = if storage_size > 0 {
+        data_size as f64 / storage_size as f64
+    } else {
+        f64::NAN
+    };
+
+    let mut res = vec![];
+
+    res.push(("Path".to_string(), dataset.name().to_string()));
+    res.push(("Shape".to_string(), format!("{:?}", shape)));
+    res.push(("Space".to_string(), space));
+    res.extend(dim_scales::scale_rows(dataset));
+    res.push(("Chunk info".to_string(), chunk_info));
+    res.push(("Filters".to_string(), filters::pipeline_text(dataset)));
+    res.push((
+        "Storage size".to_string(),
+        format!(
+            "{} ({})",
+            num_utils::file_size_fmt(storage_size),
+            num_utils::file_size_fmt_no_scale(storage_size)
+        ),
+    ));
+    res.push((
+        "Data size".to_string(),
+        format!(
+            "{} ({})",
+            num_utils::file_size_fmt(data_size),
+            num_utils::file_size_fmt_no_scale(data_size)
+        ),
+    ));
+    res.push((
+        "Compression ratio".to_string(),
+        format!("{:.2}", compression_ratio),
+    ));
+    res.push(("Datatype".to_string(), datatype));
+    res.extend(fill_value::fill_rows(dataset));
+    if dataset.attr_names().is_ok_and(|names| !names.is_empty()) {
+        res.push(("Attributes".to_string(), h5_utils::attributes_text(dataset)));
+    }
+    res.extend(object_header::object_rows(dataset));
+    res
+}
+
+fn get_text_for_group(tree_node: &TreeNode<NodeIdT>) -> Vec<(String, String)> {
+    let group = match tree_node.hdf5_object.as_ref() {
+        Some(Hdf5Object::Group(group)) => group,
+        _ => panic!("Expected a Group, found a Dataset or None"),
+    };
+
+    let num_groups = group.groups().unwrap_or(vec![]).len();
+    let num_datasets = group.datasets().unwrap_or(vec![]).len();
+    let attrs = group.attr_names().unwrap_or(vec![]);
+    let num_attrs = attrs.len();
+
+    let mut res = vec![];
+    res.push(("Path".to_string(), group.name().to_string()));
+    res.push((
+        "Number of groups direct".to_string(),
+        num_groups.to_string(),
+    ));
+    res.push((
+        "Number of groups total".to_string(),
+        format!("{}", tree_node.recursive_num_groups),
+    ));
+    res.push((
+        "Number of datasets direct".to_string(),
+        num_datasets.to_string(),
+    ));
+    res.push((
+        "Number of datasets total".to_string(),
+        tree_node.recursive_num_datasets.to_string(),
+    ));
+    res.push(("Number of attributes".to_string(), num_attrs.to_string()));
+    if num_attrs > 0 {
+        res.push(("Attributes".to_string(), h5_utils::attributes_text(group)));
+    }
+    res.push((
+        "Storage size".to_string(),
+        format!(
+            "{} ({})",
+            num_utils::file_size_fmt(tree_node.recursive_storage_data_size),
+            num_utils::file_size_fmt_no_scale(tree_node.recursive_storage_data_size)
+        ),
+    ));
+    res.extend(object_header::object_rows(group));
+    res
+}
+
+impl FileTab {
+    pub fn new(h5_file_path: PathBuf) -> FileTab {
+        FileTab {
+            h5_file_path,
+            tree_state: tui_tree_widget::TreeState::default(),
+            tree_state_last_rendered_selected: None,
+            tree: None,
+            filtered_tree: None,
+            search_query_left: String::new(),
+            search_query_right: String::new(),
+            search_query_view_offset: 0,
+            object_info_scroll_state: 0,
+            node_id_to_analysis: Arc::new(Mutex::new(HashMap::new())),
+            exclude_fill_values: false,
+            hovered_node: None,
+            copied_indicator: None,
+            tree_width_percentage: config::get().tree_width_percentage,
+            pending_session: None,
+            history: NavigationHistory::new(),
+            navigating_history: false,
+            selection_change_is_step: false,
+            marks: BTreeMap::new(),
+            status_message: None,
+            plot_kind: PlotKind::Histogram,
+            plot_x_range: XRange::default(),
+            colormap: Colormap::Viridis,
+            histogram_settings: HistogramSettings::default(),
+            field_index: 0,
+            heatmap_slice: 0,
+            dataset_view: DatasetView::Info,
+            hex_dump: None,
+            chunk_map: None,
+            info_rows: Mutex::new(HashMap::new()),
+            advice: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn restore_session(&mut self, session: FileSession) {
+        self.tree_width_percentage = session.tree_width_percentage.clamp(10, 90);
+        self.marks = session.marks.clone();
+        self.pending_session = Some(session);
+    }
+
+    /// Capture the selection, opened nodes, search query and layout so they can be restored later.
+    pub fn session(&self) -> Option<FileSession> {
+        let Some(tree) = &self.tree else {
+            return self.pending_session.clone();
+        };
+        let mut opened: Vec<Vec<String>> = self
+            .tree_state
+            .opened()
+            .iter()
+            // Leaves are opened too when expanding everything, they don't need remembering
+            .filter(|path| {
+                tree.get_selected_node(path)
+                    .is_some_and(|node| !node.children().is_empty())
+            })
+            .filter_map(|path| tree.text_path(path))
+            .collect();
+        opened.sort();
+        Some(FileSession {
+            selected: tree
+                .text_path(self.tree_state.selected())
+                .unwrap_or_default(),
+            opened,
+            search_query: self.search_query_and_cursor().0,
+            tree_width_percentage: self.tree_width_percentage,
+            marks: self.marks.clone(),
+        })
+    }
+
+    /// Short name used for the tab title.
+    pub fn title(&self) -> String {
+        self.h5_file_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.h5_file_path.to_string_lossy().to_string())
+    }
+
+    pub fn tree_from_h5(h5_file: &hdf5::File) -> Result<TreeNode<NodeIdT>, std::io::Error> {
+        fn tree_from_group(group_name: &str, group: hdf5::Group) -> TreeNode<NodeIdT> {
+            // TODO: avoid circular walks
+            // The identifier for each TreeNode is the unmodified hdf5 group/dataset name.
+            // The name is the full path inside the hdf5 file.
+            // This allows us to retrieve the object later
+
+            let mut children: Vec<_> = h5_utils::groups(&group)
+                .unwrap_or(vec![])
+                .into_iter()
+                .map(|(name, child)| tree_from_group(&name, child))
+                .collect();
+
+            let datasets = h5_utils::datasets(&group).unwrap_or(vec![]);
+
+            for (dataset_name, dataset) in datasets.into_iter() {
+                let text = dataset_name.clone();
+                let node_id = dataset.id();
+                children.push(
+                    TreeNode::new(node_id, text, vec![])
+                        .set_storage_dataset_size(dataset.storage_size())
+                        .set_dimension_scale(dim_scales::is_dimension_scale(&dataset))
+                        .set_hdf5_object(Hdf5Object::Dataset(Arc::new(dataset))),
+                );
+            }
+
+            TreeNode::new(group.id(), group_name, children)
+                .set_hdf5_object(Hdf5Object::Group(group))
+        }
+        // TODO anonymous datasets
+
+        let root_name = "/";
+        let root_group = h5_file.group(root_name).expect("Couldn't open root group");
+        Ok(tree_from_group(root_name, root_group))
+    }
+
+    pub fn set_tree(&mut self, tree: TreeNode<NodeIdT>) {
+        self.tree = Some(tree);
+        match self.pending_session.take() {
+            Some(session) => self.apply_session(session),
+            None => self.open_all_tree_nodes(),
+        }
+        self.update_filtered_tree();
+    }
+
+    fn apply_session(&mut self, session: FileSession) {
+        let Some(tree) = &self.tree else {
+            return;
+        };
+        for text_path in &session.opened {
+            if let Some(id_path) = tree.id_path(text_path) {
+                self.tree_state.open(id_path);
+            }
+        }
+        if let Some(id_path) = tree.id_path(&session.selected) {
+            self.tree_state.select(id_path);
+        }
+        self.search_query_left = session.search_query;
+        self.search_query_right.clear();
+        log::debug!("Restored session for {:?}", self.h5_file_path);
+    }
+
+    pub fn set_status(&mut self, message: String) {
+        log::info!("{}", message);
+        self.status_message = Some((message, std::time::Instant::now()));
+    }
+
+    /// Select `path`, opening its ancestors and clearing a search that would hide it.
+    fn jump_to(&mut self, path: Vec<NodeIdT>) {
+        let hidden_by_search = self
+            .filtered_tree
+            .as_ref()
+            .is_none_or(|filtered_tree| filtered_tree.get_selected_node(&path).is_none());
+        if hidden_by_search {
+            self.search_query_left.clear();
+            self.search_query_right.clear();
+        }
+        for i in 1..path.len() {
+            self.tree_state.open(path[0..i].to_vec());
+        }
+        self.tree_state.select(path);
+        if hidden_by_search {
+            self.update_filtered_tree();
+        }
+        self.tree_state.scroll_selected_into_view();
+    }
+
+    pub fn history_back(&mut self) {
+        match self.history.back().cloned() {
+            Some(path) => {
+                self.navigating_history = true;
+                self.jump_to(path);
+            }
+            None => self.set_status("Already at the oldest selection".to_string()),
+        }
+    }
+
+    pub fn history_forward(&mut self) {
+        match self.history.forward().cloned() {
+            Some(path) => {
+                self.navigating_history = true;
+                self.jump_to(path);
+            }
+            None => self.set_status("Already at the newest selection".to_string()),
+        }
+    }
+
+    pub fn set_mark(&mut self, name: char) {
+        let Some(text_path) = self
+            .tree
+            .as_ref()
+            .and_then(|tree| tree.text_path(self.tree_state.selected()))
+        else {
+            return;
+        };
+        self.set_status(format!(
+            "Mark '{}' set to {}",
+            name,
+            join_text_path(&text_path)
+        ));
+        self.marks.insert(name, text_path);
+    }
+
+    pub fn jump_to_mark(&mut self, name: char) {
+        let Some(text_path) = self.marks.get(&name) else {
+            self.set_status(format!("Mark '{}' is not set", name));
+            return;
+        };
+        match self.tree.as_ref().and_then(|tree| tree.id_path(text_path)) {
+            Some(path) => self.jump_to(path),
+            None => {
+                let message = format!(
+                    "Mark '{}' points to {}, which no longer exists",
+                    name,
+                    join_text_path(text_path)
+                );
+                self.set_status(message);
+            }
+        }
+    }
+
+    /// Paths of the objects referenced by the selection's attributes, then by its values.
+    pub fn referenced_paths(&self) -> Vec<String> {
+        let Some(object) = self
+            .tree
+            .as_ref()
+            .and_then(|tree| tree.get_selected_node(self.tree_state.selected()))
+            .and_then(|node| node.hdf5_object.as_ref())
+        else {
+            return vec![];
+        };
+        let (location, dataset): (&hdf5::Location, _) = match object {
+            Hdf5Object::Dataset(dataset) => (dataset, Some(dataset)),
+            Hdf5Object::Group(group) => (group, None),
+        };
+        let mut targets = vec![];
+        for name in location.attr_names().unwrap_or_default() {
+            if let Ok(Some(attr_targets)) = location
+                .attr(&name)
+                .and_then(|attr| references::read_references(&attr))
+            {
+                targets.extend(attr_targets);
+            }
+        }
+        if let Some(dataset) = dataset.filter(|d| d.size() <= MAX_FOLLOWED_REFERENCES) {
+            if let Ok(Some(dataset_targets)) = references::read_references(dataset) {
+                targets.extend(dataset_targets);
+            }
+        }
+        let mut paths: Vec<String> = vec![];
+        for path in targets.into_iter().filter_map(|t| t.path) {
+            if !paths.contains(&path) && paths.len() < MAX_FOLLOWED_REFERENCES {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    /// Select the object at `object_path`, e.g. `/group1/pixels`.
+    pub fn jump_to_object(&mut self, object_path: &str) {
+        let mut text_path = vec!["/".to_string()];
+        text_path.extend(
+            object_path
+                .split('/')
+                .filter(|part| !part.is_empty())
+                .map(str::to_string),
+        );
+        match self.tree.as_ref().and_then(|tree| tree.id_path(&text_path)) {
+            Some(path) => self.jump_to(path),
+            None => self.set_status(format!("{} is not in the tree", object_path)),
+        }
+    }
+
+    /// Switch datasets to `view`, or back to their info if they already show it.
+    pub fn toggle_dataset_view(&mut self, view: DatasetView) {
+        self.dataset_view = if self.dataset_view == view {
+            DatasetView::Info
+        } else {
+            view
+        };
+        self.object_info_scroll_state = 0;
+    }
+
+    fn selected_dataset(&self) -> Option<&hdf5::Dataset> {
+        let node = self
+            .tree
+            .as_ref()?
+            .get_selected_node(self.tree_state.selected())?;
+        match &node.hdf5_object {
+            Some(Hdf5Object::Dataset(dataset)) => Some(dataset),
+            _ => None,
+        }
+    }
+
+    /// The raw bytes of the selected dataset, when the hex view is on.
+    pub fn selected_hex_dump(&mut self) -> Option<SharedHexDump> {
+        if self.dataset_view != DatasetView::Hex {
+            return None;
+        }
+        let selected = self.tree_state.selected().to_vec();
+        if let Some((path, hex_dump)) = &self.hex_dump {
+            if *path == selected {
+                return Some(hex_dump.clone());
+            }
+        }
+        let hex_dump = Arc::new(HexDump::read(self.selected_dataset()?).map_err(|e| e.to_string()));
+        self.hex_dump = Some((selected, hex_dump.clone()));
+        Some(hex_dump)
+    }
+
+    /// The chunks of the selected dataset, when the chunk map is on.
+    pub fn selected_chunk_map(&mut self) -> Option<SharedChunkMap> {
+        if self.dataset_view != DatasetView::Chunks {
+            return None;
+        }
+        let selected = self.tree_state.selected().to_vec();
+        if let Some((path, chunk_map)) = &self.chunk_map {
+            if *path == selected {
+                return Some(chunk_map.clone());
+            }
+        }
+        let chunk_map =
+            Arc::new(ChunkMap::read(self.selected_dataset()?).map_err(|e| e.to_string()));
+        self.chunk_map = Some((selected, chunk_map.clone()));
+        Some(chunk_map)
+    }
+
+    /// The plot to show for `plot_data`: the preferred one if available.
+    pub fn plot_kind_for(&self, plot_data: &PlotData) -> Option<PlotKind> {
+        let available = plot_data.available();
+        if available.contains(&self.plot_kind) {
+            Some(self.plot_kind)
+        } else {
+            available.first().copied()
+        }
+    }
+
+    fn selected_plot_data(&self) -> Arc<PlotData> {
+        self.tree
+            .as_ref()
+            .and_then(|tree| tree.get_selected_node(self.tree_state.selected()))
+            .map(|node| self.analysis_for(node.id(), 0).1)
+            .unwrap_or_default()
+    }
+
+    /// Switch to the next plot the selected dataset has.
+    pub fn cycle_plot(&mut self) {
+        let plot_data = self.selected_plot_data();
+        let available = plot_data.available();
+        let Some(current) = self.plot_kind_for(&plot_data) else {
+            return;
+        };
+        let index = available
+            .iter()
+            .position(|kind| *kind == current)
+            .unwrap_or(0);
+        self.plot_kind = available[(index + 1) % available.len()];
+    }
+
+    /// Zoom the line plot by `factor`, `< 1` zooms in.
+    pub fn zoom_plot(&mut self, factor: f64) {
+        if let Some(line_data) = &self.selected_plot_data().line {
+            // Keep a couple of values in view
+            let min_width = 2.0 / line_data.len as f64;
+            self.plot_x_range.zoom(factor, min_width);
+        }
+    }
+
+    pub fn selected_histogram(&self) -> Option<Histogram> {
+        let plot_data = self.selected_plot_data();
+        let source = plot_data.histogram.as_ref()?;
+        Some(source.compute(&self.histogram_settings))
+    }
+
+    pub fn pan_plot(&mut self, amount: f64) {
+        self.plot_x_range.pan(amount);
+    }
+
+    /// Number of fields analysed separately in the selected dataset, 0 unless it is a compound.
+    pub fn num_selected_fields(&self) -> usize {
+        let Some(node) = self
+            .tree
+            .as_ref()
+            .and_then(|tree| tree.get_selected_node(self.tree_state.selected()))
+        else {
+            return 0;
+        };
+        let info_dict = self.node_id_to_analysis.lock().unwrap();
+        match info_dict.get(&(node.id(), self.analysis_options())) {
+            Some(AsyncDataAnalysis::Ready(analysis::AnalysisResult::Fields(fields))) => {
+                fields.len()
+            }
+            _ => 0,
+        }
+    }
+
+    /// Show the stats of the field `offset` away from the current one, wrapping around.
+    pub fn select_field(&mut self, offset: isize) {
+        let num_fields = self.num_selected_fields();
+        if num_fields > 0 {
+            let index = (self.field_index % num_fields) as isize + offset;
+            self.field_index = index.rem_euclid(num_fields as isize) as usize;
+            self.plot_x_range = XRange::default();
+        }
+    }
+
+    pub fn get_num_active_data_analysis_tasks(&self) -> usize {
+        let info_dict = self.node_id_to_analysis.lock().unwrap();
+        info_dict
+            .values()
+            .filter(|&v| matches!(v, AsyncDataAnalysis::Loading))
+            .count()
+    }
+
+    /// Path of the hdf5 object at `path` in the tree, e.g. `/group1/bool_ds`.
+    pub fn object_name_at(&self, path: &[NodeIdT]) -> Option<String> {
+        self.tree
+            .as_ref()
+            .and_then(|tree| tree.get_selected_node(path))
+            .and_then(|node| match &node.hdf5_object {
+                Some(Hdf5Object::Dataset(dataset)) => Some(dataset.name().to_string()),
+                Some(Hdf5Object::Group(group)) => Some(group.name().to_string()),
+                _ => None,
+            })
+    }
+
+    /// Info rows of the object at `tree_node`, read from the file.
+    fn read_info_rows(&self, tree_node: &TreeNode<NodeIdT>) -> Option<Vec<(String, String)>> {
+        match tree_node.hdf5_object.as_ref()? {
+            Hdf5Object::Dataset(_) => Some(get_text_for_dataset(tree_node)),
+            Hdf5Object::Group(group) => {
+                let mut info = get_text_for_group(tree_node);
+
+                // If this is the root group, add file info
+                if group.name() == "/" {
+                    let path = &self.h5_file_path;
+                    if let Ok(metadata) = fs::metadata(path) {
+                        if let Ok(modified) = metadata.modified() {
+                            // Convert SystemTime -> chrono::DateTime<Local>
+                            let datetime: DateTime<Local> = DateTime::<Local>::from(modified);
+                            info.push((
+                                "File last modified".to_string(),
+                                datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+                            ));
+                        }
+
+                        // Permissions
+                        let perms = metadata.permissions();
+                        #[cfg(unix)]
+                        {
+                            info.push((
+                                "File permissions".to_string(),
+                                format!("{:o}", perms.mode()),
+                            ));
+                        }
+                    }
+                    if let Ok(file) = group.file() {
+                        info.extend(file_info::file_rows(
+                            &file,
+                            path,
+                            tree_node.recursive_storage_data_size,
+                        ));
+                    }
+                }
+                Some(info)
+            }
+        }
+    }
+
+    /// Stats rows and plots of the analysis of `key`, empty if it wasn't started.
+    fn analysis_for(&self, key: NodeIdT, animation_state: u8) -> ObjectInfo {
+        let mut stats_text: Vec<(String, String)> = vec![];
+        let mut plot_data = Arc::new(PlotData::default());
+
+        let info_dict = self.node_id_to_analysis.lock().unwrap();
+
+        if let Some(node_info) = info_dict.get(&(key, self.analysis_options())) {
+            match node_info {
+                AsyncDataAnalysis::Loading => {
+                    stats_text = vec![(
+                        "Stats".into(),
+                        "Loading".to_owned() + &".".repeat((animation_state / 3 % 4).into()),
+                    )]
+                }
+                AsyncDataAnalysis::Ready(val) => match val {
+                    analysis::AnalysisResult::Failed(s) => {
+                        stats_text = vec![("Stats".into(), format!("Failed! ({})", s))];
+                    }
+                    analysis::AnalysisResult::NotAvailable => {
+                        stats_text = vec![("Stats".into(), "Not available".into())];
+                    }
+                    analysis::AnalysisResult::Stats(stats, plots) => {
+                        stats_text = stats.to_vec();
+                        plot_data = Arc::clone(plots);
+                    }
+                    analysis::AnalysisResult::Fields(fields) => {
+                        let field = &fields[self.field_index % fields.len()];
+                        stats_text = vec![(
+                            "Field".into(),
+                            format!(
+                                "{} ({}/{})",
+                                field.name,
+                                self.field_index % fields.len() + 1,
+                                fields.len()
+                            ),
+                        )];
+                        stats_text.extend(field.stats.iter().cloned());
+                        plot_data = Arc::clone(&field.plots);
+                    }
+                },
+            }
+        }
+        (stats_text, plot_data)
+    }
+
+    pub fn get_text_for(&self, path: &[NodeIdT], animation_state: u8) -> Option<ObjectInfo> {
+        let tree = self.tree.as_ref()?;
+
+        let tree_node = tree.get_selected_node(path)?;
+        let key = tree_node.id();
+
+        let cached_rows = self.info_rows.lock().unwrap().get(&key).cloned();
+        let rows = match cached_rows {
+            Some(rows) => rows,
+            None => {
+                let rows = Arc::new(self.read_info_rows(tree_node)?);
+                self.info_rows
+                    .lock()
+                    .unwrap()
+                    .insert(key, Arc::clone(&rows));
+                rows
+            }
+        };
+        let mut info = rows.to_vec();
+
+        match tree_node.hdf5_object.as_ref()? {
+            Hdf5Object::Dataset(_) => {
+                // Found by the analysis process, reading the data takes too long for a frame
+                if let Some(advice) = self.advice.lock().unwrap().get(&key) {
+                    if !advice.is_empty() {
+                        info.push(("Advice".into(), advice.clone()));
+                    }
+                }
+
+                let (stats_text, plot_data) = self.analysis_for(key, animation_state);
+                info.extend(stats_text);
+
+                Some((info, plot_data))
+            }
+            Hdf5Object::Group(_) => Some((info, Arc::new(PlotData::default()))),
+        }
+    }
+
+    pub fn search_query_and_cursor(&self) -> (String, u16) {
+        let rev_right: String = self.search_query_right.chars().rev().collect();
+        let text = self.search_query_left.clone() + &rev_right;
+        let cursor_pos = self.search_query_left.len();
+
+        (text, cursor_pos.try_into().unwrap())
+    }
+
+    pub fn update_filtered_tree(&mut self) {
+        let query = &self.search_query_and_cursor().0;
+        match &self.tree {
+            Some(tree) => {
+                self.filtered_tree = tree.filter(query);
+                self.update_selected_tree_item();
+            }
+            None => {
+                self.filtered_tree = None;
+            }
+        }
+    }
+
+    fn update_selected_tree_item(&mut self) {
+        match &self.filtered_tree {
+            Some(filtered_tree) => {
+                let nothing_selected = self.tree_state.selected().is_empty();
+                let selected_item = filtered_tree.get_selected_node(self.tree_state.selected());
+                let selected_item_is_in_tree = selected_item.is_some();
+                let selected_item_is_direct_match =
+                    selected_item.is_some_and(|t| t.is_direct_match);
+
+                if nothing_selected || !selected_item_is_in_tree || !selected_item_is_direct_match {
+                    let first_match = filtered_tree.path_to_first_match();
+                    self.tree_state.select(first_match.clone());
+                    for i in 0..first_match.len() {
+                        self.tree_state.open(first_match[0..i].to_vec());
+                    }
+                }
+                self.tree_state.scroll_selected_into_view();
+            }
+            None => {
+                self.tree_state.select(vec![]);
+            }
+        }
+    }
+
+    pub fn open_all_tree_nodes(&mut self) {
+        if let Some(tree) = &self.tree {
+            let mut to_visit = vec![(tree, vec![tree.id()])];
+            while let Some((current, id_path)) = to_visit.pop() {
+                self.tree_state.open(id_path.clone());
+                to_visit.extend(current.children().iter().map(|c| {
+                    let mut id_path = id_path.clone();
+                    id_path.push(c.id());
+                    (c, id_path)
+                }));
+            }
+        }
+    }
+
+    /// Kick off the analysis of the selected dataset if the selection changed since the last render.
+    pub fn on_selection_maybe_changed(&mut self, semaphore: &Arc<Semaphore>) {
+        if let Some(last_selected) = &self.tree_state_last_rendered_selected {
+            if last_selected != self.tree_state.selected() {
+                // if the selected node has changed, reset the scroll state
+                self.object_info_scroll_state = 0;
+                self.plot_x_range = XRange::default();
+                self.field_index = 0;
+                self.heatmap_slice = 0;
+
+                // Jumps through the history are already in it
+                if !std::mem::take(&mut self.navigating_history)
+                    && !self.tree_state.selected().is_empty()
+                {
+                    self.history.record(
+                        self.tree_state.selected().to_vec(),
+                        self.selection_change_is_step,
+                    );
+                }
+                self.selection_change_is_step = false;
+
+                if let Some(tree_node) = self
+                    .tree
+                    .as_ref()
+                    .and_then(|tree| tree.get_selected_node(self.tree_state.selected()))
+                {
+                    self.start_analysis_task(tree_node, semaphore);
+                }
+            }
+        }
+    }
+
+    /// Leaves fill values out of the stats or puts them back, and analyses the selection again.
+    /// Returns whether they are now left out.
+    pub fn toggle_exclude_fill_values(&mut self, semaphore: &Arc<Semaphore>) -> bool {
+        self.exclude_fill_values = !self.exclude_fill_values;
+        self.analyse_selection(semaphore);
+        self.exclude_fill_values
+    }
+
+    /// Show the heatmap slice `offset` away from the current one, wrapping around, and analyse it.
+    /// Returns the number of slices, nothing if the selection isn't a dataset.
+    pub fn select_heatmap_slice(
+        &mut self,
+        offset: isize,
+        semaphore: &Arc<Semaphore>,
+    ) -> Option<usize> {
+        let num_slices = match self
+            .tree
+            .as_ref()
+            .and_then(|tree| tree.get_selected_node(self.tree_state.selected()))
+            .and_then(|node| node.hdf5_object.as_ref())
+        {
+            Some(Hdf5Object::Dataset(d)) => analysis::num_heatmap_slices(&d.shape()),
+            _ => return None,
+        };
+        if num_slices > 1 {
+            let index = self.heatmap_slice.min(num_slices - 1) as isize + offset;
+            self.heatmap_slice = index.rem_euclid(num_slices as isize) as usize;
+            self.analyse_selection(semaphore);
+        }
+        Some(num_slices)
+    }
+
+    fn analysis_options(&self) -> AnalysisOptions {
+        AnalysisOptions {
+            exclude_fill_values: self.exclude_fill_values,
+            heatmap_slice: self.heatmap_slice,
+        }
+    }
+
+    fn analyse_selection(&self, semaphore: &Arc<Semaphore>) {
+        if let Some(tree_node) = self
+            .tree
+            .as_ref()
+            .and_then(|tree| tree.get_selected_node(self.tree_state.selected()))
+        {
+            self.start_analysis_task(tree_node, semaphore);
+        }
+    }
+
+    fn start_analysis_task(&self, tree_node: &TreeNode<NodeIdT>, semaphore: &Arc<Semaphore>) {
+        if let Some(Hdf5Object::Dataset(d)) = &tree_node.hdf5_object {
+            let key = tree_node.id();
+            let options = self.analysis_options();
+            let summary;
+            {
+                let mut info_dict = self.node_id_to_analysis.lock().unwrap();
+                if info_dict.contains_key(&(key, options)) {
+                    // already being processed or done
+                    return;
+                }
+                // Another slice of the heatmap only needs that slice read, the summary stays the same
+                summary = info_dict
+                    .iter()
+                    .find_map(|((k, o), analysis)| match analysis {
+                        AsyncDataAnalysis::Ready(AnalysisResult::Stats(stats, _))
+                            if *k == key
+                                && o.exclude_fill_values == options.exclude_fill_values
+                                && d.ndim() > 2 =>
+                        {
+                            Some(analysis::summary_rows(stats).to_vec())
+                        }
+                        _ => None,
+                    });
+                info_dict.insert((key, options), AsyncDataAnalysis::Loading);
+            }
+
+            let thread_arc: Arc<Mutex<AnalysisMap>> = Arc::clone(&self.node_id_to_analysis);
+            let advice = Arc::clone(&self.advice);
+            // The advice only depends on how the dataset is stored, other options reuse it
+            let needs_advice = !self.advice.lock().unwrap().contains_key(&key);
+            let semaphore = Arc::clone(semaphore);
+
+            // Get the file path and dataset path to pass to the worker process
+            let file_path = self.h5_file_path.to_string_lossy().to_string();
+            let dataset_path = d.name().to_string();
+
+            // Spawn analysis in a completely separate process
+            // Ideally it would just happpen in a separate thread, but the hdf5 read operation uses a processs wide lock
+            // so when we want to read basic stats of the dataset, or another dataset at the same time, we are forced to wait
+            // ideally the hdf5 library would support finer grained locking, but until then we have to do this workaround.
+            // Even if I open the file anew in the new thread, we still get blocked by the process wide lock.
+            tokio::spawn(async move {
+                // Acquire semaphore permit to limit concurrent processes
+                // This will wait until a permit becomes available
+                let _permit = semaphore
+                    .acquire()
+                    .await
+                    .expect("Semaphore should not be closed");
+
+                log::debug!("Forking analysis process for dataset {}", &dataset_path);
+
+                let (tx, rx) = ipc_channel::ipc::channel::<(AnalysisResult, String)>()
+                    .expect("Failed to create ipc-channel");
+
+                let file_path_buf = std::path::PathBuf::from(&file_path);
+                let dataset_path_clone = dataset_path.clone();
+
+                let result = tokio::task::spawn_blocking(move || {
+                    match unsafe { fork() } {
+                        Ok(ForkResult::Parent { child }) => {
+                            // Drop parent's copy of sender
+                            drop(tx);
+
+                            // Wait for the child to send back the analysis results
+                            let msg_res = rx.recv();
+
+                            // Reap the child process to avoid zombie processes
+                            let _ = waitpid(child, None);
+
+                            match msg_res {
+                                Ok(analysis) => Ok(analysis),
+                                Err(e) => Err(format!("IPC receive failed: {:?}", e)),
+                            }
+                        }
+                        Ok(ForkResult::Child) => {
+                            // Disable logging in the child process to avoid locks and output corruption
+                            log::set_max_level(log::LevelFilter::Off);
+
+                            // Drop child's copy of receiver
+                            drop(rx);
+
+                            // Perform the analysis
+                            let res = match summary {
+                                Some(summary) => crate::analysis::hdf5_heatmap_slice_from_path(
+                                    &file_path_buf,
+                                    &dataset_path_clone,
+                                    options,
+                                    summary,
+                                ),
+                                None => crate::analysis::hdf5_dataset_analysis_from_path(
+                                    &file_path_buf,
+                                    &dataset_path_clone,
+                                    options,
+                                ),
+                            };
+
+                            let processed_analysis = match res {
+                                Ok(analysis) => analysis,
+                                Err(e) => AnalysisResult::Failed(e.to_string()),
+                            };
+                            let dataset_advice = if needs_advice {
+                                advisor::dataset_advice_from_path(
+                                    &file_path_buf,
+                                    &dataset_path_clone,
+                                )
+                                .unwrap_or_default()
+                            } else {
+                                String::new()
+                            };
+
+                            // Send back the results
+                            let _ = tx.send((processed_analysis, dataset_advice));
+
+                            // Exit immediately to prevent child from running any other logic
+                            std::process::exit(0);
+                        }
+                        Err(e) => Err(format!("Fork failed: {}", e)),
+                    }
+                })
+                .await;
+
+                let processed_analysis = match result {
+                    Ok(Ok((analysis, dataset_advice))) => {
+                        if needs_advice {
+                            if let Ok(mut advice) = advice.lock() {
+                                advice.insert(key, dataset_advice);
+                            }
+                        }
+                        analysis
+                    }
+                    Ok(Err(err_msg)) => AnalysisResult::Failed(err_msg),
+                    Err(join_err) => AnalysisResult::Failed(format!("Task panic: {}", join_err)),
+                };
+
+                if let Ok(mut info_dict) = thread_arc.lock() {
+                    info_dict.insert((key, options), AsyncDataAnalysis::Ready(processed_analysis));
+                }
+
+                // The permit is automatically dropped here, releasing the semaphore slot
+            });
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use hdf5::plist::dataset_create::{AllocTime, FillTime, FillValue};
+use hdf5_metno::{self as hdf5, Dataset};
+
+use crate::chunk_map;
+
+fn fill_value_text(d: &Dataset) -> hdf5::Result<String> {
+    let dcpl = d.dcpl()?;
+    let origin = match dcpl.fill_value_defined() {
+        FillValue::Undefined => {
+            return Ok("Undefined, unwritten elements read as whatever is on disk".to_string())
+        }
+        FillValue::Default => "library default",
+        FillValue::UserDefined => "user-defined",
+    };
+    let descriptor = d.dtype()?.to_descriptor()?;
+    Ok(match dcpl.fill_value(&descriptor) {
+        Some(value) => format!("{} ({})", value, origin),
+        None => format!("unreadable ({})", origin),
+    })
+}
+
+fn alloc_time_text(alloc_time: AllocTime) -> &'static str {
+    match alloc_time {
+        AllocTime::Early => "early, all of it when the dataset is created",
+        AllocTime::Incr => "incremental, chunk by chunk as they are written",
+        AllocTime::Late => "late, all of it on the first write",
+    }
+}
+
+fn fill_time_text(fill_time: FillTime) -> &'static str {
+    match fill_time {
+        FillTime::IfSet => "on allocation, if the fill value is user-defined",
+        FillTime::Alloc => "on allocation",
+        FillTime::Never => "never, unwritten elements keep what is on disk",
+    }
+}
+
+/// How much of the dataset has storage, the rest reads as the fill value.
+fn allocated_text(d: &Dataset) -> String {
+    if let (Some(chunk_shape), Some(num_allocated)) = (d.chunk(), d.num_chunks()) {
+        let num_chunks: usize = chunk_map::grid_shape(&d.shape(), &chunk_shape)
+            .iter()
+            .product();
+        if num_allocated >= num_chunks {
+            return format!("All {} chunks", num_chunks);
+        }
+        return format!(
+            "{} of {} chunks ({:.1}%), the other {} read as the fill value",
+            num_allocated,
+            num_chunks,
+            100.0 * num_allocated as f64 / num_chunks as f64,
+            num_chunks - num_allocated
+        );
+    }
+    if d.size() > 0 && d.storage_size() == 0 {
+        "Nothing, all elements read as the fill value".to_string()
+    } else {
+        "All of it".to_string()
+    }
+}
+
+/// Fill value, when storage is allocated and filled, and how much of it is.
+pub fn fill_rows(d: &Dataset) -> Vec<(String, String)> {
+    let mut rows = vec![(
+        "Fill value".to_string(),
+        fill_value_text(d).unwrap_or_else(|e| format!("unreadable ({})", e)),
+    )];
+    if let Ok(dcpl) = d.dcpl() {
+        rows.push((
+            "Space allocation".to_string(),
+            alloc_time_text(dcpl.alloc_time()).to_string(),
+        ));
+        rows.push((
+            "Fill time".to_string(),
+            fill_time_text(dcpl.fill_time()).to_string(),
+        ));
+    }
+    rows.push(("Allocated".to_string(), allocated_text(d)));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::h5_utils;
+
+    #[test]
+    fn test_fill_rows() {
+        let dummy = h5_utils::dummy_file();
+        let file = hdf5::File::open(&dummy.path).unwrap();
+
+        let rows = |dataset: &str| fill_rows(&file.dataset(dataset).unwrap());
+        assert_eq!(
+            rows("variable"),
+            vec![
+                (
+                    "Fill value".to_string(),
+                    "0.0 (library default)".to_string()
+                ),
+                (
+                    "Space allocation".to_string(),
+                    alloc_time_text(AllocTime::Incr).to_string()
+                ),
+                (
+                    "Fill time".to_string(),
+                    fill_time_text(FillTime::Alloc).to_string()
+                ),
+                (
+                    "Allocated".to_string(),
+                    "2 of 10 chunks (20.0%), the other 8 read as the fill value".to_string()
+                ),
+            ]
+        );
+        let prefilled = rows("fill/prefilled");
+        assert_eq!(prefilled[0].1, "-999.0 (user-defined)");
+        assert_eq!(prefilled[1].1, alloc_time_text(AllocTime::Early));
+        assert_eq!(prefilled[3].1, "All of it");
+        assert_eq!(
+            rows("fill/unwritten")[3].1,
+            "Nothing, all elements read as the fill value"
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_uint};
+use std::ptr;
+
+use hdf5_metno as hdf5;
+use hdf5_metno_sys::h5p::{H5Pget_filter2, H5Pget_nfilters};
+use hdf5_metno_sys::h5z::{
+    H5Z_filter_t, H5Zfilter_avail, H5Z_FILTER_DEFLATE, H5Z_FILTER_FLETCHER32, H5Z_FILTER_NBIT,
+    H5Z_FILTER_SCALEOFFSET, H5Z_FILTER_SHUFFLE, H5Z_FILTER_SZIP, H5Z_FLAG_OPTIONAL,
+    H5_SZIP_EC_OPTION_MASK, H5_SZIP_NN_OPTION_MASK,
+};
+
+const BZIP2_FILTER_ID: H5Z_filter_t = 307;
+const BLOSC_FILTER_ID: H5Z_filter_t = 32001;
+const ZSTD_FILTER_ID: H5Z_filter_t = 32015;
+
+/// Names of the filters registered with The HDF Group, for plugins that aren't loaded.
+const REGISTERED_FILTERS: &[(H5Z_filter_t, &str)] = &[
+    (305, "LZO"),
+    (307, "bzip2"),
+    (32000, "LZF"),
+    (32001, "Blosc"),
+    (32002, "MAFISC"),
+    (32003, "Snappy"),
+    (32004, "LZ4"),
+    (32005, "APAX"),
+    (32006, "CBF"),
+    (32007, "JPEG-XR"),
+    (32008, "bitshuffle"),
+    (32009, "SPDP"),
+    (32010, "LPC-Rice"),
+    (32011, "CCSDS-123"),
+    (32012, "JPEG-LS"),
+    (32013, "zfp"),
+    (32014, "fpzip"),
+    (32015, "Zstandard"),
+    (32016, "B³D"),
+    (32017, "SZ"),
+    (32018, "FCIDECOMP"),
+    (32019, "JPEG"),
+    (32020, "VBZ"),
+    (32021, "FAPEC"),
+    (32022, "BitGroom"),
+    (32023, "Granular BitRound"),
+    (32024, "SZ3"),
+    (32025, "Delta-Rice"),
+    (32026, "Blosc2"),
+    (32027, "FLAC"),
+    (32028, "SPERR"),
+];
+
+/// Compressors Blosc can use, by the code stored in its parameters.
+const BLOSC_COMPRESSORS: [&str; 6] = ["blosclz", "lz4", "lz4hc", "snappy", "zlib", "zstd"];
+
+/// One filter of a dataset's pipeline, as stored in its creation property list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineFilter {
+    pub id: H5Z_filter_t,
+    /// Name stored with the filter, empty if the writer didn't give one.
+    pub name: String,
+    pub params: Vec<c_uint>,
+    /// Optional filters are skipped when they fail instead of failing the write.
+    pub optional: bool,
+    /// Whether this HDF5 library can run the filter, built in or as a plugin.
+    pub available: bool,
+}
+
+/// The filters applied to the chunks of `dataset`, in the order they are applied when writing.
+pub fn read_pipeline(dataset: &hdf5::Dataset) -> hdf5::Result<Vec<PipelineFilter>> {
+    let dcpl = dataset.dcpl()?;
+    let mut filters = vec![];
+    hdf5::sync::sync(|| unsafe {
+        let num_filters = H5Pget_nfilters(dcpl.id());
+        for index in 0..num_filters.max(0) as c_uint {
+            let mut flags: c_uint = 0;
+            let mut params: Vec<c_uint> = vec![0; 32];
+            let mut num_params = params.len();
+            let mut name: Vec<c_char> = vec![0; 257];
+            let id = H5Pget_filter2(
+                dcpl.id(),
+                index,
+                &mut flags,
+                &mut num_params,
+                params.as_mut_ptr(),
+                name.len(),
+                name.as_mut_ptr(),
+                ptr::null_mut(),
+            );
+            if id < 0 {
+                continue;
+            }
+            params.truncate(num_params.min(params.len()));
+            filters.push(PipelineFilter {
+                id,
+                name: CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned(),
+                params,
+                optional: flags & H5Z_FLAG_OPTIONAL != 0,
+                available: H5Zfilter_avail(id) > 0,
+            });
+        }
+    });
+    Ok(filters)
+}
+
+fn registered_name(id: H5Z_filter_t) -> Option<&'static str> {
+    REGISTERED_FILTERS
+        .iter()
+        .find(|(registered_id, _)| *registered_id == id)
+        .map(|(_, name)| *name)
+}
+
+fn szip_text(params: &[c_uint]) -> String {
+    let mut parts = vec![];
+    if let Some(&mask) = params.first() {
+        if mask & H5_SZIP_NN_OPTION_MASK != 0 {
+            parts.push("nearest neighbour coding".to_string());
+        } else if mask & H5_SZIP_EC_OPTION_MASK != 0 {
+            parts.push("entropy coding".to_string());
+        }
+    }
+    if let Some(pixels) = params.get(1) {
+        parts.push(format!("{} pixels per block", pixels));
+    }
+    if let Some(bits) = params.get(2) {
+        parts.push(format!("{} bits per pixel", bits));
+    }
+    if let Some(pixels) = params.get(3) {
+        parts.push(format!("{} pixels per scanline", pixels));
+    }
+    parts.join(", ")
+}
+
+/// Only the parameters of atomic types are decoded, those of arrays and compounds nest.
+fn nbit_text(params: &[c_uint]) -> String {
+    const NBIT_ATOMIC: c_uint = 1;
+    match params {
+        [_, _, _, NBIT_ATOMIC, size, _, precision, offset, ..] => {
+            format!("{} of {} bits kept, offset {}", precision, size * 8, offset)
+        }
+        _ => String::new(),
+    }
+}
+
+fn scale_offset_text(params: &[c_uint]) -> String {
+    match params {
+        [0, factor, ..] => format!("float, {} decimal digits kept", factor),
+        [1, factor, ..] => format!("float, E-scale factor {}", factor),
+        [2, 0, ..] => "integer, min bits computed".to_string(),
+        [2, bits, ..] => format!("integer, {} min bits", bits),
+        _ => String::new(),
+    }
+}
+
+fn blosc_text(params: &[c_uint]) -> String {
+    let mut parts = vec![];
+    if let Some(&compressor) = params.get(6) {
+        parts.push(
+            BLOSC_COMPRESSORS
+                .get(compressor as usize)
+                .map_or(format!("compressor {}", compressor), |name| {
+                    name.to_string()
+                }),
+        );
+    }
+    if let Some(level) = params.get(4) {
+        parts.push(format!("level {}", level));
+    }
+    match params.get(5) {
+        Some(0) => parts.push("no shuffle".to_string()),
+        Some(1) => parts.push("byte shuffle".to_string()),
+        Some(2) => parts.push("bit shuffle".to_string()),
+        _ => {}
+    }
+    parts.join(", ")
+}
+
+impl PipelineFilter {
+    /// Name of the filter: the built in name, the registered name or the name stored in the file.
+    pub fn display_name(&self) -> String {
+        let builtin = match self.id {
+            H5Z_FILTER_DEFLATE => Some("deflate"),
+            H5Z_FILTER_SHUFFLE => Some("shuffle"),
+            H5Z_FILTER_FLETCHER32 => Some("fletcher32"),
+            H5Z_FILTER_SZIP => Some("szip"),
+            H5Z_FILTER_NBIT => Some("n-bit"),
+            H5Z_FILTER_SCALEOFFSET => Some("scale-offset"),
+            _ => None,
+        };
+        match builtin.or_else(|| registered_name(self.id)) {
+            Some(name) => name.to_string(),
+            None if !self.name.is_empty() => self.name.clone(),
+            None => format!("filter {}", self.id),
+        }
+    }
+
+    /// Whether the filter makes the data smaller, as opposed to reordering or checksumming it.
+    pub fn is_compression(&self) -> bool {
+        !matches!(self.id, H5Z_FILTER_SHUFFLE | H5Z_FILTER_FLETCHER32)
+    }
+
+    /// The parameters of the filters we know, e.g. `level 3` for deflate.
+    fn params_text(&self) -> String {
+        let params = self.params.as_slice();
+        match self.id {
+            H5Z_FILTER_DEFLATE | BZIP2_FILTER_ID | ZSTD_FILTER_ID => params
+                .first()
+                .map_or(String::new(), |level| format!("level {}", level)),
+            H5Z_FILTER_SHUFFLE => params
+                .first()
+                .map_or(String::new(), |size| format!("{} byte elements", size)),
+            H5Z_FILTER_FLETCHER32 => "checksum".to_string(),
+            H5Z_FILTER_SZIP => szip_text(params),
+            H5Z_FILTER_NBIT => nbit_text(params),
+            H5Z_FILTER_SCALEOFFSET => scale_offset_text(params),
+            BLOSC_FILTER_ID => blosc_text(params),
+            _ if params.is_empty() => String::new(),
+            _ => format!("params {:?}", params),
+        }
+    }
+
+    /// e.g. `deflate (level 3)`, flagged when the dataset can't be read without a plugin.
+    pub fn text(&self) -> String {
+        let mut text = self.display_name();
+        // Ids below 256 are reserved for the filters built into HDF5
+        if self.id >= 256 {
+            text.push_str(&format!(" [id {}]", self.id));
+        }
+        let params = self.params_text();
+        if !params.is_empty() {
+            text.push_str(&format!(" ({})", params));
+        }
+        if self.optional {
+            text.push_str(", optional");
+        }
+        if !self.available {
+            text.push_str(" - NOT AVAILABLE, load its plugin (HDF5_PLUGIN_PATH) to read the data");
+        }
+        text
+    }
+}
+
+/// One numbered line per filter, or `None`.
+pub fn pipeline_text(dataset: &hdf5::Dataset) -> String {
+    match read_pipeline(dataset) {
+        Ok(filters) if filters.is_empty() => "None".to_string(),
+        Ok(filters) => filters
+            .iter()
+            .enumerate()
+            .map(|(i, filter)| format!("{}. {}", i + 1, filter.text()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Err(e) => format!("unreadable ({})", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::h5_utils;
+
+    #[test]
+    fn test_filter_pipeline() {
+        let dummy = h5_utils::dummy_file();
+        let file = hdf5::File::open(&dummy.path).unwrap();
+
+        assert_eq!(
+            pipeline_text(&file.dataset("variable").unwrap()),
+            "1. deflate (level 3), optional"
+        );
+        assert_eq!(
+            pipeline_text(&file.dataset("group1/filtered").unwrap()),
+            "1. shuffle (4 byte elements), optional\n\
+             2. Blosc [id 32001] (zstd, level 5, no shuffle), optional\n\
+             3. fletcher32 (checksum)"
+        );
+        assert_eq!(
+            pipeline_text(&file.dataset("group1/bool_ds").unwrap()),
+            "None"
+        );
+
+        let missing = PipelineFilter {
+            id: 32004,
+            name: String::new(),
+            params: vec![0],
+            optional: false,
+            available: false,
+        };
+        assert!(missing
+            .text()
+            .starts_with("LZ4 [id 32004] (params [0]) - NOT AVAILABLE"));
+        let unknown = PipelineFilter {
+            id: 40000,
+            name: "in-house".to_string(),
+            params: vec![],
+            optional: true,
+            available: true,
+        };
+        assert_eq!(unknown.text(), "in-house [id 40000], optional");
+    }
+}
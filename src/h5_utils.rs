@@ -1,13 +1,31 @@
-use hdf5::{File, H5Type, Result};
+use half::f16;
+use hdf5::{File, H5Type, ObjectReference1, Result};
 use hdf5_metno as hdf5;
-use hdf5_metno::types::FixedUnicode;
+use hdf5_metno::types::{
+    EnumType, FixedAscii, FixedUnicode, IntSize, TypeDescriptor, VarLenArray, VarLenAscii,
+    VarLenUnicode,
+};
+use hdf5_metno_sys::h5::H5free_memory;
+use hdf5_metno_sys::h5d::{H5Dcreate2, H5Dwrite};
+use hdf5_metno_sys::h5p::H5P_DEFAULT;
+use hdf5_metno_sys::h5s::H5S_ALL;
+use hdf5_metno_sys::h5t::{
+    H5T_class_t, H5Tcopy, H5Tcreate, H5Tget_class, H5Tget_precision, H5Tget_tag, H5Tset_tag,
+    H5T_NATIVE_B16,
+};
 use ndarray::arr2;
 use ndarray::Array1;
 use ndarray::Array2;
+use num_complex::Complex;
 use rand::distr::{Bernoulli, Distribution};
 use rand::rngs::StdRng;
 use rand::SeedableRng;
-use std::path::PathBuf;
+use std::ffi::{CStr, CString};
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::dim_scales::ScaleReference;
+use crate::references::{self, RegionReference};
+use std::path::{Path, PathBuf};
 
 // Calling group.name() or dataset.name() was very slow for some reason.
 // But group.member_names() was fast.
@@ -48,6 +66,50 @@ pub fn datasets(group: &hdf5::Group) -> hdf5::Result<Vec<(String, hdf5::Dataset)
     })
 }
 
+/// Like `Datatype::to_descriptor`, but also describes reference types.
+pub fn datatype_descriptor(dtype: &hdf5::Datatype) -> Result<TypeDescriptor> {
+    dtype
+        .to_descriptor()
+        .or_else(|e| references::reference_descriptor(dtype).ok_or(e))
+}
+
+/// Text for the types `Datatype::to_descriptor` can't describe: opaque and bitfield.
+pub fn raw_datatype_text(dtype: &hdf5::Datatype) -> Option<String> {
+    match hdf5::sync::sync(|| unsafe { H5Tget_class(dtype.id()) }) {
+        H5T_class_t::H5T_OPAQUE => Some(match opaque_tag(dtype) {
+            Some(tag) => format!("opaque ({} bytes, tag {:?})", dtype.size(), tag),
+            None => format!("opaque ({} bytes)", dtype.size()),
+        }),
+        H5T_class_t::H5T_BITFIELD => {
+            let precision = hdf5::sync::sync(|| unsafe { H5Tget_precision(dtype.id()) });
+            Some(format!("bitfield ({} bits)", precision))
+        }
+        _ => None,
+    }
+}
+
+/// The tag describing what an opaque type holds, if it has one.
+pub fn opaque_tag(dtype: &hdf5::Datatype) -> Option<String> {
+    let tag = hdf5::sync::sync(|| unsafe {
+        let ptr = H5Tget_tag(dtype.id());
+        if ptr.is_null() {
+            return None;
+        }
+        let tag = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+        H5free_memory(ptr.cast());
+        Some(tag)
+    })?;
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// The datatype as shown in the object info.
+pub fn datatype_to_text(dtype: &hdf5::Datatype) -> String {
+    match datatype_descriptor(dtype) {
+        Ok(descriptor) => type_descriptor_to_text(descriptor),
+        Err(_) => raw_datatype_text(dtype).unwrap_or("unknown".to_string()),
+    }
+}
+
 pub fn type_descriptor_to_text(dt: hdf5::types::TypeDescriptor) -> String {
     match dt {
         hdf5::types::TypeDescriptor::Compound(ct) => {
@@ -79,13 +141,137 @@ pub fn type_descriptor_to_text(dt: hdf5::types::TypeDescriptor) -> String {
                     prefix_indentation, &field.name, &indented
                 ));
             }
-            rep.push_str(")");
+            rep.push(')');
             rep
         }
+        hdf5::types::TypeDescriptor::Enum(et) => {
+            let members: Vec<String> = et
+                .members
+                .iter()
+                .map(|m| format!("{} = {}", m.name, enum_value_text(&et, m.value)))
+                .collect();
+            format!(
+                "{} {{ {} }}",
+                hdf5::types::TypeDescriptor::Enum(et.clone()),
+                members.join(", ")
+            )
+        }
         non_compound => format!("{}", non_compound),
     }
 }
 
+/// `value` as a number, sign extended for signed enums.
+fn enum_value_text(enum_type: &EnumType, value: u64) -> String {
+    if enum_type.signed {
+        let shift = 64 - 8 * enum_type.size as u32;
+        (((value << shift) as i64) >> shift).to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Name of the member with `value`, or the value itself if no member has it.
+pub fn enum_member_name(enum_type: &EnumType, value: u64) -> String {
+    enum_type
+        .members
+        .iter()
+        .find(|m| m.value == value)
+        .map_or_else(|| enum_value_text(enum_type, value), |m| m.name.clone())
+}
+
+/// Values of an enum dataset or attribute as the bits of its base integer, like `EnumMember::value`.
+pub fn read_enum_values(container: &hdf5::Container, enum_type: &EnumType) -> Result<Vec<u64>> {
+    macro_rules! read_as {
+        ($t:ty, $bits:ty) => {
+            container
+                .read_raw::<$t>()?
+                .into_iter()
+                .map(|v| v as $bits as u64)
+                .collect()
+        };
+    }
+    Ok(match (enum_type.size, enum_type.signed) {
+        (IntSize::U1, true) => read_as!(i8, u8),
+        (IntSize::U2, true) => read_as!(i16, u16),
+        (IntSize::U4, true) => read_as!(i32, u32),
+        (IntSize::U8, true) => read_as!(i64, u64),
+        (IntSize::U1, false) => read_as!(u8, u8),
+        (IntSize::U2, false) => read_as!(u16, u16),
+        (IntSize::U4, false) => read_as!(u32, u32),
+        (IntSize::U8, false) => read_as!(u64, u64),
+    })
+}
+
+/// Values shown for array attributes.
+const ATTRIBUTE_PREVIEW_VALUES: usize = 10;
+
+/// Short text of an attribute's value, with enums by member name.
+pub fn attribute_value_text(attr: &hdf5::Attribute) -> String {
+    let Ok(dtype) = attr.dtype() else {
+        return "unknown".to_string();
+    };
+    let Ok(descriptor) = datatype_descriptor(&dtype) else {
+        return format!("<{}>", datatype_to_text(&dtype));
+    };
+    let values: Result<Vec<String>> = match &descriptor {
+        TypeDescriptor::Enum(et) => read_enum_values(attr, et).map(|values| {
+            values
+                .into_iter()
+                .map(|v| enum_member_name(et, v))
+                .collect()
+        }),
+        TypeDescriptor::Integer(_)
+        | TypeDescriptor::Unsigned(_)
+        | TypeDescriptor::Float(_)
+        | TypeDescriptor::Boolean => attr
+            .read_raw::<f64>()
+            .map(|values| values.iter().map(|v| v.to_string()).collect()),
+        TypeDescriptor::VarLenUnicode => attr
+            .read_raw::<VarLenUnicode>()
+            .map(|values| values.iter().map(|v| format!("{:?}", v.as_str())).collect()),
+        TypeDescriptor::VarLenAscii => attr
+            .read_raw::<VarLenAscii>()
+            .map(|values| values.iter().map(|v| format!("{:?}", v.as_str())).collect()),
+        TypeDescriptor::Reference(_) | TypeDescriptor::VarLenArray(_)
+            if references::reference_descriptor(&dtype).is_some() =>
+        {
+            references::read_references(attr).map(|targets| {
+                targets
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect()
+            })
+        }
+        _ => return format!("<{}>", descriptor),
+    };
+    match values {
+        Err(e) => format!("unreadable ({})", e),
+        Ok(values) if attr.is_scalar() => values.into_iter().next().unwrap_or_default(),
+        Ok(mut values) => {
+            if values.len() > ATTRIBUTE_PREVIEW_VALUES {
+                values.truncate(ATTRIBUTE_PREVIEW_VALUES);
+                values.push("…".to_string());
+            }
+            format!("[{}]", values.join(", "))
+        }
+    }
+}
+
+/// A `name: value` line for each attribute of `location`.
+pub fn attributes_text(location: &hdf5::Location) -> String {
+    location
+        .attr_names()
+        .unwrap_or_default()
+        .iter()
+        .map(|name| match location.attr(name) {
+            Ok(attr) => format!("{}: {}", name, attribute_value_text(&attr)),
+            Err(e) => format!("{}: unreadable ({})", name, e),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[derive(H5Type, Clone, PartialEq, Debug)] // register with HDF5
 #[repr(u8)]
 pub enum Color {
@@ -94,6 +280,14 @@ pub enum Color {
     B = 3,
 }
 
+#[derive(H5Type, Clone, Copy, PartialEq, Debug)] // register with HDF5
+#[repr(i8)]
+pub enum Step {
+    Back = -1,
+    Stay = 0,
+    Forward = 1,
+}
+
 #[derive(H5Type, Clone, PartialEq, Debug)] // register with HDF5
 #[repr(C)]
 pub struct Pixel {
@@ -152,6 +346,22 @@ pub struct Pixel {
     field50: i32,
 }
 
+#[derive(H5Type, Clone, PartialEq, Debug)] // register with HDF5
+#[repr(C)]
+pub struct Position {
+    x: f32,
+    y: f32,
+}
+
+/// Compound with a nested compound and a string field.
+#[derive(H5Type, Clone, PartialEq, Debug)] // register with HDF5
+#[repr(C)]
+pub struct Particle {
+    position: Position,
+    charge: i8,
+    label: FixedAscii<8>,
+}
+
 impl Pixel {
     pub fn new(x: i64, y: i64, color: Color) -> Self {
         Self {
@@ -230,7 +440,49 @@ pub fn generate_dummy_split_file() -> Result<()> {
     generate_dummy_core(&file)
 }
 
-fn generate_dummy_core(file: &File) -> Result<()> {
+/// The dummy file the tests read, removed once the last test holding it drops it.
+#[cfg(test)]
+pub struct DummyFile {
+    pub path: PathBuf,
+}
+
+#[cfg(test)]
+impl Drop for DummyFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// The dummy file (with a 512 byte userblock) shared by the tests running at the same time,
+/// generated again only after every test holding it is done.
+#[cfg(test)]
+pub fn dummy_file() -> std::sync::Arc<DummyFile> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, Weak};
+    static SHARED: Mutex<Weak<DummyFile>> = Mutex::new(Weak::new());
+    static GENERATED: AtomicUsize = AtomicUsize::new(0);
+
+    let mut shared = SHARED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(dummy) = shared.upgrade() {
+        return dummy;
+    }
+    let dummy = Arc::new(DummyFile {
+        path: std::env::temp_dir().join(format!(
+            "h5inspect_test_{}_{}.h5",
+            std::process::id(),
+            GENERATED.fetch_add(1, Ordering::Relaxed)
+        )),
+    });
+    let file = File::with_options()
+        .with_fcpl(|p| p.userblock(512))
+        .create(&dummy.path)
+        .unwrap();
+    generate_dummy_core(&file).unwrap();
+    *shared = Arc::downgrade(&dummy);
+    dummy
+}
+
+pub fn generate_dummy_core(file: &File) -> Result<()> {
     // Seeded RNG for reproducibility
     let mut rng = StdRng::seed_from_u64(42);
     let bernoulli = Bernoulli::new(0.5).unwrap(); // Bernoulli distribution with p=0.5
@@ -309,13 +561,28 @@ fn generate_dummy_core(file: &File) -> Result<()> {
         0.0000000000000023092839,
     ])?;
 
+    let halves: Vec<f16> = (0..100).map(|i| f16::from_f32(i as f32 / 8.0)).collect();
+    group1
+        .new_dataset_builder()
+        .with_data(&halves)
+        .create("half_ds")?;
+
+    // A unit circle, as h5py writes complex numbers
+    let circle: Vec<Complex<f64>> = (0..64)
+        .map(|i| Complex::from_polar(1.0, i as f64 * std::f64::consts::TAU / 64.0))
+        .collect();
+    group1
+        .new_dataset_builder()
+        .with_data(&circle)
+        .create("complex_ds")?;
+
     let large_ds_len_1st_half = 1_000_000;
     let large_ds_len_2nd_half = 200_000;
     let large_ds = group1
         .new_dataset::<i64>()
         .shape((large_ds_len_1st_half + large_ds_len_2nd_half,))
         .create("large_rand")?;
-    let range = 1_000_000_001..std::i64::MAX; // range for random integers greater than 1 billion
+    let range = 1_000_000_001..i64::MAX; // range for random integers greater than 1 billion
     let random_numbers: Vec<i64> = (0..(large_ds_len_1st_half + large_ds_len_2nd_half))
         .map(|x| {
             if x < large_ds_len_1st_half {
@@ -335,6 +602,17 @@ fn generate_dummy_core(file: &File) -> Result<()> {
     // Write data to the dataset
     dataset.write_scalar(&unsafe { FixedUnicode::<5>::from_str_unchecked("asdfg") })?;
 
+    let names: Vec<VarLenUnicode> = ["ada", "", "grace", "ada", "émilie"]
+        .iter()
+        .cycle()
+        .take(200)
+        .map(|name| name.parse().unwrap())
+        .collect();
+    group1
+        .new_dataset_builder()
+        .with_data(&names)
+        .create("names")?;
+
     use Color::*;
 
     let builder = group1.new_dataset_builder();
@@ -348,11 +626,151 @@ fn generate_dummy_core(file: &File) -> Result<()> {
         ]))
         // finalize and write the dataset
         .create("pixels")?;
+    let colors: Vec<Color> = [R, G, B, B, B].iter().cycle().take(50).cloned().collect();
+    group1
+        .new_dataset_builder()
+        .with_data(&colors)
+        .create("colors")?;
+
     // create an attr with fixed shape but don't write the data
     let attr = ds.new_attr::<Color>().shape([3]).create("colors")?;
     // write the attr data
     attr.write(&[R, G, B])?;
 
+    let particles: Vec<Particle> = (0..100)
+        .map(|i| Particle {
+            position: Position {
+                x: i as f32 * 0.5,
+                y: -(i as f32),
+            },
+            charge: (i % 3) as i8 - 1,
+            label: FixedAscii::from_ascii(&format!("p{}", i)).unwrap(),
+        })
+        .collect();
+    group1
+        .new_dataset_builder()
+        .with_data(&particles)
+        .create("particles")?;
+
+    // References to other objects, and to regions of a dataset
+    let targets = ["pixels", "colors", "particles"];
+    let refs: Vec<ObjectReference1> = targets
+        .iter()
+        .map(|name| group1.reference(name))
+        .collect::<Result<_>>()?;
+    let refs_ds = group1
+        .new_dataset_builder()
+        .with_data(&refs)
+        .create("refs")?;
+    refs_ds
+        .new_attr::<ObjectReference1>()
+        .create("source")?
+        .write_scalar(&file.reference::<ObjectReference1>("variable")?)?;
+    let regions = [
+        RegionReference::new(&group1, "pixels", (0..2, 1).into())?,
+        RegionReference::new(&group1, "colors", (10..20).into())?,
+    ];
+    group1
+        .new_dataset_builder()
+        .with_data(&regions)
+        .create("region_refs")?;
+
+    let filtered = group1
+        .new_dataset::<i32>()
+        .chunk(100)
+        .shuffle()
+        .blosc_zstd(5, false)
+        .fletcher32()
+        .shape(1000)
+        .create("filtered")?;
+    filtered.write(&Array1::from_iter(0..1000))?;
+
+    // Only 3 of its 16 chunks are ever written
+    let sparse = group1
+        .new_dataset::<i32>()
+        .chunk((10, 10))
+        .deflate(4)
+        .shape((40, 40))
+        .create("sparse")?;
+    sparse.write_slice(
+        &Array2::from_shape_fn((10, 20), |(j, i)| (j * i) as i32),
+        (0..10, 0..20),
+    )?;
+    sparse.write_slice(&Array2::<i32>::ones((10, 10)), (30..40, 30..40))?;
+
+    // Compresses well but isn't compressed
+    group1
+        .new_dataset_builder()
+        .with_data(&Array1::from_iter((0..100_000).map(|i| i / 1000)))
+        .create("steps")?;
+
+    // Each row along the first dimension is larger than what previews read
+    group1
+        .new_dataset_builder()
+        .with_data(&ndarray::Array3::from_shape_fn(
+            (2, 3, 20_000),
+            |(i, j, k)| ((i * 3 + j) * 20_000 + k) as u16,
+        ))
+        .create("wide_rows")?;
+
+    // Fill values: user-defined and allocated up front, never written, and grown past what was written
+    let fill = file.create_group("fill")?;
+    fill.new_dataset::<f32>()
+        .shape(100)
+        .fill_value(-999.0f32)
+        .alloc_time(Some(hdf5::plist::dataset_create::AllocTime::Early))
+        .create("prefilled")?;
+    fill.new_dataset::<i32>().shape(100).create("unwritten")?;
+    let resized = fill
+        .new_dataset::<i32>()
+        .chunk(4)
+        .shape(1..)
+        .fill_value(-1)
+        .create("resized")?;
+    resized.resize(8)?;
+    resized.write_slice(&[3, 5], 0..2)?;
+
+    // Ragged and fixed size arrays
+    let ragged: Vec<VarLenArray<i32>> = (0..20)
+        .map(|i| VarLenArray::from_slice(&(0..i % 5).collect::<Vec<i32>>()))
+        .collect();
+    group1
+        .new_dataset_builder()
+        .with_data(&ragged)
+        .create("ragged")?;
+    let steps: Vec<VarLenArray<Step>> = (0..10)
+        .map(|i| {
+            let walk: Vec<Step> = (0..i % 4)
+                .map(|j| [Step::Back, Step::Stay, Step::Forward][(i + j) % 3])
+                .collect();
+            VarLenArray::from_slice(&walk)
+        })
+        .collect();
+    group1
+        .new_dataset_builder()
+        .with_data(&steps)
+        .create("ragged_steps")?;
+    let vectors: Vec<[f32; 3]> = (0..50).map(|i| [i as f32, -(i as f32), 0.5]).collect();
+    group1
+        .new_dataset_builder()
+        .with_data(&vectors)
+        .create("vectors")?;
+
+    // Types only shown as bytes
+    let opaque_id = hdf5::sync::sync(|| unsafe {
+        let id = H5Tcreate(H5T_class_t::H5T_OPAQUE, 4);
+        let tag = CString::new("pixel checksum").unwrap();
+        H5Tset_tag(id, tag.as_ptr());
+        id
+    });
+    let opaque_type: hdf5::Datatype = unsafe { hdf5::from_id(opaque_id)? };
+    let opaque_bytes: Vec<u8> = (0..40u8).map(|i| i.wrapping_mul(37)).collect();
+    create_raw_dataset(&group1, "opaque", &opaque_type, &opaque_bytes)?;
+    let bitfield_type: hdf5::Datatype =
+        unsafe { hdf5::from_id(hdf5::sync::sync(|| H5Tcopy(*H5T_NATIVE_B16)))? };
+    let bitfield_bytes: Vec<u8> = (0..16u16).flat_map(|i| (1u16 << i).to_ne_bytes()).collect();
+    create_raw_dataset(&group1, "bitfield", &bitfield_type, &bitfield_bytes)?;
+
     let group2 = group1.create_group("group2")?;
     let group2_d1 = group2
         .new_dataset::<i32>()
@@ -372,9 +790,123 @@ fn generate_dummy_core(file: &File) -> Result<()> {
         dataset.write(&arr)?;
     }
 
+    // Dimension scales, attached the way H5DSattach_scale does it
+    let grid = file.create_group("grid")?;
+    let time = grid
+        .new_dataset_builder()
+        .with_data(&Array1::from_iter((0..10).map(|i| i as f64 * 0.5)))
+        .create("time")?;
+    let x = grid
+        .new_dataset_builder()
+        .with_data(&Array1::from_iter((0..20).map(|i| 100.0 + 10.0 * i as f32)))
+        .create("x")?;
+    grid.new_dataset_builder()
+        .with_data(&Array2::from_shape_fn((10, 20), |(t, i)| (t * i) as f32))
+        .create("temperature")?;
+    grid.new_dataset_builder()
+        .with_data(&Array1::from_iter((0..10).map(|t| (t * t) as f32)))
+        .create("signal")?;
+    for (scale, name, attached) in [
+        (&time, "time (s)", vec![("temperature", 0), ("signal", 0)]),
+        (&x, "x", vec![("temperature", 1)]),
+    ] {
+        scale
+            .new_attr::<FixedAscii<16>>()
+            .create("CLASS")?
+            .write_scalar(&FixedAscii::<16>::from_ascii("DIMENSION_SCALE").unwrap())?;
+        scale
+            .new_attr::<FixedAscii<16>>()
+            .create("NAME")?
+            .write_scalar(&FixedAscii::<16>::from_ascii(name).unwrap())?;
+        let references: Vec<ScaleReference> = attached
+            .iter()
+            .map(|&(dataset, dimension)| {
+                Ok(ScaleReference {
+                    dataset: grid.reference(dataset)?,
+                    dimension,
+                })
+            })
+            .collect::<Result<_>>()?;
+        scale
+            .new_attr_builder()
+            .with_data(&references)
+            .create("REFERENCE_LIST")?;
+    }
+    for (dataset, scales) in [("temperature", vec!["time", "x"]), ("signal", vec!["time"])] {
+        let lists: Vec<VarLenArray<ObjectReference1>> = scales
+            .iter()
+            .map(|scale| Ok(VarLenArray::from_slice(&[grid.reference(scale)?])))
+            .collect::<Result<_>>()?;
+        grid.dataset(dataset)?
+            .new_attr_builder()
+            .with_data(&lists)
+            .create("DIMENSION_LIST")?;
+    }
+
     Ok(())
 }
 
+/// A 1D dataset of `bytes` read as `dtype`, for types `TypeDescriptor` can't describe.
+fn create_raw_dataset(
+    group: &hdf5::Group,
+    name: &str,
+    dtype: &hdf5::Datatype,
+    bytes: &[u8],
+) -> Result<()> {
+    let space = hdf5::Dataspace::try_new(bytes.len() / dtype.size())?;
+    let name = CString::new(name).map_err(|e| e.to_string())?;
+    let dataset_id = hdf5::sync::sync(|| unsafe {
+        H5Dcreate2(
+            group.id(),
+            name.as_ptr(),
+            dtype.id(),
+            space.id(),
+            H5P_DEFAULT,
+            H5P_DEFAULT,
+            H5P_DEFAULT,
+        )
+    });
+    let dataset: hdf5::Dataset = unsafe { hdf5::from_id(dataset_id)? };
+    let status = hdf5::sync::sync(|| unsafe {
+        H5Dwrite(
+            dataset.id(),
+            dtype.id(),
+            H5S_ALL,
+            H5S_ALL,
+            H5P_DEFAULT,
+            bytes.as_ptr().cast(),
+        )
+    });
+    if status < 0 {
+        return Err(format!("could not write {}", dataset.name()).into());
+    }
+    Ok(())
+}
+
+const HDF5_SIGNATURE: &[u8; 8] = b"\x89HDF\r\n\x1a\n";
+
+// The superblock signature is at offset 0 or, when a userblock is present,
+// at the next power of two from 512 onwards.
+pub fn is_hdf5_file(file_path: &Path) -> bool {
+    let Ok(mut file) = std::fs::File::open(file_path) else {
+        return false;
+    };
+    let file_len = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+    let mut signature = [0u8; 8];
+    let mut offset = 0u64;
+    while offset + signature.len() as u64 <= file_len {
+        if file.seek(SeekFrom::Start(offset)).is_err() || file.read_exact(&mut signature).is_err() {
+            return false;
+        }
+        if &signature == HDF5_SIGNATURE {
+            return true;
+        }
+        offset = if offset == 0 { 512 } else { offset * 2 };
+    }
+    false
+}
+
 pub fn open_file(file_path: &PathBuf) -> Result<hdf5::File> {
     let file = hdf5::File::with_options()
         .with_fapl(|p| p.sec2())
@@ -395,10 +927,11 @@ pub fn open_file(file_path: &PathBuf) -> Result<hdf5::File> {
 
     let mut clean_path = file_path.clone();
     if let Some(file_name) = file_path.file_name().and_then(|n| n.to_str()) {
-        if file_name.ends_with("-m.h5") {
-            clean_path.set_file_name(&file_name[..file_name.len() - 5]);
-        } else if file_name.ends_with("-r.h5") {
-            clean_path.set_file_name(&file_name[..file_name.len() - 5]);
+        if let Some(stem) = file_name
+            .strip_suffix("-m.h5")
+            .or_else(|| file_name.strip_suffix("-r.h5"))
+        {
+            clean_path.set_file_name(stem);
         }
     }
 
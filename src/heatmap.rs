@@ -0,0 +1,308 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+use serde::{Deserialize, Serialize};
+
+use crate::dim_scales::AxisScale;
+use crate::num_utils;
+
+/// Largest number of samples read along each axis, bigger images are read with a stride.
+pub const MAX_HEATMAP_SIDE: usize = 512;
+
+/// Colors are clipped to these percentiles so a few outliers don't wash out the image.
+const COLOR_LIMIT_PERCENTILES: (f64, f64) = (1.0, 99.0);
+
+const COLORBAR_WIDTH: u16 = 2;
+const COLORBAR_LABEL_WIDTH: u16 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Colormap {
+    Viridis,
+    Gray,
+    Diverging,
+}
+
+const VIRIDIS: [(u8, u8, u8); 9] = [
+    (68, 1, 84),
+    (71, 44, 122),
+    (59, 81, 139),
+    (44, 113, 142),
+    (33, 144, 141),
+    (39, 173, 129),
+    (92, 200, 99),
+    (170, 220, 50),
+    (253, 231, 37),
+];
+
+const GRAY: [(u8, u8, u8); 2] = [(0, 0, 0), (255, 255, 255)];
+
+const DIVERGING: [(u8, u8, u8); 3] = [(59, 76, 192), (221, 221, 221), (180, 4, 38)];
+
+impl Colormap {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colormap::Viridis => "viridis",
+            Colormap::Gray => "gray",
+            Colormap::Diverging => "diverging",
+        }
+    }
+
+    pub fn next(&self) -> Colormap {
+        match self {
+            Colormap::Viridis => Colormap::Gray,
+            Colormap::Gray => Colormap::Diverging,
+            Colormap::Diverging => Colormap::Viridis,
+        }
+    }
+
+    /// Color for `t` in `0..=1`, interpolated between the colormap's anchors.
+    pub fn color(&self, t: f64) -> Color {
+        let anchors: &[(u8, u8, u8)] = match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Gray => &GRAY,
+            Colormap::Diverging => &DIVERGING,
+        };
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let position = t * (anchors.len() - 1) as f64;
+        let i = (position.floor() as usize).min(anchors.len() - 2);
+        let frac = position - i as f64;
+        let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * frac).round() as u8;
+        let (a, b) = (anchors[i], anchors[i + 1]);
+        Color::Rgb(lerp(a.0, b.0), lerp(a.1, b.1), lerp(a.2, b.2))
+    }
+}
+
+/// A 2D slice of a dataset, sampled with a stride so it stays small.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatmapData {
+    /// Shape of the full slice, before sampling.
+    pub shape: (usize, usize),
+    pub step: (usize, usize),
+    pub rows: usize,
+    pub cols: usize,
+    /// Sampled values in row-major order.
+    pub values: Vec<f64>,
+    /// Values mapped to the ends of the colormap.
+    pub limits: (f64, f64),
+    /// Which slice of the dataset this is, e.g. `[0, :, :]`.
+    pub slice: String,
+    /// Dimension scales of the rows and columns.
+    pub scales: (Option<AxisScale>, Option<AxisScale>),
+}
+
+impl HeatmapData {
+    pub fn new(
+        values: ndarray::Array2<f64>,
+        shape: (usize, usize),
+        step: (usize, usize),
+        slice: String,
+    ) -> HeatmapData {
+        let (rows, cols) = values.dim();
+        let values: Vec<f64> = values.iter().copied().collect();
+        let mut finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+        finite.sort_by(f64::total_cmp);
+        let limits = if finite.is_empty() {
+            (0.0, 1.0)
+        } else {
+            (
+                num_utils::percentile_of_sorted(&finite, COLOR_LIMIT_PERCENTILES.0),
+                num_utils::percentile_of_sorted(&finite, COLOR_LIMIT_PERCENTILES.1),
+            )
+        };
+        HeatmapData {
+            shape,
+            step,
+            rows,
+            cols,
+            values,
+            limits,
+            slice,
+            scales: (None, None),
+        }
+    }
+
+    /// Color limits for `colormap`. The diverging one is centered on zero when the data crosses it.
+    fn limits_for(&self, colormap: Colormap) -> (f64, f64) {
+        let (lo, hi) = self.limits;
+        if colormap == Colormap::Diverging && lo < 0.0 && hi > 0.0 {
+            let extent = lo.abs().max(hi);
+            (-extent, extent)
+        } else {
+            (lo, hi)
+        }
+    }
+
+    fn normalize(&self, value: f64, (lo, hi): (f64, f64)) -> f64 {
+        if hi > lo {
+            (value - lo) / (hi - lo)
+        } else {
+            0.5
+        }
+    }
+
+    /// The sample drawn at pixel `(x, y)` of a `width` by `height` pixel image.
+    fn sample_at(&self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        let row = (y * self.rows / height.max(1)).min(self.rows.saturating_sub(1));
+        let col = (x * self.cols / width.max(1)).min(self.cols.saturating_sub(1));
+        (row, col)
+    }
+
+    /// `[row, col]`, with the values of the dimension scales where there are any.
+    pub fn position_text(&self, (row, col): (usize, usize)) -> String {
+        let text = |index: usize, scale: &Option<AxisScale>| match scale {
+            Some(scale) => format!("{} = {}", scale.name, scale.label(index as f64)),
+            None => index.to_string(),
+        };
+        format!(
+            "[{}, {}]",
+            text(row, &self.scales.0),
+            text(col, &self.scales.1)
+        )
+    }
+
+    /// Range of each dimension scale, e.g. `rows: time 0.0…4.5`.
+    pub fn scales_text(&self) -> String {
+        [
+            ("rows", &self.scales.0, self.shape.0),
+            ("columns", &self.scales.1, self.shape.1),
+        ]
+        .iter()
+        .filter_map(|(axis, scale, len)| {
+            let scale = scale.as_ref()?;
+            Some(format!(
+                "{}: {} {}…{}",
+                axis,
+                scale.name,
+                scale.label(0.0),
+                scale.label(len.saturating_sub(1) as f64)
+            ))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+
+    /// Index into the full slice and value of the sample under `position`,
+    /// using the top half of the cell since the mouse can't tell the halves apart.
+    pub fn value_under(&self, area: Rect, position: Position) -> Option<((usize, usize), f64)> {
+        let [image_area, _, _] = split_area(area);
+        if !image_area.contains(position) || self.values.is_empty() {
+            return None;
+        }
+        let x = (position.x - image_area.x) as usize;
+        let y = (position.y - image_area.y) as usize * 2;
+        let (row, col) = self.sample_at(
+            x,
+            y,
+            image_area.width as usize,
+            image_area.height as usize * 2,
+        );
+        let value = self.values[row * self.cols + col];
+        Some(((row * self.step.0, col * self.step.1), value))
+    }
+}
+
+/// The image, then the colorbar and its labels on the right.
+fn split_area(area: Rect) -> [Rect; 3] {
+    Layout::horizontal([
+        Constraint::Fill(1),
+        Constraint::Length(COLORBAR_WIDTH + 1),
+        Constraint::Length(COLORBAR_LABEL_WIDTH),
+    ])
+    .areas(area)
+}
+
+/// Draws two pixels per cell with `▀`, the top one in the foreground and the bottom one in the background.
+pub struct HeatmapWidget<'a> {
+    pub data: &'a HeatmapData,
+    pub colormap: Colormap,
+    pub label_style: Style,
+}
+
+impl Widget for HeatmapWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [image, colorbar, labels] = split_area(area);
+        let data = self.data;
+        let limits = data.limits_for(self.colormap);
+        if data.values.is_empty() || image.is_empty() {
+            return;
+        }
+
+        let (width, height) = (image.width as usize, image.height as usize * 2);
+        let pixel_color = |x: usize, y: usize| {
+            let (row, col) = data.sample_at(x, y, width, height);
+            let value = data.values[row * data.cols + col];
+            if value.is_finite() {
+                self.colormap.color(data.normalize(value, limits))
+            } else {
+                Color::Reset
+            }
+        };
+        for cell_y in 0..image.height {
+            for cell_x in 0..image.width {
+                let (x, y) = (cell_x as usize, cell_y as usize * 2);
+                buf[(image.x + cell_x, image.y + cell_y)]
+                    .set_char('▀')
+                    .set_style(Style::new().fg(pixel_color(x, y)).bg(pixel_color(x, y + 1)));
+            }
+        }
+
+        // Colorbar with the top value at the top
+        let bar_x = colorbar.x + 1;
+        let bar_pixels = colorbar.height as f64 * 2.0;
+        let bar_t = |y: f64| 1.0 - y / (bar_pixels - 1.0).max(1.0);
+        for cell_y in 0..colorbar.height {
+            let y = cell_y as f64 * 2.0;
+            let style = Style::new()
+                .fg(self.colormap.color(bar_t(y)))
+                .bg(self.colormap.color(bar_t(y + 1.0)));
+            for dx in 0..COLORBAR_WIDTH.min(colorbar.width.saturating_sub(1)) {
+                buf[(bar_x + dx, colorbar.y + cell_y)]
+                    .set_char('▀')
+                    .set_style(style);
+            }
+        }
+        if labels.height > 0 {
+            let (lo, hi) = limits;
+            let mid_y = labels.y + labels.height / 2;
+            let bottom_y = labels.y + labels.height - 1;
+            for (y, value) in [(labels.y, hi), (mid_y, (lo + hi) / 2.0), (bottom_y, lo)] {
+                buf.set_stringn(
+                    labels.x + 1,
+                    y,
+                    num_utils::basic_float_fmt(value as f32),
+                    labels.width.saturating_sub(1) as usize,
+                    self.label_style,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heatmap_limits_and_lookup() {
+        let mut values = ndarray::Array2::from_shape_fn((10, 20), |(r, c)| (r * 20 + c) as f64);
+        values[(0, 0)] = f64::NAN;
+        values[(9, 19)] = 1e9;
+        let data = HeatmapData::new(values, (100, 40), (10, 2), "[:, :]".to_string());
+        assert_eq!((data.rows, data.cols), (10, 20));
+        // The outlier doesn't stretch the color scale
+        assert!(data.limits.1 < 200.0);
+        assert!(data.limits.0 >= 1.0);
+
+        assert_eq!(Colormap::Gray.color(0.0), Color::Rgb(0, 0, 0));
+        assert_eq!(Colormap::Gray.color(1.0), Color::Rgb(255, 255, 255));
+        assert_eq!(Colormap::Viridis.color(2.0), Color::Rgb(253, 231, 37));
+
+        // 20 columns over a 20 cell wide image, 10 rows over 5 cells
+        let area = Rect::new(0, 0, 20 + COLORBAR_WIDTH + 1 + COLORBAR_LABEL_WIDTH, 5);
+        let ((row, col), value) = data.value_under(area, Position::new(3, 2)).unwrap();
+        assert_eq!((row, col), (40, 6));
+        assert_eq!(value, 83.0);
+        assert_eq!(data.value_under(area, Position::new(21, 2)), None);
+    }
+}
@@ -0,0 +1,233 @@
+use std::cmp::Ordering;
+use std::error::Error;
+
+use hdf5::types::TypeDescriptor;
+use hdf5_metno::{self as hdf5, Dataset};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use crate::analysis;
+use crate::h5_utils;
+use crate::num_utils;
+
+/// Bytes read from the start of a dataset for the hex view.
+const MAX_HEX_BYTES: usize = 1 << 16;
+
+pub const BYTES_PER_LINE: usize = 16;
+
+/// The first bytes of a dataset as it is laid out in memory, read with its own datatype.
+#[derive(Debug, Clone)]
+pub struct HexDump {
+    pub bytes: Vec<u8>,
+    pub element_size: usize,
+    /// Name and offset in the element of each compound field, nested fields flattened.
+    pub fields: Vec<(String, usize)>,
+    /// Size of the whole dataset, of which `bytes` is the start.
+    pub total_bytes: usize,
+    pub type_text: String,
+}
+
+/// Styles for the parts of the hex view.
+pub struct HexStyles {
+    pub offset: Style,
+    /// Consecutive elements alternate between these.
+    pub elements: [Style; 2],
+}
+
+/// The first `max_elements` of a dataset of `shape`, in storage order, as a hyperslab:
+/// index 0 along the leading dimensions, a range along one, and the trailing ones whole.
+/// Rounded up to whole rows of the trailing dimensions, `None` if that is the whole dataset.
+fn leading_selection(shape: &[usize], max_elements: usize) -> Option<hdf5::Hyperslab> {
+    if shape.iter().product::<usize>() <= max_elements {
+        return None;
+    }
+    // Trailing dimensions taken whole, and how many elements one index before them covers
+    let mut whole_from = shape.len();
+    let mut row_len = 1;
+    while whole_from > 0 && row_len * shape[whole_from - 1] <= max_elements {
+        whole_from -= 1;
+        row_len *= shape[whole_from];
+    }
+    let partial = whole_from - 1;
+    let selection: Vec<hdf5::SliceOrIndex> = (0..shape.len())
+        .map(|i| match i.cmp(&partial) {
+            Ordering::Less => (0..1).into(),
+            Ordering::Equal => (0..max_elements.div_ceil(row_len)).into(),
+            Ordering::Greater => (..).into(),
+        })
+        .collect();
+    Some(selection.into())
+}
+
+impl HexDump {
+    pub fn read(d: &Dataset) -> Result<HexDump, Box<dyn Error>> {
+        let dtype = d.dtype()?;
+        let element_size = dtype.size().max(1);
+        let mut fields = vec![];
+        if let Ok(TypeDescriptor::Compound(compound)) = dtype.to_descriptor() {
+            let mut leaves = vec![];
+            analysis::flatten_fields(&compound, "", 0, &mut leaves);
+            fields = leaves
+                .into_iter()
+                .map(|(name, _, offset)| (name, offset))
+                .collect();
+        }
+
+        let selection = leading_selection(&d.shape(), MAX_HEX_BYTES.div_ceil(element_size))
+            .map(hdf5::Selection::from);
+        let mut bytes = analysis::read_bytes(d, &dtype, selection)?;
+        bytes.truncate(MAX_HEX_BYTES);
+
+        Ok(HexDump {
+            bytes,
+            element_size,
+            fields,
+            total_bytes: d.size() * element_size,
+            type_text: h5_utils::datatype_to_text(&dtype),
+        })
+    }
+
+    /// The element and field layout, then `offset  hex  |ascii|` lines.
+    /// Elements alternate in style and the first byte of each compound field is underlined.
+    pub fn lines(&self, styles: &HexStyles) -> Vec<Line<'static>> {
+        let mut lines = vec![Line::styled(
+            format!(
+                "{} bytes per element, {}",
+                self.element_size,
+                self.type_text.lines().next().unwrap_or_default()
+            ),
+            styles.offset,
+        )];
+        if !self.fields.is_empty() {
+            let fields: Vec<String> = self
+                .fields
+                .iter()
+                .map(|(name, offset)| format!("{} @{}", name, offset))
+                .collect();
+            lines.push(Line::styled(
+                format!("Fields: {}", fields.join(", ")),
+                styles.offset,
+            ));
+        }
+        lines.push(Line::default());
+
+        for (line_index, chunk) in self.bytes.chunks(BYTES_PER_LINE).enumerate() {
+            let start = line_index * BYTES_PER_LINE;
+            let mut spans = vec![Span::styled(format!("{:08x}  ", start), styles.offset)];
+            for i in 0..BYTES_PER_LINE {
+                if i == BYTES_PER_LINE / 2 {
+                    spans.push(Span::raw(" "));
+                }
+                match chunk.get(i) {
+                    Some(byte) => {
+                        let offset = start + i;
+                        let mut style = styles.elements[(offset / self.element_size) % 2];
+                        let in_element = offset % self.element_size;
+                        if self.fields.iter().any(|(_, o)| *o == in_element) {
+                            style = style.add_modifier(Modifier::UNDERLINED);
+                        }
+                        spans.push(Span::styled(format!("{:02x}", byte), style));
+                        spans.push(Span::raw(" "));
+                    }
+                    None => spans.push(Span::raw("   ")),
+                }
+            }
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            spans.push(Span::styled(format!(" |{}|", ascii), styles.offset));
+            lines.push(Line::from(spans));
+        }
+
+        if self.total_bytes > self.bytes.len() {
+            lines.push(Line::styled(
+                format!(
+                    "… {} more bytes",
+                    num_utils::format_integer_with_separator(
+                        (self.total_bytes - self.bytes.len()) as u64
+                    )
+                ),
+                styles.offset,
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdf5::File;
+
+    fn text(line: &Line) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_hexdump() {
+        let dummy = h5_utils::dummy_file();
+        let file = File::open(&dummy.path).unwrap();
+        let styles = HexStyles {
+            offset: Style::new(),
+            elements: [Style::new(), Style::new().add_modifier(Modifier::BOLD)],
+        };
+
+        let opaque = HexDump::read(&file.dataset("group1/opaque").unwrap()).unwrap();
+        let lines = opaque.lines(&styles);
+        assert_eq!(
+            text(&lines[0]),
+            "4 bytes per element, opaque (4 bytes, tag \"pixel checksum\")"
+        );
+        assert_eq!(
+            text(&lines[2]),
+            "00000000  00 25 4a 6f 94 b9 de 03  28 4d 72 97 bc e1 06 2b  |.%Jo....(Mr....+|"
+        );
+        // The second element is in the other style
+        assert_eq!(lines[2].spans[1].style, styles.elements[0]);
+        assert_eq!(lines[2].spans[9].style, styles.elements[1]);
+
+        let pixels = HexDump::read(&file.dataset("group1/pixels").unwrap()).unwrap();
+        assert_eq!(pixels.fields[0], ("x".to_string(), 0));
+        assert_eq!(pixels.fields[1], ("y".to_string(), 8));
+        assert_eq!(pixels.bytes.len(), 6 * pixels.element_size);
+        let lines = pixels.lines(&styles);
+        assert!(text(&lines[1]).starts_with("Fields: x @0, y @8, color @16"));
+        // Byte 8, after the gap in the middle of the line, starts `y`
+        assert!(lines[3].spans[18]
+            .style
+            .add_modifier
+            .contains(Modifier::UNDERLINED));
+
+        let large = HexDump::read(&file.dataset("group1/large_rand").unwrap()).unwrap();
+        assert_eq!(large.bytes.len(), MAX_HEX_BYTES);
+        assert_eq!(
+            text(large.lines(&styles).last().unwrap()),
+            "… 9_534_464 more bytes"
+        );
+
+        // Only the start of the first row is read, not whole rows
+        assert_eq!(leading_selection(&[2, 3, 20_000], 32_768), {
+            let selection: Vec<hdf5::SliceOrIndex> =
+                vec![(0..1).into(), (0..2).into(), (..).into()];
+            Some(selection.into())
+        });
+        let wide = HexDump::read(&file.dataset("group1/wide_rows").unwrap()).unwrap();
+        assert_eq!(wide.bytes.len(), MAX_HEX_BYTES);
+        let values: Vec<u16> = wide
+            .bytes
+            .chunks(2)
+            .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(values, (0..(MAX_HEX_BYTES / 2) as u16).collect::<Vec<_>>());
+    }
+}
@@ -1,4 +1,3 @@
-use crate::ui;
 use plotters::coord;
 use plotters::prelude::DrawingArea;
 use plotters::prelude::*;
@@ -6,73 +5,296 @@ use plotters::prelude::{ChartBuilder, LabelAreaPosition};
 use plotters_ratatui_backend::{
     widget_fn, AreaResult, Draw, Error, PlottersWidget, RatatuiBackend,
 };
+use ratatui::layout::Direction;
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Bar, BarChart, BarGroup};
+use serde::{Deserialize, Serialize};
 
-use crate::analysis;
+use crate::config;
 use crate::num_utils;
 
+/// Upper bound on the number of values sent from the analysis process, larger datasets are sampled.
+const MAX_HISTOGRAM_VALUES: usize = 1 << 21;
+
+pub const MIN_BINS: usize = 2;
+pub const MAX_BINS: usize = 1000;
+
+/// Percentile ranges the histogram can be clipped to, in the order they are cycled through.
+pub const CLIP_PERCENTILES: [Option<(f64, f64)>; 4] = [
+    None,
+    Some((1.0, 99.0)),
+    Some((5.0, 95.0)),
+    Some((25.0, 75.0)),
+];
+
+/// How to bin the values, changed interactively.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramSettings {
+    pub bins: usize,
+    pub log_counts: bool,
+    pub clip_percentiles: Option<(f64, f64)>,
+}
+
+impl Default for HistogramSettings {
+    fn default() -> HistogramSettings {
+        HistogramSettings {
+            bins: config::get().histogram_bins,
+            log_counts: false,
+            clip_percentiles: None,
+        }
+    }
+}
+
+impl HistogramSettings {
+    pub fn cycle_clip(&mut self) {
+        let index = CLIP_PERCENTILES
+            .iter()
+            .position(|clip| *clip == self.clip_percentiles)
+            .unwrap_or(0);
+        self.clip_percentiles = CLIP_PERCENTILES[(index + 1) % CLIP_PERCENTILES.len()];
+    }
+
+    pub fn scale_bins(&mut self, factor: f64) {
+        self.bins = ((self.bins as f64 * factor).round() as usize).clamp(MIN_BINS, MAX_BINS);
+    }
+}
+
+/// The sorted finite values of a dataset, so it can be re-binned without reading it again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramSource {
+    sorted: Vec<f64>,
+    /// Every `stride`th value was kept, counts are scaled back up by it.
+    pub stride: usize,
+    pub is_integer: bool,
+}
+
+/// Bin edges and counts. Values outside the edges were clipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub edges: Vec<f64>,
+    pub counts: Vec<u64>,
+    pub num_below: u64,
+    pub num_above: u64,
+}
+
+impl HistogramSource {
+    /// `None` if there is nothing finite to bin.
+    pub fn new(values: &[f64], is_integer: bool) -> Option<HistogramSource> {
+        let stride = values.len().div_ceil(MAX_HISTOGRAM_VALUES).max(1);
+        let mut sorted: Vec<f64> = values
+            .iter()
+            .step_by(stride)
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f64::total_cmp);
+        Some(HistogramSource {
+            sorted,
+            stride,
+            is_integer,
+        })
+    }
+
+    /// Like `new`, for values that are already every `stride`th one of the data.
+    pub fn from_sample(values: &[f64], is_integer: bool, stride: usize) -> Option<HistogramSource> {
+        let mut source = HistogramSource::new(values, is_integer)?;
+        source.stride *= stride;
+        Some(source)
+    }
+
+    pub fn compute(&self, settings: &HistogramSettings) -> Histogram {
+        let sorted = &self.sorted;
+        let (lo, hi) = match settings.clip_percentiles {
+            Some((p_lo, p_hi)) => (
+                num_utils::percentile_of_sorted(sorted, p_lo),
+                num_utils::percentile_of_sorted(sorted, p_hi),
+            ),
+            None => (sorted[0], sorted[sorted.len() - 1]),
+        };
+
+        let bins = settings.bins.max(1);
+        // Integers get whole-number wide bins centered on the values they hold
+        let (start, width, num_bins) = if self.is_integer {
+            let (lo, hi) = (lo.floor(), hi.ceil());
+            let width = ((hi - lo + 1.0) / bins as f64).ceil().max(1.0);
+            let num_bins = ((hi - lo + 1.0) / width).ceil() as usize;
+            (lo - 0.5, width, num_bins)
+        } else if hi > lo {
+            (lo, (hi - lo) / bins as f64, bins)
+        } else {
+            (lo - 0.5, 1.0, 1)
+        };
+        let mut edges: Vec<f64> = (0..=num_bins).map(|i| start + i as f64 * width).collect();
+        // Avoid losing the largest value to rounding
+        edges[num_bins] = edges[num_bins].max(hi);
+
+        let first = sorted.partition_point(|&v| v < edges[0]);
+        let last = sorted.partition_point(|&v| v <= edges[num_bins]);
+        let mut boundaries: Vec<usize> = edges[1..num_bins]
+            .iter()
+            .map(|&edge| sorted.partition_point(|&v| v < edge))
+            .collect();
+        boundaries.insert(0, first);
+        boundaries.push(last);
+
+        let stride = self.stride as u64;
+        Histogram {
+            counts: boundaries
+                .windows(2)
+                .map(|w| (w[1] - w[0]) as u64 * stride)
+                .collect(),
+            edges,
+            num_below: first as u64 * stride,
+            num_above: (sorted.len() - last) as u64 * stride,
+        }
+    }
+}
+
+impl Histogram {
+    /// Tab separated `start end count` rows, for pasting into a spreadsheet.
+    pub fn to_tsv(&self) -> String {
+        let mut text = "bin_start\tbin_end\tcount\n".to_string();
+        for (i, count) in self.counts.iter().enumerate() {
+            text.push_str(&format!(
+                "{}\t{}\t{}\n",
+                self.edges[i],
+                self.edges[i + 1],
+                count
+            ));
+        }
+        text
+    }
+}
+
 pub fn histogram_widget(
-    hist_data: &analysis::HistogramData,
+    histogram: &Histogram,
+    log_counts: bool,
     _widget_height: u16,
     widget_width: u16,
 ) -> PlottersWidget<impl Draw, impl Fn(DrawingAreaErrorKind<Error>)> {
-    let bins = hist_data.iter().map(|&(b, _)| b).collect::<Vec<f32>>();
-    let counts = hist_data.iter().map(|&(_, c)| c).collect::<Vec<u32>>();
-
-    let min_bin = bins.iter().fold(f32::INFINITY, |a, &b| a.min(b));
-    let max_bin = bins.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
-    let bin_width = if bins.len() > 1 {
-        (max_bin - min_bin) / (bins.len() - 1) as f32
-    } else {
-        1.0
-    };
+    let edges = histogram.edges.clone();
+    // Log counts are drawn as log10(count + 1) with the labels mapped back
+    let heights: Vec<f64> = histogram
+        .counts
+        .iter()
+        .map(|&c| {
+            if log_counts {
+                (c as f64 + 1.0).log10()
+            } else {
+                c as f64
+            }
+        })
+        .collect();
+    let max_height = heights.iter().cloned().fold(1.0, f64::max);
+    let x_range = edges[0]..edges[edges.len() - 1];
 
-    // let min_cnt = *counts.iter().min().unwrap();
-    let max_cnt = *counts.iter().max().unwrap();
+    let theme = &config::get().theme;
+    let accent = RGBColor(theme.accent.0, theme.accent.1, theme.accent.2);
+    let axis = RGBColor(theme.plot_axis.0, theme.plot_axis.1, theme.plot_axis.2);
 
     let draw_fn = move |area: DrawingArea<RatatuiBackend, coord::Shift>| -> AreaResult {
         let mut chart = ChartBuilder::on(&area)
             .margin(1)
             .set_label_area_size(LabelAreaPosition::Left, 30)
             .set_label_area_size(LabelAreaPosition::Bottom, 10)
-            // .caption("Histogram Test", ("sans-serif", 50.0).into_font().color(&WHITE))
-            .build_cartesian_2d(min_bin..(max_bin + bin_width), 0..max_cnt)?;
+            .build_cartesian_2d(x_range.clone(), 0.0..max_height)?;
 
         chart
             .configure_mesh()
             .disable_x_mesh()
             .disable_y_mesh()
-            // .bold_line_style(WHITE)
-            // .y_desc("Count")
-            // .x_desc("Bucket")
-            .y_label_formatter(&|y| num_utils::large_int_fmt(*y as u64))
+            .y_label_formatter(&|y| {
+                let count = if log_counts { 10f64.powf(*y) - 1.0 } else { *y };
+                num_utils::large_int_fmt(count.round().max(0.0) as u64)
+            })
             .x_labels(2u16.max(widget_width / 10).into())
-            .x_label_formatter(&|x| num_utils::basic_float_fmt(*x))
-            // .axis_desc_style(("sans-serif", 15).into_font().color(&WHITE))
+            .x_label_formatter(&|x| num_utils::basic_float_fmt(*x as f32))
             .axis_style(ShapeStyle {
-                color: WHITE.into(),
+                color: axis.into(),
                 filled: true,
                 stroke_width: 0,
             })
-            .label_style(("sans-serif", 15).into_font().color(&WHITE))
+            .label_style(("sans-serif", 15).into_font().color(&axis))
             .draw()?;
 
-        // Draw histogram bars
-        for (i, &count) in counts.iter().enumerate() {
-            let x0 = min_bin + i as f32 * bin_width;
-
+        // Draw histogram bars as vertical lines, leaving a gap on either side
+        for (i, &height) in heights.iter().enumerate() {
+            let (x0, x1) = (edges[i], edges[i + 1]);
             let num_lines: usize = 30;
-            let step = bin_width / (num_lines as f32);
+            let step = (x1 - x0) / (num_lines as f64);
             for j in 6..(num_lines - 5) {
-                let x = x0 + j as f32 * step;
-                chart.draw_series(LineSeries::new(
-                    vec![(x, 0), (x, count)],
-                    &RGBColor(ui::MAGENTA_R, ui::MAGENTA_G, ui::MAGENTA_B),
-                ))?;
+                let x = x0 + j as f64 * step;
+                chart.draw_series(LineSeries::new(vec![(x, 0.0), (x, height)], &accent))?;
             }
         }
 
         area.present()
     };
 
-    return widget_fn(draw_fn);
+    widget_fn(draw_fn)
+}
+
+/// One horizontal bar per label, e.g. how often each enum member occurs.
+pub fn count_bar_chart(counts: &[(String, u64)], label_style: Style) -> BarChart<'_> {
+    let theme = &config::get().theme;
+    let bars: Vec<Bar> = counts
+        .iter()
+        .map(|(label, count)| {
+            Bar::default()
+                .label(Line::from(label.as_str()))
+                .value(*count)
+                .text_value(num_utils::format_integer_with_separator(*count))
+        })
+        .collect();
+    BarChart::default()
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .bar_style(Style::new().fg(theme.accent_color()))
+        .value_style(label_style.reversed())
+        .label_style(label_style)
+        .data(BarGroup::default().bars(&bars))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_binning() {
+        let mut values: Vec<f64> = (0..100).map(|i| (i % 10) as f64).collect();
+        values.push(f64::NAN);
+        values.push(1e12);
+        let source = HistogramSource::new(&values, true).unwrap();
+
+        let mut settings = HistogramSettings {
+            bins: 5,
+            log_counts: false,
+            clip_percentiles: None,
+        };
+        // The outlier makes the integer bins very wide
+        let histogram = source.compute(&settings);
+        assert_eq!(histogram.counts.iter().sum::<u64>(), 101);
+        assert_eq!(histogram.counts[0], 100);
+
+        settings.cycle_clip();
+        assert_eq!(settings.clip_percentiles, Some((1.0, 99.0)));
+        settings.bins = 10;
+        let histogram = source.compute(&settings);
+        assert_eq!(histogram.edges[0], -0.5);
+        assert_eq!(histogram.counts, vec![10; 10]);
+        assert_eq!((histogram.num_below, histogram.num_above), (0, 1));
+        assert!(histogram
+            .to_tsv()
+            .starts_with("bin_start\tbin_end\tcount\n-0.5\t0.5\t10\n"));
+
+        let floats = HistogramSource::new(&[0.0, 0.5, 1.0, 1.0], false).unwrap();
+        settings.clip_percentiles = None;
+        settings.bins = 2;
+        assert_eq!(floats.compute(&settings).counts, vec![1, 3]);
+    }
 }
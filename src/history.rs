@@ -0,0 +1,81 @@
+const MAX_HISTORY_LEN: usize = 100;
+
+/// Browser-like back/forward list of visited selections.
+/// Consecutive small steps (moving up and down the tree) are merged into one entry,
+/// so going back jumps to where the user came from rather than one row up.
+pub struct NavigationHistory<T> {
+    entries: Vec<T>,
+    index: usize,
+    last_was_step: bool,
+}
+
+impl<T: PartialEq + Clone> NavigationHistory<T> {
+    pub fn new() -> NavigationHistory<T> {
+        NavigationHistory {
+            entries: vec![],
+            index: 0,
+            last_was_step: false,
+        }
+    }
+
+    pub fn record(&mut self, entry: T, is_step: bool) {
+        if self.entries.get(self.index) == Some(&entry) {
+            return;
+        }
+        // Visiting somewhere new drops the forward entries
+        self.entries.truncate(self.index + 1);
+        if is_step && self.last_was_step && !self.entries.is_empty() {
+            self.entries[self.index] = entry;
+        } else {
+            self.entries.push(entry);
+            if self.entries.len() > MAX_HISTORY_LEN {
+                self.entries.remove(0);
+            }
+            self.index = self.entries.len() - 1;
+        }
+        self.last_was_step = is_step;
+    }
+
+    pub fn back(&mut self) -> Option<&T> {
+        if self.index == 0 || self.entries.is_empty() {
+            return None;
+        }
+        self.index -= 1;
+        self.last_was_step = false;
+        self.entries.get(self.index)
+    }
+
+    pub fn forward(&mut self) -> Option<&T> {
+        if self.index + 1 >= self.entries.len() {
+            return None;
+        }
+        self.index += 1;
+        self.last_was_step = false;
+        self.entries.get(self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_merges_steps_and_truncates_forward() {
+        let mut history = NavigationHistory::new();
+        history.record("a", false);
+        history.record("b", true);
+        history.record("c", true);
+        history.record("x", false);
+
+        assert_eq!(history.back(), Some(&"c"));
+        assert_eq!(history.back(), Some(&"a"));
+        assert_eq!(history.back(), None);
+        assert_eq!(history.forward(), Some(&"c"));
+
+        // Stepping away after going back forgets "x"
+        history.record("d", true);
+        assert_eq!(history.forward(), None);
+        assert_eq!(history.back(), Some(&"c"));
+        assert_eq!(history.forward(), Some(&"d"));
+    }
+}
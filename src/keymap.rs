@@ -0,0 +1,548 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+
+/// Everything a key can be bound to. What an action does depends on the current mode,
+/// e.g. `MoveLeft` closes a group in the tree but goes to the parent dir in the file browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    PageUp,
+    PageDown,
+    GoToTop,
+    GoToBottom,
+    ToggleNode,
+    NextItem,
+    PrevItem,
+    ExpandAll,
+    HistoryBack,
+    HistoryForward,
+    SetMark,
+    JumpToMark,
+    FollowReference,
+    CyclePlot,
+    CycleColormap,
+    ZoomIn,
+    ZoomOut,
+    PanLeft,
+    PanRight,
+    MoreBins,
+    FewerBins,
+    ToggleLogCounts,
+    CycleHistogramClip,
+    NextField,
+    PrevField,
+    NextSlice,
+    PrevSlice,
+    ToggleHexView,
+    ToggleChunkMap,
+    ToggleFillValues,
+    Search,
+    YankPath,
+    YankHistogram,
+    RunPostCommand,
+    NextTab,
+    PrevTab,
+    OpenFileBrowser,
+    ParentDir,
+    Refresh,
+    RecentFiles,
+    ToggleLogs,
+    ShowHelp,
+    CommandPalette,
+    Back,
+    Quit,
+}
+
+impl Action {
+    /// All actions, in the order they are listed on the help screen.
+    pub const ALL: [Action; 49] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::PageUp,
+        Action::PageDown,
+        Action::GoToTop,
+        Action::GoToBottom,
+        Action::ToggleNode,
+        Action::NextItem,
+        Action::PrevItem,
+        Action::ExpandAll,
+        Action::HistoryBack,
+        Action::HistoryForward,
+        Action::SetMark,
+        Action::JumpToMark,
+        Action::FollowReference,
+        Action::CyclePlot,
+        Action::CycleColormap,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::MoreBins,
+        Action::FewerBins,
+        Action::ToggleLogCounts,
+        Action::CycleHistogramClip,
+        Action::NextField,
+        Action::PrevField,
+        Action::NextSlice,
+        Action::PrevSlice,
+        Action::ToggleHexView,
+        Action::ToggleChunkMap,
+        Action::ToggleFillValues,
+        Action::Search,
+        Action::YankPath,
+        Action::YankHistogram,
+        Action::RunPostCommand,
+        Action::NextTab,
+        Action::PrevTab,
+        Action::OpenFileBrowser,
+        Action::ParentDir,
+        Action::Refresh,
+        Action::RecentFiles,
+        Action::ToggleLogs,
+        Action::ShowHelp,
+        Action::CommandPalette,
+        Action::Back,
+        Action::Quit,
+    ];
+
+    /// Name used for the action in the `[keys]` section of the config.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "move-up",
+            Action::MoveDown => "move-down",
+            Action::MoveLeft => "move-left",
+            Action::MoveRight => "move-right",
+            Action::PageUp => "page-up",
+            Action::PageDown => "page-down",
+            Action::GoToTop => "go-to-top",
+            Action::GoToBottom => "go-to-bottom",
+            Action::ToggleNode => "toggle-node",
+            Action::NextItem => "next-item",
+            Action::PrevItem => "prev-item",
+            Action::ExpandAll => "expand-all",
+            Action::HistoryBack => "history-back",
+            Action::HistoryForward => "history-forward",
+            Action::SetMark => "set-mark",
+            Action::JumpToMark => "jump-to-mark",
+            Action::FollowReference => "follow-reference",
+            Action::CyclePlot => "cycle-plot",
+            Action::CycleColormap => "cycle-colormap",
+            Action::ZoomIn => "zoom-in",
+            Action::ZoomOut => "zoom-out",
+            Action::PanLeft => "pan-left",
+            Action::PanRight => "pan-right",
+            Action::MoreBins => "more-bins",
+            Action::FewerBins => "fewer-bins",
+            Action::ToggleLogCounts => "toggle-log-counts",
+            Action::CycleHistogramClip => "cycle-histogram-clip",
+            Action::NextField => "next-field",
+            Action::PrevField => "prev-field",
+            Action::NextSlice => "next-slice",
+            Action::PrevSlice => "prev-slice",
+            Action::ToggleHexView => "toggle-hex-view",
+            Action::ToggleChunkMap => "toggle-chunk-map",
+            Action::ToggleFillValues => "toggle-fill-values",
+            Action::YankHistogram => "yank-histogram",
+            Action::Search => "search",
+            Action::YankPath => "yank-path",
+            Action::RunPostCommand => "run-post-command",
+            Action::NextTab => "next-tab",
+            Action::PrevTab => "prev-tab",
+            Action::OpenFileBrowser => "open-file-browser",
+            Action::ParentDir => "parent-dir",
+            Action::Refresh => "refresh",
+            Action::RecentFiles => "recent-files",
+            Action::ToggleLogs => "toggle-logs",
+            Action::ShowHelp => "show-help",
+            Action::CommandPalette => "command-palette",
+            Action::Back => "back",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Close group / go to tree",
+            Action::MoveRight => "Inspect object info",
+            Action::PageUp => "Page up",
+            Action::PageDown => "Page down",
+            Action::GoToTop => "Go to top",
+            Action::GoToBottom => "Go to bottom",
+            Action::ToggleNode => "Close/open group",
+            Action::NextItem => "Next item",
+            Action::PrevItem => "Previous item",
+            Action::ExpandAll => "Expand all groups",
+            Action::HistoryBack => "Back to previous selection",
+            Action::HistoryForward => "Forward to next selection",
+            Action::SetMark => "Set mark (then a letter)",
+            Action::JumpToMark => "Jump to mark (then a letter)",
+            Action::FollowReference => "Jump to an object referenced by the selection",
+            Action::CyclePlot => "Switch plot (histogram / line / heatmap / counts)",
+            Action::CycleColormap => "Switch heatmap colormap",
+            Action::ZoomIn => "Zoom in on the line plot",
+            Action::ZoomOut => "Zoom out of the line plot",
+            Action::PanLeft => "Pan the line plot left",
+            Action::PanRight => "Pan the line plot right",
+            Action::MoreBins => "More histogram bins",
+            Action::FewerBins => "Fewer histogram bins",
+            Action::ToggleLogCounts => "Log scale histogram counts",
+            Action::CycleHistogramClip => "Clip histogram range to percentiles",
+            Action::NextField => "Next field of a compound dataset",
+            Action::PrevField => "Previous field of a compound dataset",
+            Action::NextSlice => "Next heatmap slice along the leading dimensions",
+            Action::PrevSlice => "Previous heatmap slice along the leading dimensions",
+            Action::ToggleHexView => "Show the raw bytes of datasets",
+            Action::ToggleChunkMap => "Show where the chunks of datasets are stored",
+            Action::ToggleFillValues => "Leave fill values out of the stats",
+            Action::YankHistogram => "Yank (copy) histogram bins and counts",
+            Action::Search => "Fuzzy search",
+            Action::YankPath => "Yank (copy) node path",
+            Action::RunPostCommand => "Run $H5INSPECT_POST",
+            Action::NextTab => "Next file tab",
+            Action::PrevTab => "Previous file tab",
+            Action::OpenFileBrowser => "Browse for files to open",
+            Action::ParentDir => "Parent directory (file browser)",
+            Action::Refresh => "Refresh (file browser)",
+            Action::RecentFiles => "Recent files (file browser)",
+            Action::ToggleLogs => "Debug logs",
+            Action::ShowHelp => "Help screen",
+            Action::CommandPalette => "Command palette",
+            Action::Back => "Back / close",
+            Action::Quit => "Quit",
+        }
+    }
+
+    /// Mouse gestures that do the same thing, shown next to the keys on the help screen.
+    pub fn mouse_hint(&self) -> Option<&'static str> {
+        match self {
+            Action::MoveUp | Action::MoveDown => Some("scroll"),
+            Action::ToggleNode => Some("click"),
+            Action::YankPath => Some("double-click"),
+            Action::NextTab | Action::PrevTab => Some("click tab"),
+            _ => None,
+        }
+    }
+}
+
+/// A key together with its modifiers, e.g. `ctrl-p`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyBinding {
+        // Shift is already part of the character or of BackTab, terminals disagree on whether to report it
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        KeyBinding { code, modifiers }
+    }
+
+    fn plain(code: KeyCode) -> KeyBinding {
+        KeyBinding::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyBinding {
+        KeyBinding::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn alt(c: char) -> KeyBinding {
+        KeyBinding::new(KeyCode::Char(c), KeyModifiers::ALT)
+    }
+
+    /// Parse bindings like `j`, `G`, `ctrl-p`, `alt-<`, `shift-tab` or `PageDown`.
+    pub fn parse(text: &str) -> Option<KeyBinding> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        while let Some((prefix, remainder)) = rest.split_once('-') {
+            let modifier = match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" | "c" => KeyModifiers::CONTROL,
+                "alt" | "m" => KeyModifiers::ALT,
+                "shift" | "s" => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            if remainder.is_empty() {
+                break;
+            }
+            modifiers |= modifier;
+            rest = remainder;
+        }
+
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "space" => KeyCode::Char(' '),
+            "backtab" => KeyCode::BackTab,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            lower => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                        KeyCode::Char(c.to_ascii_uppercase())
+                    }
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => {
+                        let n = lower.strip_prefix('f')?.parse::<u8>().ok()?;
+                        KeyCode::F(n)
+                    }
+                }
+            }
+        };
+        Some(KeyBinding::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(KeyBinding, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap::vim()
+    }
+}
+
+impl Keymap {
+    /// Arrow keys plus vim-style letters.
+    pub fn vim() -> Keymap {
+        use KeyCode::*;
+        let mut keymap = Keymap::common();
+        keymap.add(Action::MoveUp, [KeyBinding::plain(Char('k'))]);
+        keymap.add(Action::MoveDown, [KeyBinding::plain(Char('j'))]);
+        keymap.add(Action::MoveLeft, [KeyBinding::plain(Char('h'))]);
+        keymap.add(Action::MoveRight, [KeyBinding::plain(Char('l'))]);
+        // it's a lot easier to go the first one this way than to use "gg" like in vim
+        keymap.add(Action::GoToTop, [KeyBinding::plain(Char('g'))]);
+        keymap.add(Action::GoToBottom, [KeyBinding::plain(Char('G'))]);
+        keymap.add(Action::ToggleNode, [KeyBinding::plain(Char('c'))]);
+        keymap
+    }
+
+    /// Arrow keys plus emacs-style control sequences.
+    pub fn emacs() -> Keymap {
+        let mut keymap = Keymap::common();
+        keymap.add(Action::MoveUp, [KeyBinding::ctrl('p')]);
+        keymap.add(Action::MoveDown, [KeyBinding::ctrl('n')]);
+        keymap.add(Action::MoveLeft, [KeyBinding::ctrl('b')]);
+        keymap.add(Action::MoveRight, [KeyBinding::ctrl('f')]);
+        keymap.add(Action::PageUp, [KeyBinding::alt('v')]);
+        keymap.add(Action::PageDown, [KeyBinding::ctrl('v')]);
+        keymap.add(Action::GoToTop, [KeyBinding::alt('<')]);
+        keymap.add(Action::GoToBottom, [KeyBinding::alt('>')]);
+        keymap.add(Action::Search, [KeyBinding::ctrl('s')]);
+        keymap.add(Action::YankPath, [KeyBinding::alt('w')]);
+        keymap.add(Action::Back, [KeyBinding::ctrl('g')]);
+        keymap.add(Action::CommandPalette, [KeyBinding::alt('x')]);
+        keymap
+    }
+
+    /// Bindings shared by every preset.
+    fn common() -> Keymap {
+        use KeyCode::*;
+        let mut keymap = Keymap { bindings: vec![] };
+        keymap.add(Action::MoveUp, [KeyBinding::plain(Up)]);
+        keymap.add(Action::MoveDown, [KeyBinding::plain(Down)]);
+        keymap.add(Action::MoveLeft, [KeyBinding::plain(Left)]);
+        keymap.add(Action::MoveRight, [KeyBinding::plain(Right)]);
+        keymap.add(Action::PageUp, [KeyBinding::plain(PageUp)]);
+        keymap.add(Action::PageDown, [KeyBinding::plain(PageDown)]);
+        keymap.add(Action::GoToTop, [KeyBinding::plain(Home)]);
+        keymap.add(Action::GoToBottom, [KeyBinding::plain(End)]);
+        keymap.add(Action::ToggleNode, [KeyBinding::plain(Enter)]);
+        keymap.add(Action::NextItem, [KeyBinding::plain(Tab)]);
+        keymap.add(Action::PrevItem, [KeyBinding::plain(BackTab)]);
+        keymap.add(Action::ExpandAll, [KeyBinding::plain(Char('f'))]);
+        keymap.add(
+            Action::HistoryBack,
+            [
+                KeyBinding::new(Left, KeyModifiers::ALT),
+                KeyBinding::ctrl('o'),
+            ],
+        );
+        keymap.add(
+            Action::HistoryForward,
+            [KeyBinding::new(Right, KeyModifiers::ALT)],
+        );
+        keymap.add(Action::SetMark, [KeyBinding::plain(Char('m'))]);
+        keymap.add(Action::JumpToMark, [KeyBinding::plain(Char('\''))]);
+        keymap.add(Action::FollowReference, [KeyBinding::plain(Char('e'))]);
+        keymap.add(Action::CyclePlot, [KeyBinding::plain(Char('p'))]);
+        keymap.add(Action::CycleColormap, [KeyBinding::plain(Char('C'))]);
+        keymap.add(
+            Action::ZoomIn,
+            [KeyBinding::plain(Char('+')), KeyBinding::plain(Char('='))],
+        );
+        keymap.add(Action::ZoomOut, [KeyBinding::plain(Char('-'))]);
+        keymap.add(Action::PanLeft, [KeyBinding::plain(Char('<'))]);
+        keymap.add(Action::PanRight, [KeyBinding::plain(Char('>'))]);
+        keymap.add(Action::MoreBins, [KeyBinding::plain(Char('b'))]);
+        keymap.add(Action::FewerBins, [KeyBinding::plain(Char('B'))]);
+        keymap.add(Action::ToggleLogCounts, [KeyBinding::plain(Char('a'))]);
+        keymap.add(Action::CycleHistogramClip, [KeyBinding::plain(Char('x'))]);
+        keymap.add(Action::NextField, [KeyBinding::plain(Char('n'))]);
+        keymap.add(Action::PrevField, [KeyBinding::plain(Char('N'))]);
+        keymap.add(Action::NextSlice, [KeyBinding::plain(Char('.'))]);
+        keymap.add(Action::PrevSlice, [KeyBinding::plain(Char(','))]);
+        keymap.add(Action::ToggleHexView, [KeyBinding::plain(Char('X'))]);
+        keymap.add(Action::ToggleChunkMap, [KeyBinding::plain(Char('M'))]);
+        keymap.add(Action::ToggleFillValues, [KeyBinding::plain(Char('F'))]);
+        keymap.add(Action::YankHistogram, [KeyBinding::plain(Char('Y'))]);
+        keymap.add(Action::Search, [KeyBinding::plain(Char('/'))]);
+        keymap.add(Action::YankPath, [KeyBinding::plain(Char('y'))]);
+        keymap.add(Action::RunPostCommand, [KeyBinding::plain(Char('i'))]);
+        keymap.add(Action::NextTab, [KeyBinding::plain(Char(']'))]);
+        keymap.add(Action::PrevTab, [KeyBinding::plain(Char('['))]);
+        keymap.add(Action::OpenFileBrowser, [KeyBinding::plain(Char('o'))]);
+        keymap.add(Action::ParentDir, [KeyBinding::plain(Backspace)]);
+        keymap.add(Action::Refresh, [KeyBinding::plain(Char('r'))]);
+        keymap.add(Action::RecentFiles, [KeyBinding::plain(Char('R'))]);
+        keymap.add(Action::ToggleLogs, [KeyBinding::plain(Char('L'))]);
+        keymap.add(Action::ShowHelp, [KeyBinding::plain(Char('?'))]);
+        keymap.add(Action::CommandPalette, [KeyBinding::ctrl('p')]);
+        keymap.add(Action::Back, [KeyBinding::plain(Esc)]);
+        keymap.add(Action::Quit, [KeyBinding::plain(Char('q'))]);
+        keymap
+    }
+
+    pub fn preset(name: &str) -> Option<Keymap> {
+        match name {
+            "vim" | "default" => Some(Keymap::vim()),
+            "emacs" => Some(Keymap::emacs()),
+            _ => None,
+        }
+    }
+
+    fn add(&mut self, action: Action, keys: impl IntoIterator<Item = KeyBinding>) {
+        for key in keys {
+            // A key triggers a single action, the latest binding wins
+            self.bindings.retain(|(k, _)| *k != key);
+            self.bindings.push((key, action));
+        }
+    }
+
+    /// Replace the keys bound to `action`.
+    pub fn bind(&mut self, action: Action, keys: Vec<KeyBinding>) {
+        self.bindings.retain(|(_, a)| *a != action);
+        self.add(action, keys);
+    }
+
+    pub fn action_for(&self, key: &KeyEvent) -> Option<Action> {
+        let pressed = KeyBinding::new(key.code, key.modifiers);
+        self.bindings
+            .iter()
+            .find(|(k, _)| *k == pressed)
+            .map(|(_, action)| *action)
+    }
+
+    pub fn keys_for(&self, action: Action) -> Vec<KeyBinding> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(k, _)| *k)
+            .collect()
+    }
+
+    /// The keys for `action` as shown to the user, e.g. `↑,k`.
+    pub fn keys_text(&self, action: Action) -> String {
+        self.keys_for(action)
+            .iter()
+            .map(|k| k.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_rebind() {
+        assert_eq!(KeyBinding::parse("ctrl-p"), Some(KeyBinding::ctrl('p')));
+        assert_eq!(KeyBinding::parse("alt-<"), Some(KeyBinding::alt('<')));
+        assert_eq!(KeyBinding::parse("ctrl--"), Some(KeyBinding::ctrl('-')));
+        assert_eq!(
+            KeyBinding::parse("shift-tab"),
+            Some(KeyBinding::plain(KeyCode::BackTab))
+        );
+        assert_eq!(
+            KeyBinding::parse("shift-g"),
+            Some(KeyBinding::plain(KeyCode::Char('G')))
+        );
+        assert_eq!(
+            KeyBinding::parse("PageDown"),
+            Some(KeyBinding::plain(KeyCode::PageDown))
+        );
+        assert_eq!(KeyBinding::parse("nonsense"), None);
+
+        let mut keymap = Keymap::vim();
+        // Terminals may report shift along with an uppercase letter
+        let shift_g = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.action_for(&shift_g), Some(Action::GoToBottom));
+
+        keymap.bind(
+            Action::Quit,
+            vec![KeyBinding::ctrl('q'), KeyBinding::plain(KeyCode::Char('j'))],
+        );
+        let j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+        assert_eq!(keymap.action_for(&j), Some(Action::Quit));
+        assert_eq!(keymap.keys_text(Action::MoveDown), "↓");
+        assert_eq!(keymap.keys_text(Action::Quit), "Ctrl+q,j");
+    }
+}
@@ -0,0 +1,258 @@
+use plotters::coord;
+use plotters::prelude::DrawingArea;
+use plotters::prelude::*;
+use plotters::prelude::{ChartBuilder, LabelAreaPosition};
+use plotters_ratatui_backend::{
+    widget_fn, AreaResult, Draw, Error, PlottersWidget, RatatuiBackend,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::dim_scales::AxisScale;
+use crate::num_utils;
+
+/// Upper bound on the number of points sent from the analysis process.
+const MAX_LINE_POINTS: usize = 1 << 18;
+
+/// The braille canvas has two dots per terminal column.
+const DOTS_PER_COLUMN: usize = 2;
+
+/// Values of a 1D dataset. Long datasets are reduced to the min and max of equally sized buckets,
+/// so zooming in on them stops at bucket resolution but spikes are never lost.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineData {
+    pub len: usize,
+    pub bucket_size: usize,
+    pub mins: Vec<f64>,
+    pub maxs: Vec<f64>,
+    /// Dimension scale labelling the x axis instead of the index.
+    pub x_scale: Option<AxisScale>,
+}
+
+/// The visible part of the x axis, as fractions of the dataset length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Default for XRange {
+    fn default() -> XRange {
+        XRange {
+            start: 0.0,
+            end: 1.0,
+        }
+    }
+}
+
+impl XRange {
+    /// Scale the visible width by `factor` around its center, showing at least `min_width`.
+    pub fn zoom(&mut self, factor: f64, min_width: f64) {
+        let center = (self.start + self.end) / 2.0;
+        let width = ((self.end - self.start) * factor).clamp(min_width.min(1.0), 1.0);
+        self.start = center - width / 2.0;
+        self.end = center + width / 2.0;
+        self.pan(0.0);
+    }
+
+    /// Move by `amount` times the visible width, stopping at the ends.
+    pub fn pan(&mut self, amount: f64) {
+        let width = self.end - self.start;
+        self.start = (self.start + amount * width).clamp(0.0, 1.0 - width);
+        self.end = self.start + width;
+    }
+}
+
+/// One pixel column of the plot: the index where it starts and the value range it covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Column {
+    pub x: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl LineData {
+    pub fn from_values(values: &[f64]) -> LineData {
+        let bucket_size = values.len().div_ceil(MAX_LINE_POINTS).max(1);
+        let (mins, maxs) = values
+            .chunks(bucket_size)
+            .map(|bucket| finite_min_max(bucket.iter().map(|&v| (v, v))))
+            .unzip();
+        LineData {
+            len: values.len(),
+            bucket_size,
+            mins,
+            maxs,
+            x_scale: None,
+        }
+    }
+
+    /// Min/max decimation of the visible range to at most `num_columns` columns.
+    /// Columns without any finite values are left out and split the rest into runs,
+    /// so excluded values show as gaps in the line.
+    pub fn decimate(&self, range: XRange, num_columns: usize) -> Vec<Vec<Column>> {
+        let num_buckets = self.mins.len();
+        let first = ((range.start * num_buckets as f64).floor() as usize).min(num_buckets);
+        let last = ((range.end * num_buckets as f64).ceil() as usize).clamp(first, num_buckets);
+        let num_visible = last - first;
+        let num_columns = num_columns.clamp(1, num_visible.max(1));
+
+        let mut runs = vec![vec![]];
+        for c in 0..num_columns {
+            let b0 = first + c * num_visible / num_columns;
+            let b1 = (first + (c + 1) * num_visible / num_columns).max(b0 + 1);
+            let (min, max) = finite_min_max(
+                self.mins[b0..b1.min(num_buckets)]
+                    .iter()
+                    .copied()
+                    .zip(self.maxs[b0..b1.min(num_buckets)].iter().copied()),
+            );
+            if min.is_finite() {
+                runs.last_mut().unwrap().push(Column {
+                    x: (b0 * self.bucket_size) as f64,
+                    min,
+                    max,
+                });
+            } else if runs.last().is_some_and(|run| !run.is_empty()) {
+                runs.push(vec![]);
+            }
+        }
+        runs.retain(|run| !run.is_empty());
+        runs
+    }
+}
+
+/// Smallest min and largest max, ignoring NaN. Both are NaN if nothing was finite.
+fn finite_min_max(pairs: impl Iterator<Item = (f64, f64)>) -> (f64, f64) {
+    let (min, max) = pairs.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (a, b)| {
+        (lo.min(a), hi.max(b))
+    });
+    if min.is_finite() && max.is_finite() {
+        (min, max)
+    } else {
+        (f64::NAN, f64::NAN)
+    }
+}
+
+pub fn line_plot_widget(
+    line_data: &LineData,
+    range: XRange,
+    _widget_height: u16,
+    widget_width: u16,
+) -> PlottersWidget<impl Draw, impl Fn(DrawingAreaErrorKind<Error>)> {
+    let num_columns = widget_width as usize * DOTS_PER_COLUMN;
+    let runs = line_data.decimate(range, num_columns);
+    let columns: Vec<Column> = runs.concat();
+    // Few enough single values to be told apart get a marker each
+    let is_sparse = columns.len() * 4 < num_columns && columns.iter().all(|c| c.min == c.max);
+
+    let x0 = range.start * line_data.len as f64;
+    let x1 = (range.end * line_data.len as f64).max(x0 + 1.0);
+    let (mut y0, mut y1) = finite_min_max(columns.iter().map(|c| (c.min, c.max)));
+    if !y0.is_finite() {
+        (y0, y1) = (0.0, 1.0);
+    } else if y0 == y1 {
+        let pad = (y0.abs() * 0.1).max(1.0);
+        (y0, y1) = (y0 - pad, y1 + pad);
+    }
+
+    let x_scale = line_data.x_scale.clone();
+    let theme = &config::get().theme;
+    let accent = RGBColor(theme.accent.0, theme.accent.1, theme.accent.2);
+    let axis = RGBColor(theme.plot_axis.0, theme.plot_axis.1, theme.plot_axis.2);
+
+    let draw_fn = move |area: DrawingArea<RatatuiBackend, coord::Shift>| -> AreaResult {
+        let mut chart = ChartBuilder::on(&area)
+            .margin(1)
+            .set_label_area_size(LabelAreaPosition::Left, 30)
+            .set_label_area_size(LabelAreaPosition::Bottom, 10)
+            .build_cartesian_2d(x0..x1, y0..y1)?;
+
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .disable_y_mesh()
+            .y_label_formatter(&|y| num_utils::basic_float_fmt(*y as f32))
+            .x_labels(2u16.max(widget_width / 10).into())
+            .x_label_formatter(&|x| match &x_scale {
+                Some(scale) => scale.label(*x),
+                None => num_utils::large_int_fmt(*x as u64),
+            })
+            .axis_style(ShapeStyle {
+                color: axis.into(),
+                filled: true,
+                stroke_width: 0,
+            })
+            .label_style(("sans-serif", 15).into_font().color(&axis))
+            .draw()?;
+
+        for run in &runs {
+            let points = run.iter().flat_map(|c| {
+                if c.min == c.max {
+                    vec![(c.x, c.min)]
+                } else {
+                    vec![(c.x, c.min), (c.x, c.max)]
+                }
+            });
+            chart.draw_series(LineSeries::new(points, &accent))?;
+        }
+        if is_sparse {
+            chart.draw_series(columns.iter().map(|c| Cross::new((c.x, c.min), 2, accent)))?;
+        }
+
+        area.present()
+    };
+
+    widget_fn(draw_fn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimation_keeps_spikes() {
+        let mut values = vec![0.0; 10_000];
+        values[1234] = 100.0;
+        values[5678] = -50.0;
+        values[9000] = f64::NAN;
+        let line_data = LineData::from_values(&values);
+        assert_eq!(line_data.mins.len(), 10_000);
+
+        let columns = line_data.decimate(XRange::default(), 100).concat();
+        assert_eq!(columns.len(), 100);
+        assert_eq!(columns[12].max, 100.0);
+        assert_eq!(columns[56].min, -50.0);
+        assert_eq!(
+            columns[90],
+            Column {
+                x: 9000.0,
+                min: 0.0,
+                max: 0.0
+            }
+        );
+
+        // Zoomed in far enough, every value gets its own column
+        let mut range = XRange::default();
+        range.zoom(0.001, 0.0);
+        range.pan(-1000.0);
+        assert_eq!(range.start, 0.0);
+        assert!((range.end - 0.001).abs() < 1e-9);
+        let columns = line_data.decimate(range, 100).concat();
+        assert_eq!(columns.len(), 10);
+        assert!(columns.iter().all(|c| c.min == c.max));
+
+        // Values left out break the line instead of being drawn across
+        let mut values: Vec<f64> = (0..100).map(|i| i as f64).collect();
+        values[40..60].fill(f64::NAN);
+        let runs = LineData::from_values(&values).decimate(XRange::default(), 100);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].last().unwrap().x, 39.0);
+        assert_eq!(runs[1][0].x, 60.0);
+        assert_eq!(runs[1].len(), 40);
+
+        let huge = LineData::from_values(&vec![1.0; MAX_LINE_POINTS * 3 + 1]);
+        assert_eq!(huge.bucket_size, 4);
+        assert!(huge.mins.len() <= MAX_LINE_POINTS);
+    }
+}
@@ -1,15 +1,32 @@
 use crate::app::App;
-use clap;
 use color_eyre::Result;
 use std::error::Error;
-use tui_logger;
 
+mod advisor;
 mod analysis;
 mod app;
+mod chunk_map;
+mod command_palette;
+mod config;
+mod dim_scales;
 mod events;
+mod file_browser;
+mod file_info;
+mod file_tab;
+mod fill_value;
+mod filters;
 mod h5_utils;
+mod heatmap;
+mod hexdump;
 mod hist_plot;
+mod history;
+mod keymap;
+mod line_plot;
 mod num_utils;
+mod object_header;
+mod references;
+mod session;
+mod string_stats;
 mod tree;
 mod ui;
 
@@ -50,9 +67,10 @@ fn main() -> Result<(), Box<dyn Error>> {
         .arg(
             clap::Arg::new("h5file")
                 .value_name("FILE")
-                .help("Name of hdf5 file to inspect")
-                .value_hint(clap::ValueHint::FilePath)
-                .required_unless_present("generate-dummy-file"),
+                .help("Name of hdf5 file(s) to inspect, each opens in its own tab. Pass a directory (or nothing) to browse for files")
+                .value_hint(clap::ValueHint::AnyPath)
+                .num_args(0..)
+                .action(clap::ArgAction::Append),
         )
         .arg(
             clap::Arg::new("logs")
@@ -62,6 +80,12 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .value_hint(clap::ValueHint::FilePath)
                 .required(false),
         )
+        .arg(
+            clap::Arg::new("advise")
+                .long("advise")
+                .help("Check the chunking, compression and allocation of every dataset in the files (or HDF5 files under the directories), print advice and exit")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             clap::Arg::new("generate-dummy-file")
                 .long("generate-dummy-file")
@@ -77,11 +101,20 @@ fn main() -> Result<(), Box<dyn Error>> {
         return Ok(());
     }
 
-    // For all other operations, h5file is required
-    let h5_file_name: &String = matches
-        .get_one("h5file")
-        .expect("clap should have enforced presence of h5file argument");
-    let h5_file_path = std::path::PathBuf::from(h5_file_name);
+    let paths: Vec<std::path::PathBuf> = matches
+        .get_many::<String>("h5file")
+        .unwrap_or_default()
+        .map(std::path::PathBuf::from)
+        .collect();
+
+    if matches.get_flag("advise") {
+        return advise(&paths);
+    }
+
+    // Directories are browsed, everything else is opened as a file
+    let (browse_dirs, h5_file_paths): (Vec<_>, Vec<_>) =
+        paths.into_iter().partition(|path| path.is_dir());
+    let browse_dir = browse_dirs.into_iter().next();
 
     initialize_logger(matches.get_one::<String>("logs"))?;
 
@@ -90,7 +123,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let runtime = build_runtime();
 
     color_eyre::install()?;
-    let app = App::new(h5_file_path.clone());
+    let app = App::new(h5_file_paths, browse_dir);
 
     let res = runtime.block_on(app.run());
 
@@ -100,6 +133,53 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
 }
 
+/// Prints the advice for every file, directories are searched for HDF5 files.
+/// Files that can't be checked are reported and skipped.
+fn advise(paths: &[std::path::PathBuf]) -> Result<(), Box<dyn Error>> {
+    if paths.is_empty() {
+        return Err("--advise needs at least one file or directory".into());
+    }
+    let mut num_checked = 0;
+    let mut num_failed = 0;
+    for path in paths {
+        let files = match advisor::files_to_check(path) {
+            Ok(files) => files,
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                num_failed += 1;
+                continue;
+            }
+        };
+        if files.is_empty() {
+            eprintln!("{}: no HDF5 files found", path.display());
+        }
+        for file in files {
+            match advisor::file_report(&file) {
+                Ok(report) => {
+                    print!("{}", report);
+                    num_checked += 1;
+                }
+                Err(e) => {
+                    eprintln!("{}: {}", file.display(), e);
+                    num_failed += 1;
+                }
+            }
+        }
+    }
+    if num_failed > 0 {
+        return Err(format!(
+            "{} of {} files could not be checked",
+            num_failed,
+            num_checked + num_failed
+        )
+        .into());
+    }
+    if num_checked == 0 {
+        return Err("No HDF5 files to check".into());
+    }
+    Ok(())
+}
+
 fn initialize_logger(log_file_path: Option<&String>) -> Result<(), Box<dyn Error>> {
     // Initialize tui_logger as the main logger
     tui_logger::init_logger(log::LevelFilter::Trace)?;
@@ -158,7 +238,7 @@ fn build_runtime() -> tokio::runtime::Runtime {
 //     }
 
 //     fn run_app(h5_file_path: std::path::PathBuf) -> Result<(), Box<dyn Error>> {
-//         let app = App::new(h5_file_path);
+//         let app = App::new(vec![h5_file_path], None);
 
 //         let backend = ratatui::backend::TestBackend::new(200, 120);
 //         let terminal = ratatui::Terminal::new(backend).unwrap();
@@ -1,4 +1,6 @@
+use crate::config;
 use duplicate::duplicate_item;
+use half::f16;
 use num_traits::ToPrimitive;
 use numfmt::{Formatter, Precision, Scales};
 use std::fmt::Display;
@@ -23,6 +25,7 @@ pub trait Summable: Sized {
     [ i16 ] [i64];
     [ i32 ] [i64];
     [ i64 ] [i128];
+    [ f16 ] [f64];
     [ f32 ] [f64];
     [ f64 ] [f64];
 )]
@@ -45,6 +48,7 @@ pub trait IsNan {
     [i16] [false];
     [i32] [false];
     [i64] [false];
+    [f16] [self.is_nan()];
     [f32] [self.is_nan()];
     [f64] [self.is_nan()];
 )]
@@ -69,6 +73,7 @@ pub trait MyToPrimitive {
     [i16] [Some((*self).to_f64().unwrap())];
     [i32] [Some((*self).to_f64().unwrap())];
     [i64] [Some((*self).to_f64().unwrap())];
+    [f16] [Some(self.to_f64())];
     [f32] [Some((*self).to_f64().unwrap())];
     [f64] [Some((*self).to_f64().unwrap())];
 )]
@@ -78,14 +83,23 @@ impl MyToPrimitive for num_type {
     }
 }
 
-pub fn format_integer_with_underscore(num: u64) -> String {
+/// Linearly interpolated percentile `p` (0 to 100) of sorted, non-empty `values`.
+pub fn percentile_of_sorted(values: &[f64], p: f64) -> f64 {
+    let position = (p / 100.0).clamp(0.0, 1.0) * (values.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    values[below] + (values[above] - values[below]) * (position - below as f64)
+}
+
+pub fn format_integer_with_separator(num: u64) -> String {
+    let separator = &config::get().thousands_separator;
     let num_str = num.to_string();
     let mut formatted = String::new();
     let len = num_str.len();
 
     for (i, c) in num_str.chars().enumerate() {
-        if i > 0 && (len - i) % 3 == 0 {
-            formatted.push('_');
+        if i > 0 && (len - i).is_multiple_of(3) {
+            formatted.push_str(separator);
         }
         formatted.push(c);
     }
@@ -94,7 +108,7 @@ pub fn format_integer_with_underscore(num: u64) -> String {
 }
 
 pub fn file_size_fmt_no_scale(size: u64) -> String {
-    format_integer_with_underscore(size) + " B"
+    format_integer_with_separator(size) + " B"
 }
 
 pub fn file_size_fmt(size: u64) -> String {
@@ -129,6 +143,22 @@ pub fn basic_float_fmt(x: f32) -> String {
     f.fmt2(x).to_owned()
 }
 
+/// Format a float for the stats, rounded to the configured number of significant digits.
+pub fn float_fmt(x: f64) -> String {
+    match config::get().float_significant_digits {
+        Some(digits) if x.is_finite() && x != 0.0 => {
+            let magnitude = x.abs().log10().floor() as i32;
+            if magnitude >= digits as i32 || magnitude < -4 {
+                format!("{:.*e}", digits - 1, x)
+            } else {
+                let decimals = (digits as i32 - 1 - magnitude) as usize;
+                format!("{:.*}", decimals, x)
+            }
+        }
+        _ => dtoa::Buffer::new().format(x).to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
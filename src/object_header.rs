@@ -0,0 +1,181 @@
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::ptr;
+
+use chrono::{DateTime, Local};
+use hdf5_metno as hdf5;
+use hdf5_metno_sys::h5::{H5_ih_info_t, H5free_memory};
+use hdf5_metno_sys::h5o::{
+    H5O_hdr_info_t, H5O_info2_t, H5Oget_info3, H5Oget_native_info, H5Otoken_to_str, H5O_INFO_BASIC,
+    H5O_INFO_TIME, H5O_NATIVE_INFO_ALL,
+};
+
+use crate::num_utils;
+
+/// Same layout as `H5O_native_info_t`, whose fields aren't public.
+#[repr(C)]
+#[derive(Default)]
+struct NativeInfo {
+    hdr: H5O_hdr_info_t,
+    object_index: H5_ih_info_t,
+    attribute_index: H5_ih_info_t,
+}
+
+/// What the object header of a group or dataset records about it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectHeader {
+    /// Token of the object, its address in files using the native format.
+    pub token: String,
+    /// Number of hard links to the object.
+    pub ref_count: u32,
+    pub version: u32,
+    pub num_messages: u32,
+    pub num_chunks: u32,
+    pub total_size: u64,
+    pub free_size: u64,
+    /// Size of the dense storage of attributes, 0 when they are in the header.
+    pub attribute_storage: u64,
+    /// Seconds since the epoch, 0 when the file doesn't track times.
+    pub created: i64,
+    pub modified: i64,
+    pub changed: i64,
+}
+
+fn time_text(seconds: i64) -> String {
+    DateTime::from_timestamp(seconds, 0)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or(seconds.to_string())
+}
+
+impl ObjectHeader {
+    pub fn read(location: &hdf5::Location) -> hdf5::Result<ObjectHeader> {
+        hdf5::sync::sync(|| unsafe {
+            let mut info: H5O_info2_t = std::mem::zeroed();
+            if H5Oget_info3(location.id(), &mut info, H5O_INFO_BASIC | H5O_INFO_TIME) < 0 {
+                return Err(hdf5::Error::from("could not read the object info"));
+            }
+            let mut native = NativeInfo::default();
+            if H5Oget_native_info(
+                location.id(),
+                (&mut native as *mut NativeInfo).cast(),
+                H5O_NATIVE_INFO_ALL,
+            ) < 0
+            {
+                return Err(hdf5::Error::from("could not read the object header"));
+            }
+            let mut token_ptr: *mut c_char = ptr::null_mut();
+            let token = if H5Otoken_to_str(location.id(), &info.token, &mut token_ptr) >= 0
+                && !token_ptr.is_null()
+            {
+                let token = CStr::from_ptr(token_ptr).to_string_lossy().into_owned();
+                H5free_memory(token_ptr.cast());
+                token
+            } else {
+                "unknown".to_string()
+            };
+            Ok(ObjectHeader {
+                token,
+                ref_count: info.rc,
+                version: native.hdr.version,
+                num_messages: native.hdr.nmesgs,
+                num_chunks: native.hdr.nchunks,
+                total_size: native.hdr.space.total,
+                free_size: native.hdr.space.free,
+                attribute_storage: native.attribute_index.index_size
+                    + native.attribute_index.heap_size,
+                created: info.btime as i64,
+                modified: info.mtime as i64,
+                changed: info.ctime as i64,
+            })
+        })
+    }
+
+    pub fn rows(&self) -> Vec<(String, String)> {
+        let mut header = format!(
+            "version {}, {} in {} chunk{} ({} free), {} messages",
+            self.version,
+            num_utils::file_size_fmt(self.total_size),
+            self.num_chunks,
+            if self.num_chunks == 1 { "" } else { "s" },
+            num_utils::file_size_fmt(self.free_size),
+            self.num_messages
+        );
+        if self.attribute_storage > 0 {
+            header.push_str(&format!(
+                ", attributes stored densely in {}",
+                num_utils::file_size_fmt(self.attribute_storage)
+            ));
+        }
+        let mut rows = vec![
+            (
+                "Object address".to_string(),
+                match self.token.parse::<u64>() {
+                    Ok(address) => format!("{} ({:#x})", address, address),
+                    Err(_) => self.token.clone(),
+                },
+            ),
+            (
+                "Hard links".to_string(),
+                format!("{} (reference count)", self.ref_count),
+            ),
+            ("Object header".to_string(), header),
+        ];
+        if self.created == 0 && self.modified == 0 && self.changed == 0 {
+            rows.push((
+                "Object times".to_string(),
+                "Not tracked in this file".to_string(),
+            ));
+        } else {
+            for (key, time) in [
+                ("Created", self.created),
+                ("Modified", self.modified),
+                ("Metadata changed", self.changed),
+            ] {
+                if time != 0 {
+                    rows.push((key.to_string(), time_text(time)));
+                }
+            }
+        }
+        rows
+    }
+}
+
+/// Object header rows of `location`, or a single row saying why they are missing.
+pub fn object_rows(location: &hdf5::Location) -> Vec<(String, String)> {
+    match ObjectHeader::read(location) {
+        Ok(header) => header.rows(),
+        Err(e) => vec![("Object header".to_string(), format!("unreadable ({})", e))],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::h5_utils;
+
+    #[test]
+    fn test_object_header() {
+        let dummy = h5_utils::dummy_file();
+        let file = hdf5::File::open(&dummy.path).unwrap();
+
+        let dataset = ObjectHeader::read(&file.dataset("group1/pixels").unwrap()).unwrap();
+        assert_eq!(dataset.ref_count, 1);
+        assert!(dataset.total_size > dataset.free_size);
+        let rows = dataset.rows();
+        let address: u64 = dataset.token.parse().unwrap();
+        assert_eq!(rows[0].1, format!("{} ({:#x})", address, address));
+        assert!(rows[2]
+            .1
+            .starts_with(&format!("version {}, ", dataset.version)));
+        // Datasets are created without times, groups with them
+        assert_eq!(rows[3].1, "Not tracked in this file");
+
+        let group = ObjectHeader::read(&file.group("group3").unwrap()).unwrap();
+        assert!(group.created > 0);
+        assert!(group.rows().iter().any(|(key, _)| key == "Created"));
+    }
+}
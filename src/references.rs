@@ -0,0 +1,349 @@
+use std::ffi::CString;
+use std::fmt;
+use std::ptr;
+
+use hdf5::types::{Reference, TypeDescriptor, VarLenArray};
+use hdf5::{H5Type, ObjectReference, ObjectReference1, ObjectReference2, ReferencedObject};
+use hdf5_metno as hdf5;
+use hdf5_metno_sys::h5i::hid_t;
+use hdf5_metno_sys::h5p::H5P_DEFAULT;
+use hdf5_metno_sys::h5r::{
+    hdset_reg_ref_t, H5R_ref_t, H5R_type_t, H5Rcreate, H5Rget_name, H5Rget_region, H5Rget_type,
+    H5Ropen_region,
+};
+use hdf5_metno_sys::h5s::{
+    H5S_sel_type, H5Sget_select_elem_npoints, H5Sget_select_elem_pointlist,
+    H5Sget_select_hyper_blocklist, H5Sget_select_hyper_nblocks, H5Sget_select_type,
+};
+use hdf5_metno_sys::h5t::{H5T_class_t, H5Tget_class, H5Tget_super};
+
+use crate::num_utils;
+
+/// References listed in the stats of a reference dataset.
+const PREVIEW_REFERENCES: usize = 10;
+
+/// A legacy dataset region reference, as stored before HDF5 1.12.
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct RegionReference(hdset_reg_ref_t);
+
+impl RegionReference {
+    /// A reference to the `selection` of the dataset `name` in `group`.
+    pub fn new(
+        group: &hdf5::Group,
+        name: &str,
+        selection: hdf5::Selection,
+    ) -> hdf5::Result<RegionReference> {
+        let space = group.dataset(name)?.space()?.select(selection)?;
+        let name = CString::new(name).map_err(|e| e.to_string())?;
+        let mut reference = RegionReference([0; 12]);
+        let status = hdf5::sync::sync(|| unsafe {
+            H5Rcreate(
+                reference.0.as_mut_ptr().cast(),
+                group.id(),
+                name.as_ptr(),
+                H5R_type_t::H5R_DATASET_REGION1,
+                space.id(),
+            )
+        });
+        if status < 0 {
+            return Err("could not create region reference".into());
+        }
+        Ok(reference)
+    }
+}
+
+unsafe impl H5Type for RegionReference {
+    fn type_descriptor() -> TypeDescriptor {
+        TypeDescriptor::Reference(Reference::Region)
+    }
+}
+
+/// Where a reference points. Null and dangling references have no path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceTarget {
+    pub path: Option<String>,
+    /// Selected hyperslab or points of a region reference, e.g. `(0..2, 1)`.
+    pub region: Option<String>,
+}
+
+impl fmt::Display for ReferenceTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.path, &self.region) {
+            (Some(path), Some(region)) => write!(f, "→ {} {}", path, region),
+            (Some(path), None) => write!(f, "→ {}", path),
+            (None, _) => write!(f, "null"),
+        }
+    }
+}
+
+fn reference_kind(dtype: &hdf5::Datatype) -> Option<Reference> {
+    if dtype.is::<ObjectReference1>() {
+        Some(Reference::Object)
+    } else if dtype.is::<RegionReference>() {
+        Some(Reference::Region)
+    } else if dtype.is::<ObjectReference2>() {
+        Some(Reference::Std)
+    } else {
+        None
+    }
+}
+
+/// The descriptor of a reference type, directly or in variable length arrays like `DIMENSION_LIST`.
+/// `Datatype::to_descriptor` fails for these.
+pub fn reference_descriptor(dtype: &hdf5::Datatype) -> Option<TypeDescriptor> {
+    let class = hdf5::sync::sync(|| unsafe { H5Tget_class(dtype.id()) });
+    if class == H5T_class_t::H5T_VLEN {
+        let base_id = hdf5::sync::sync(|| unsafe { H5Tget_super(dtype.id()) });
+        let base: hdf5::Datatype = unsafe { hdf5::from_id(base_id) }.ok()?;
+        let kind = reference_kind(&base)?;
+        return Some(TypeDescriptor::VarLenArray(Box::new(
+            TypeDescriptor::Reference(kind),
+        )));
+    }
+    reference_kind(dtype).map(TypeDescriptor::Reference)
+}
+
+fn object_path(object: ReferencedObject) -> String {
+    match object {
+        ReferencedObject::Group(group) => group.name(),
+        ReferencedObject::Dataset(dataset) => dataset.name(),
+        ReferencedObject::Datatype(_) => "(named datatype)".to_string(),
+    }
+}
+
+/// Blocks or points listed in a region before the rest are summarised.
+const PREVIEW_REGION_PARTS: usize = 4;
+
+/// Coordinates of a block, `start..end` along each axis or just the index for a single element.
+fn block_text(start: &[u64], end: &[u64]) -> String {
+    let ranges: Vec<String> = start
+        .iter()
+        .zip(end)
+        .map(|(&s, &e)| {
+            if s == e {
+                s.to_string()
+            } else {
+                format!("{}..{}", s, e + 1)
+            }
+        })
+        .collect();
+    format!("({})", ranges.join(", "))
+}
+
+/// The hyperslab blocks or points selected in a region, e.g. `(0..2, 1)`.
+/// Read from the dataspace since `Dataspace::get_selection` rejects some valid hyperslabs.
+fn selection_text(space_id: hid_t) -> Option<String> {
+    let space: hdf5::Dataspace = unsafe { hdf5::from_id(space_id) }.ok()?;
+    let ndim = space.ndim();
+    let (parts, coords_per_part) = hdf5::sync::sync(|| unsafe {
+        match H5Sget_select_type(space.id()) {
+            H5S_sel_type::H5S_SEL_ALL => Some((vec![], 0)),
+            H5S_sel_type::H5S_SEL_NONE => Some((vec![], 0)),
+            H5S_sel_type::H5S_SEL_HYPERSLABS => {
+                let n = H5Sget_select_hyper_nblocks(space.id());
+                let mut coords = vec![0; n.max(0) as usize * 2 * ndim];
+                (H5Sget_select_hyper_blocklist(space.id(), 0, n as u64, coords.as_mut_ptr()) >= 0)
+                    .then_some((coords, 2 * ndim))
+            }
+            H5S_sel_type::H5S_SEL_POINTS => {
+                let n = H5Sget_select_elem_npoints(space.id());
+                let mut coords = vec![0; n.max(0) as usize * ndim];
+                (H5Sget_select_elem_pointlist(space.id(), 0, n as u64, coords.as_mut_ptr()) >= 0)
+                    .then_some((coords, ndim))
+            }
+            _ => None,
+        }
+    })?;
+    if coords_per_part == 0 {
+        return Some(
+            if space.selection_size() > 0 {
+                "(all)"
+            } else {
+                "(none)"
+            }
+            .to_string(),
+        );
+    }
+    let num_parts = parts.len() / coords_per_part;
+    let mut texts: Vec<String> = parts
+        .chunks_exact(coords_per_part)
+        .take(PREVIEW_REGION_PARTS)
+        .map(|part| {
+            // Points have a single corner, blocks a start and an end corner
+            let (start, end) = part.split_at(ndim);
+            block_text(start, if end.is_empty() { start } else { end })
+        })
+        .collect();
+    if num_parts > PREVIEW_REGION_PARTS {
+        texts.push(format!("… {} more", num_parts - PREVIEW_REGION_PARTS));
+    }
+    Some(texts.join(" "))
+}
+
+fn object_target<R: ObjectReference>(location: &hdf5::Location, reference: &R) -> ReferenceTarget {
+    ReferenceTarget {
+        path: location.dereference(reference).ok().map(object_path),
+        region: None,
+    }
+}
+
+fn region_target(location: &hdf5::Location, reference: &RegionReference) -> ReferenceTarget {
+    if reference.0.iter().all(|&b| b == 0) {
+        return ReferenceTarget {
+            path: None,
+            region: None,
+        };
+    }
+    let ptr = reference.0.as_ptr().cast();
+    let ref_type = H5R_type_t::H5R_DATASET_REGION1;
+    let path = hdf5::sync::sync(|| unsafe {
+        let len = H5Rget_name(location.id(), ref_type, ptr, ptr::null_mut(), 0);
+        if len <= 0 {
+            return None;
+        }
+        let mut name = vec![0u8; len as usize + 1];
+        H5Rget_name(
+            location.id(),
+            ref_type,
+            ptr,
+            name.as_mut_ptr().cast(),
+            name.len(),
+        );
+        name.truncate(len as usize);
+        String::from_utf8(name).ok()
+    });
+    let space_id = hdf5::sync::sync(|| unsafe { H5Rget_region(location.id(), ref_type, ptr) });
+    ReferenceTarget {
+        path,
+        region: selection_text(space_id),
+    }
+}
+
+/// Standard references can point at objects or at regions of datasets.
+fn std_target(location: &hdf5::Location, reference: &ObjectReference2) -> ReferenceTarget {
+    let ptr: *const H5R_ref_t = reference.ptr().cast();
+    let ref_type = hdf5::sync::sync(|| unsafe { H5Rget_type(ptr) });
+    let mut target = object_target(location, reference);
+    if ref_type == H5R_type_t::H5R_DATASET_REGION2 {
+        let space_id =
+            hdf5::sync::sync(|| unsafe { H5Ropen_region(ptr, H5P_DEFAULT, H5P_DEFAULT) });
+        target.region = selection_text(space_id);
+    }
+    target
+}
+
+/// Targets of every reference in a dataset or attribute, in memory order.
+/// `None` if it doesn't hold references.
+pub fn read_references(container: &hdf5::Container) -> hdf5::Result<Option<Vec<ReferenceTarget>>> {
+    let location: &hdf5::Location = container;
+    let Some(descriptor) = reference_descriptor(&container.dtype()?) else {
+        return Ok(None);
+    };
+    let targets = match descriptor {
+        TypeDescriptor::Reference(Reference::Object) => container
+            .read_raw::<ObjectReference1>()?
+            .iter()
+            .map(|r| object_target(location, r))
+            .collect(),
+        TypeDescriptor::Reference(Reference::Region) => container
+            .read_raw::<RegionReference>()?
+            .iter()
+            .map(|r| region_target(location, r))
+            .collect(),
+        TypeDescriptor::Reference(Reference::Std) => container
+            .read_raw::<ObjectReference2>()?
+            .iter()
+            .map(|r| std_target(location, r))
+            .collect(),
+        TypeDescriptor::VarLenArray(inner)
+            if *inner == TypeDescriptor::Reference(Reference::Object) =>
+        {
+            container
+                .read_raw::<VarLenArray<ObjectReference1>>()?
+                .iter()
+                .flat_map(|array| {
+                    array
+                        .iter()
+                        .map(|r| object_target(location, r))
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(targets))
+}
+
+/// Rows for the object info of a dataset holding references.
+pub fn reference_stats(targets: &[ReferenceTarget]) -> Vec<(String, String)> {
+    let mut preview: Vec<String> = targets
+        .iter()
+        .take(PREVIEW_REFERENCES)
+        .enumerate()
+        .map(|(i, target)| format!("[{}] {}", i, target))
+        .collect();
+    if targets.len() > PREVIEW_REFERENCES {
+        preview.push(format!(
+            "… {} more",
+            num_utils::format_integer_with_separator((targets.len() - PREVIEW_REFERENCES) as u64)
+        ));
+    }
+    let num_null = targets.iter().filter(|t| t.path.is_none()).count();
+    let mut paths: Vec<&String> = targets.iter().filter_map(|t| t.path.as_ref()).collect();
+    paths.sort();
+    paths.dedup();
+    vec![
+        ("References".to_owned(), preview.join("\n")),
+        (
+            "Null references".to_owned(),
+            num_utils::format_integer_with_separator(num_null as u64),
+        ),
+        (
+            "Distinct targets".to_owned(),
+            num_utils::format_integer_with_separator(paths.len() as u64),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::h5_utils;
+
+    #[test]
+    fn test_read_references() -> Result<(), Box<dyn std::error::Error>> {
+        let dummy = h5_utils::dummy_file();
+        let file = hdf5::File::open(&dummy.path)?;
+
+        let refs = file.dataset("group1/refs")?;
+        let targets = read_references(&refs)?.unwrap();
+        let paths: Vec<String> = targets.iter().map(|t| t.to_string()).collect();
+        assert_eq!(
+            paths,
+            [
+                "→ /group1/pixels",
+                "→ /group1/colors",
+                "→ /group1/particles"
+            ]
+        );
+        let source = refs.attr("source")?;
+        assert_eq!(
+            h5_utils::attribute_value_text(&source),
+            "→ /variable".to_string()
+        );
+
+        let region_refs = file.dataset("group1/region_refs")?;
+        let regions = read_references(&region_refs)?.unwrap();
+        assert_eq!(regions[0].to_string(), "→ /group1/pixels (0..2, 1)");
+        assert_eq!(regions[1].to_string(), "→ /group1/colors (10..20)");
+
+        let stats = reference_stats(&regions);
+        assert_eq!(stats[1], ("Null references".to_owned(), "0".to_owned()));
+        assert_eq!(stats[2], ("Distinct targets".to_owned(), "2".to_owned()));
+
+        let colors = file.dataset("group1/colors")?;
+        assert!(read_references(&colors)?.is_none());
+        Ok(())
+    }
+}
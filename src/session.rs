@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+const SESSION_FILE_NAME: &str = "session.json";
+const MAX_RECENT_FILES: usize = 20;
+
+/// Where the user left off in a file. Tree nodes are stored by name since node ids change between runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileSession {
+    pub selected: Vec<String>,
+    pub opened: Vec<Vec<String>>,
+    pub search_query: String,
+    pub tree_width_percentage: u16,
+    #[serde(default)]
+    pub marks: BTreeMap<char, Vec<String>>,
+}
+
+/// Recently opened files and their sessions, persisted in the config dir.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionStore {
+    pub recent_files: Vec<PathBuf>,
+    pub files: HashMap<PathBuf, FileSession>,
+    #[serde(skip)]
+    store_path: Option<PathBuf>,
+}
+
+impl SessionStore {
+    pub fn load(config_dir: Option<&Path>) -> SessionStore {
+        let Some(store_path) = config_dir.map(|dir| dir.join(SESSION_FILE_NAME)) else {
+            return SessionStore::default();
+        };
+
+        let mut store = match std::fs::read_to_string(&store_path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log::error!("Ignoring invalid session file {:?}: {}", store_path, e);
+                SessionStore::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => SessionStore::default(),
+            Err(e) => {
+                log::error!("Failed to read session file {:?}: {}", store_path, e);
+                SessionStore::default()
+            }
+        };
+        store.store_path = Some(store_path);
+        store
+    }
+
+    pub fn save(&self) {
+        let Some(store_path) = &self.store_path else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(store_path, json).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => log::debug!("Saved session to {:?}", store_path),
+            Err(e) => log::error!("Failed to save session to {:?}: {}", store_path, e),
+        }
+    }
+
+    /// Move `path` to the front of the recent files.
+    pub fn add_recent_file(&mut self, path: &Path) {
+        let path = session_key(path);
+        self.recent_files.retain(|p| p != &path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+        // Forget sessions of files that fell off the recent list
+        let recent_files = &self.recent_files;
+        self.files.retain(|p, _| recent_files.contains(p));
+    }
+
+    pub fn file_session(&self, path: &Path) -> Option<&FileSession> {
+        self.files.get(&session_key(path))
+    }
+
+    pub fn set_file_session(&mut self, path: &Path, session: FileSession) {
+        self.files.insert(session_key(path), session);
+    }
+}
+
+fn session_key(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("h5inspect_session_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut store = SessionStore::load(Some(&dir));
+        assert!(store.recent_files.is_empty());
+
+        let session = FileSession {
+            selected: vec!["/".into(), "group1".into(), "bool_ds".into()],
+            opened: vec![vec!["/".into()], vec!["/".into(), "group1".into()]],
+            search_query: "bool".into(),
+            tree_width_percentage: 30,
+            marks: BTreeMap::from([('a', vec!["/".into(), "group1".into()])]),
+        };
+        for i in 0..MAX_RECENT_FILES + 2 {
+            store.add_recent_file(&dir.join(format!("{}.h5", i)));
+        }
+        store.add_recent_file(&dir.join("5.h5"));
+        store.set_file_session(&dir.join("5.h5"), session.clone());
+        store.save();
+
+        let store = SessionStore::load(Some(&dir));
+        assert_eq!(store.recent_files.len(), MAX_RECENT_FILES);
+        assert_eq!(store.recent_files[0], dir.join("5.h5"));
+        assert_eq!(
+            store.recent_files[1],
+            dir.join(format!("{}.h5", MAX_RECENT_FILES + 1))
+        );
+        assert_eq!(store.file_session(&dir.join("5.h5")), Some(&session));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::analysis::PlotData;
+use crate::hist_plot::HistogramSource;
+use crate::num_utils;
+
+/// Number of strings shown in the preview.
+const PREVIEW_STRINGS: usize = 10;
+
+/// Number of most frequent strings listed.
+const MOST_FREQUENT_STRINGS: usize = 5;
+
+/// Distinct strings that get counted, strings first seen after this many are not.
+const MAX_COUNTED_DISTINCT: usize = 1 << 20;
+
+/// Upper bound on the string lengths kept for the length histogram, more are sampled.
+const MAX_SAMPLED_LENGTHS: usize = 1 << 21;
+
+/// Stats of a string dataset, built up one string at a time so it can be read in chunks.
+pub struct StringStats {
+    num_strings: usize,
+    preview: Vec<String>,
+    num_empty: u64,
+    counts: HashMap<String, u64>,
+    /// Whether some strings weren't counted because `counts` was full.
+    counts_full: bool,
+    min_len: usize,
+    max_len: usize,
+    total_len: u64,
+    /// The length of every `length_stride`th string is kept for the histogram.
+    length_stride: usize,
+    lengths: Vec<f64>,
+}
+
+impl StringStats {
+    /// `expected` is the number of strings that will be added, used to sample the lengths.
+    pub fn new(expected: usize) -> StringStats {
+        StringStats {
+            num_strings: 0,
+            preview: vec![],
+            num_empty: 0,
+            counts: HashMap::new(),
+            counts_full: false,
+            min_len: usize::MAX,
+            max_len: 0,
+            total_len: 0,
+            length_stride: expected.div_ceil(MAX_SAMPLED_LENGTHS).max(1),
+            lengths: vec![],
+        }
+    }
+
+    pub fn add(&mut self, s: &str) {
+        let len = s.chars().count();
+        if self.num_strings < PREVIEW_STRINGS {
+            self.preview.push(s.to_owned());
+        }
+        if self.num_strings.is_multiple_of(self.length_stride) {
+            self.lengths.push(len as f64);
+        }
+        self.num_strings += 1;
+        self.num_empty += s.is_empty() as u64;
+        self.min_len = self.min_len.min(len);
+        self.max_len = self.max_len.max(len);
+        self.total_len += len as u64;
+
+        if let Some(count) = self.counts.get_mut(s) {
+            *count += 1;
+        } else if self.counts.len() < MAX_COUNTED_DISTINCT {
+            self.counts.insert(s.to_owned(), 1);
+        } else {
+            self.counts_full = true;
+        }
+    }
+
+    /// Rows for the object info and a histogram of the string lengths.
+    pub fn finish(self) -> (Vec<(String, String)>, Arc<PlotData>) {
+        let mut info = vec![];
+        if self.num_strings == 0 {
+            return (info, Arc::new(PlotData::default()));
+        }
+
+        let mut preview: Vec<String> = self.preview.iter().map(|s| format!("{:?}", s)).collect();
+        if self.num_strings > PREVIEW_STRINGS {
+            preview.push(format!(
+                "… {} more",
+                num_utils::format_integer_with_separator(
+                    (self.num_strings - PREVIEW_STRINGS) as u64
+                )
+            ));
+        }
+        info.push(("Preview".to_owned(), preview.join("\n")));
+
+        info.push((
+            "Empty strings".to_owned(),
+            num_utils::format_integer_with_separator(self.num_empty),
+        ));
+        let num_distinct = num_utils::format_integer_with_separator(self.counts.len() as u64);
+        info.push((
+            "Distinct strings".to_owned(),
+            if self.counts_full {
+                format!("> {}", num_distinct)
+            } else {
+                num_distinct
+            },
+        ));
+
+        let mean_len = self.total_len as f64 / self.num_strings as f64;
+        info.push(("Min length".to_owned(), self.min_len.to_string()));
+        info.push(("Mean length".to_owned(), num_utils::float_fmt(mean_len)));
+        info.push(("Max length".to_owned(), self.max_len.to_string()));
+
+        let mut most_frequent: Vec<(&String, &u64)> = self.counts.iter().collect();
+        // Ties are broken by the string so the order is stable
+        most_frequent.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let most_frequent: Vec<String> = most_frequent
+            .iter()
+            .take(MOST_FREQUENT_STRINGS)
+            .map(|(s, count)| {
+                format!(
+                    "{} × {:?}",
+                    num_utils::format_integer_with_separator(**count),
+                    s
+                )
+            })
+            .collect();
+        info.push(("Most frequent".to_owned(), most_frequent.join("\n")));
+
+        let plot_data = PlotData {
+            histogram: HistogramSource::from_sample(&self.lengths, true, self.length_stride),
+            ..Default::default()
+        };
+        (info, Arc::new(plot_data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hist_plot::HistogramSettings;
+
+    #[test]
+    fn test_string_stats() {
+        let mut stats = StringStats::new(15);
+        for s in ["b", "", "ccc", "b", "héllo", "b", "ccc"]
+            .into_iter()
+            .cycle()
+            .take(15)
+        {
+            stats.add(s);
+        }
+        let (info, plots) = stats.finish();
+        let row = |key: &str| info.iter().find(|(k, _)| k == key).unwrap().1.clone();
+
+        assert!(row("Preview").starts_with("\"b\"\n\"\"\n\"ccc\""));
+        assert!(row("Preview").ends_with("… 5 more"));
+        assert_eq!(row("Empty strings"), "2");
+        assert_eq!(row("Distinct strings"), "4");
+        assert_eq!(row("Min length"), "0");
+        assert_eq!(row("Max length"), "5");
+        assert_eq!(row("Most frequent").lines().next(), Some("7 × \"b\""));
+        assert_eq!(row("Most frequent").lines().nth(1), Some("4 × \"ccc\""));
+
+        let settings = HistogramSettings {
+            bins: 6,
+            log_counts: false,
+            clip_percentiles: None,
+        };
+        let histogram = plots.histogram.as_ref().unwrap().compute(&settings);
+        assert_eq!(histogram.counts, vec![2, 7, 0, 4, 0, 2]);
+    }
+}
@@ -19,6 +19,8 @@ where
     matching_indices: Vec<usize>,
     pub is_direct_match: bool,
     pub hdf5_object: Option<Hdf5Object>,
+    /// Whether the node is a dataset other datasets use as a dimension scale.
+    pub is_dimension_scale: bool,
 }
 
 impl<IdT> TreeNode<IdT>
@@ -76,6 +78,7 @@ where
             matching_indices: indices,
             is_direct_match,
             hdf5_object: None,
+            is_dimension_scale: false,
         }
     }
 
@@ -84,6 +87,11 @@ where
         self
     }
 
+    pub fn set_dimension_scale(mut self, is_dimension_scale: bool) -> Self {
+        self.is_dimension_scale = is_dimension_scale;
+        self
+    }
+
     pub fn set_hdf5_object(mut self, hdf5_object: Hdf5Object) -> Self {
         self.hdf5_object = Some(hdf5_object);
         self
@@ -134,13 +142,16 @@ where
             .collect();
 
         if i_match || !matching_children.is_empty() {
-            Some(TreeNode::new_with_indices(
-                self.id.clone(),
-                self.text.clone(),
-                matching_children,
-                indices.unwrap_or(vec![]),
-                i_match,
-            ))
+            Some(
+                TreeNode::new_with_indices(
+                    self.id.clone(),
+                    self.text.clone(),
+                    matching_children,
+                    indices.unwrap_or(vec![]),
+                    i_match,
+                )
+                .set_dimension_scale(self.is_dimension_scale),
+            )
         } else {
             None
         }
@@ -161,10 +172,10 @@ where
                 .iter()
                 .any(|child| path_to_first_match_helper(child, path))
             {
-                return true;
+                true
             } else {
                 path.pop();
-                return false;
+                false
             }
         }
 
@@ -179,20 +190,47 @@ where
 
     pub fn get_selected_node(&self, path: &[IdT]) -> Option<&TreeNode<IdT>> {
         if path.is_empty() {
-            return None;
+            None
         } else if self.id == path[0] {
             let path_for_children = &path[1..];
             if path_for_children.is_empty() {
                 return Some(self);
             }
 
-            return self
-                .children
+            self.children
                 .iter()
-                .find_map(|child| child.get_selected_node(path_for_children));
+                .find_map(|child| child.get_selected_node(path_for_children))
+        } else {
+            None
+        }
+    }
+
+    /// The node names along `path`, which unlike ids are stable between runs.
+    pub fn text_path(&self, path: &[IdT]) -> Option<Vec<String>> {
+        let (first, rest) = path.split_first()?;
+        if self.id != *first {
+            return None;
         }
+        let mut texts = vec![self.text.clone()];
+        if !rest.is_empty() {
+            let child = self.children.iter().find(|c| c.id == rest[0])?;
+            texts.extend(child.text_path(rest)?);
+        }
+        Some(texts)
+    }
 
-        return None;
+    /// The inverse of [`TreeNode::text_path`].
+    pub fn id_path(&self, text_path: &[String]) -> Option<Vec<IdT>> {
+        let (first, rest) = text_path.split_first()?;
+        if self.text != *first {
+            return None;
+        }
+        let mut ids = vec![self.id.clone()];
+        if !rest.is_empty() {
+            let child = self.children.iter().find(|c| c.text == rest[0])?;
+            ids.extend(child.id_path(rest)?);
+        }
+        Some(ids)
     }
 }
 
@@ -249,6 +287,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn test_get_selected_node() {
         let tree = TreeNode::<i32>::new(
             0,
@@ -265,4 +304,21 @@ mod tests {
         assert_eq!(tree.get_selected_node(&vec![]), None);
         assert_eq!(tree.get_selected_node(&vec![0, 5]), None);
     }
+
+    #[test]
+    fn test_text_path_round_trip() {
+        let tree = TreeNode::<i32>::new(
+            0,
+            "/",
+            vec![
+                TreeNode::new(1, "group1", vec![TreeNode::new(3, "ds", vec![])]),
+                TreeNode::new(2, "group2", vec![]),
+            ],
+        );
+        let texts = tree.text_path(&[0, 1, 3]).unwrap();
+        assert_eq!(texts, vec!["/", "group1", "ds"]);
+        assert_eq!(tree.id_path(&texts), Some(vec![0, 1, 3]));
+        assert_eq!(tree.text_path(&[0, 2, 3]), None);
+        assert_eq!(tree.id_path(&["/".into(), "missing".into()]), None);
+    }
 }
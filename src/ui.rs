@@ -1,5 +1,14 @@
-use crate::app::{App, SelectionMode};
-use crate::hist_plot;
+use crate::analysis::{PlotData, PlotKind};
+use crate::app::{self, App, PendingMark, SelectionMode};
+use crate::chunk_map::ChunkStyles;
+use crate::config;
+use crate::file_browser::BrowserEntryKind;
+use crate::heatmap::HeatmapWidget;
+use crate::hexdump::HexStyles;
+use crate::hist_plot::{self, Histogram};
+use crate::keymap::Action;
+use crate::line_plot;
+use crate::num_utils;
 use crate::tree;
 
 use num_traits::clamp;
@@ -14,20 +23,15 @@ use ratatui::{
     style::{Color, Style, Stylize},
     widgets::{
         Block, BorderType, Borders, Cell, Clear, Paragraph, Row, Scrollbar, ScrollbarOrientation,
-        ScrollbarState, Table, TableState, Wrap,
+        ScrollbarState, Table, TableState, Tabs, Wrap,
     },
     Frame,
 };
 use std::ops::Mul;
 use tui_big_text::{BigText, PixelSize};
-use tui_logger;
 use tui_tree_widget::Tree as WidgetTreeRoot;
 use tui_tree_widget::TreeItem as WidgetTreeItem;
 
-pub const MAGENTA_R: u8 = 0xfc;
-pub const MAGENTA_G: u8 = 0x4c;
-pub const MAGENTA_B: u8 = 0xb4;
-
 enum Styles {
     TreeItemHighlight,
     DefaultText,
@@ -37,25 +41,46 @@ enum Styles {
     BorderDefault,
     LogBorder,
     NoMatchesFound,
+    Copied,
+    Hovered,
+    Success,
+    KeyBinding,
+    Warning,
+    HelpTitle,
+    Code,
 }
 
 fn get_style(style: Styles, mode: SelectionMode) -> Style {
+    let theme = &config::get().theme;
     let s = match style {
         Styles::TreeItemHighlight => Style::new().reversed(),
-        Styles::DefaultText => Style::new().fg(Color::White),
-        Styles::SearchCharMatch => Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
-        Styles::Magenta => Style::new().fg(Color::Rgb(MAGENTA_R, MAGENTA_G, MAGENTA_B)),
-        Styles::BorderHighlight => Style::default().fg(Color::Red),
-        Styles::BorderDefault => Style::default().fg(Color::White),
-        Styles::LogBorder => Style::default().fg(Color::Blue),
-        Styles::NoMatchesFound => Style::default().bg(if mode == SelectionMode::HelpScreen {
-            Color::Rgb(0x9c, 0x33, 0x36)
-        } else {
-            Color::Red
-        }),
+        Styles::DefaultText => Style::new().fg(theme.text),
+        Styles::SearchCharMatch => Style::new()
+            .fg(theme.search_match)
+            .add_modifier(Modifier::BOLD),
+        Styles::Magenta => Style::new().fg(theme.accent_color()),
+        Styles::BorderHighlight => Style::default().fg(theme.border_highlight),
+        Styles::BorderDefault => Style::default().fg(theme.border_default),
+        Styles::LogBorder => Style::default().fg(theme.log_border),
+        Styles::NoMatchesFound => Style::default().bg(theme.no_matches),
+        Styles::Copied => Style::new().bg(theme.copied_bg).fg(theme.copied_fg),
+        Styles::Hovered => Style::new().bg(theme.hover_bg).fg(theme.hover_fg),
+        Styles::Success => Style::new().fg(theme.success).add_modifier(Modifier::BOLD),
+        Styles::KeyBinding => Style::new().fg(theme.key_binding),
+        Styles::Warning => Style::new().fg(theme.no_matches),
+        Styles::HelpTitle => Style::new()
+            .fg(theme.help_title_color())
+            .add_modifier(Modifier::BOLD),
+        Styles::Code => Style::new()
+            .fg(theme.code_fg)
+            .bg(theme.code_bg)
+            .add_modifier(Modifier::BOLD),
     };
 
-    if mode == SelectionMode::HelpScreen {
+    if matches!(
+        mode,
+        SelectionMode::HelpScreen | SelectionMode::CommandPalette
+    ) {
         s.add_modifier(Modifier::DIM)
     } else {
         s
@@ -63,11 +88,43 @@ fn get_style(style: Styles, mode: SelectionMode) -> Style {
 }
 
 pub fn ui(frame: &mut Frame, app: &mut App) {
+    let [tab_bar_area, main_area] = Layout::vertical([
+        Constraint::Length(if app.tabs.len() > 1 { 1 } else { 0 }),
+        Constraint::Min(0),
+    ])
+    .areas(frame.area());
+
+    render_tab_bar(frame, app, tab_bar_area);
+
+    if app.base_mode() == SelectionMode::FileBrowsing || app.tabs.is_empty() {
+        let [browser_area, log_area] = Layout::vertical([
+            Constraint::Percentage(if app.show_logs { 50 } else { 100 }),
+            Constraint::Min(0),
+        ])
+        .areas(main_area);
+        render_file_browser(frame, app, browser_area);
+        if app.show_logs {
+            render_logger(frame, app, log_area);
+        }
+
+        let help_screen_area = get_help_screen_area(frame.area());
+        if app.mode == SelectionMode::HelpScreen {
+            render_help_screen(frame, app, help_screen_area);
+        }
+        render_command_palette(frame, app);
+        app.set_last_object_info_area(Rect::default());
+        app.set_last_tree_area(Rect::default());
+        app.set_last_search_query_area(Rect::default());
+        app.set_last_help_screen_area(help_screen_area);
+        app.set_last_tab_bar_area(tab_bar_area);
+        return;
+    }
+
     let chunks = Layout::horizontal([
-        Constraint::Percentage(app.tree_width_percentage),
+        Constraint::Percentage(app.tab().tree_width_percentage),
         Constraint::Min(0),
     ])
-    .split(frame.area());
+    .split(main_area);
 
     let left_layout =
         Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).split(chunks[0]);
@@ -93,17 +150,49 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
     if app.mode == SelectionMode::HelpScreen {
         render_help_screen(frame, app, help_screen_area);
     }
+    render_command_palette(frame, app);
     app.set_last_object_info_area(object_info_area);
     app.set_last_tree_area(left_layout[0]);
     app.set_last_search_query_area(left_layout[1]);
     app.set_last_help_screen_area(help_screen_area);
+    app.set_last_tab_bar_area(tab_bar_area);
+}
+
+fn render_tab_bar(frame: &mut Frame, app: &mut App, area: Rect) {
+    app.tab_title_ranges.clear();
+    if area.height == 0 {
+        return;
+    }
+
+    const DIVIDER: &str = " | ";
+    let titles: Vec<String> = app
+        .tabs
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| format!("{}: {}", i + 1, tab.title()))
+        .collect();
+
+    // Tabs pads each title with a space on both sides and separates them with the divider
+    let mut x = area.x;
+    for title in &titles {
+        let width = title.chars().count() as u16 + 2;
+        app.tab_title_ranges.push(x..x + width);
+        x += width + DIVIDER.chars().count() as u16;
+    }
+
+    let tabs = Tabs::new(titles)
+        .select(app.active_tab)
+        .style(get_style(Styles::DefaultText, app.mode))
+        .highlight_style(get_style(Styles::TreeItemHighlight, app.mode))
+        .divider(DIVIDER);
+    frame.render_widget(tabs, area);
 }
 
 impl<IdT> tree::TreeNode<IdT>
 where
     IdT: Eq + std::hash::Hash + Clone + std::fmt::Debug,
 {
-    pub fn into_tree_item(
+    pub fn to_tree_item(
         &self,
         mode: SelectionMode,
         hovered_path: Option<&[IdT]>,
@@ -115,8 +204,8 @@ where
             .children()
             .iter()
             .map(|child| {
-                current_path.push(child.id().clone());
-                let item = child.into_tree_item(
+                current_path.push(child.id());
+                let item = child.to_tree_item(
                     mode,
                     hovered_path,
                     copied_indicator,
@@ -149,11 +238,12 @@ where
                         Span::raw(c.to_string())
                     };
                     if is_copied {
-                        span = span.bg(Color::Rgb(46, 125, 50)).fg(Color::White);
+                        span = span.patch_style(get_style(Styles::Copied, mode));
                     } else if is_hovered {
-                        span = span.bg(Color::Gray);
+                        let hovered = get_style(Styles::Hovered, mode);
+                        span = span.bg(hovered.bg.unwrap_or_default());
                         if !matching_indices.contains(&i) {
-                            span = span.fg(Color::Black);
+                            span = span.patch_style(hovered);
                         }
                     }
                     span
@@ -168,9 +258,19 @@ where
                 get_style(Styles::Magenta, mode),
             );
             if is_copied {
-                span = span.bg(Color::Rgb(46, 125, 50)).fg(Color::White);
+                span = span.patch_style(get_style(Styles::Copied, mode));
             } else if is_hovered {
-                span = span.bg(Color::Gray).fg(Color::Black);
+                span = span.patch_style(get_style(Styles::Hovered, mode));
+            }
+            formatted_text.push_span(span);
+        }
+
+        if self.is_dimension_scale {
+            let mut span = Span::styled(" [scale]", get_style(Styles::Magenta, mode));
+            if is_copied {
+                span = span.patch_style(get_style(Styles::Copied, mode));
+            } else if is_hovered {
+                span = span.patch_style(get_style(Styles::Hovered, mode));
             }
             formatted_text.push_span(span);
         }
@@ -178,10 +278,7 @@ where
         if is_copied {
             formatted_text.push_span(Span::styled(
                 " [Copied!]",
-                Style::new()
-                    .bg(Color::Rgb(46, 125, 50))
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
+                get_style(Styles::Copied, mode).add_modifier(Modifier::BOLD),
             ));
         }
 
@@ -197,19 +294,20 @@ fn render_object_info(frame: &mut Frame, app: &mut App, area: Rect) {
         false
     };
 
+    let dataset_view = dataset_view_lines(app);
+    let title = dataset_view
+        .as_ref()
+        .map_or("Object info", |(title, _)| *title);
     let object_info = Block::new()
         .title(if is_copied_all {
             Line::from(vec![
-                Span::raw("Object info"),
-                Span::styled(
-                    " [Copied!]",
-                    Style::new().fg(Color::Green).add_modifier(Modifier::BOLD),
-                ),
+                Span::raw(title),
+                Span::styled(" [Copied!]", get_style(Styles::Success, app.mode)),
             ])
         } else {
-            Line::from("Object info")
+            Line::from(title)
         })
-        .title_top(Line::from("Help screen (type '?')").right_aligned())
+        .title_top(help_hint_line())
         .title_bottom({
             let num_active_tasks = app.get_num_active_data_analysis_tasks();
 
@@ -226,7 +324,7 @@ fn render_object_info(frame: &mut Frame, app: &mut App, area: Rect) {
                 Line::from(format!(
                     "Analysis tasks: {}/{}",
                     num_active_tasks,
-                    App::NUM_ANALYSIS_PERMITS
+                    config::get().max_concurrent_analyses
                 ))
                 .left_aligned()
                 .style(get_style(Styles::DefaultText, app.mode).add_modifier(Modifier::DIM))
@@ -237,9 +335,7 @@ fn render_object_info(frame: &mut Frame, app: &mut App, area: Rect) {
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(if is_copied_all {
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD)
+            get_style(Styles::Success, app.mode)
         } else if app.mode == SelectionMode::ObjectInfoInspecting {
             get_style(Styles::BorderHighlight, app.mode)
         } else {
@@ -251,17 +347,22 @@ fn render_object_info(frame: &mut Frame, app: &mut App, area: Rect) {
     let mut rows = vec![];
     app.object_info_row_keys.clear();
 
+    if let Some((_, lines)) = dataset_view {
+        render_dataset_view(frame, app, lines, area.inner(Margin::new(1, 1)));
+        return;
+    }
+
     let key_col_width = 26;
     let table_widths = [Constraint::Min(key_col_width), Constraint::Percentage(100)];
     let data_col_width = std::cmp::max(area.width.saturating_sub(key_col_width + 3), 2); // 3 for borders
 
-    let mut histogram_data: Option<_> = None;
+    let mut plot_data = None;
 
-    let selected = app.tree_state.selected().to_vec();
+    let selected = app.tab().tree_state.selected().to_vec();
     if !selected.is_empty() {
         let info = app.get_text_for(&selected);
-        if let Some((info, hist_data_opt)) = info {
-            histogram_data = hist_data_opt;
+        if let Some((info, plots)) = info {
+            plot_data = Some(plots);
             info.iter().for_each(|(k, v)| {
                 let is_copied_row =
                     if let Some((copied_key, time)) = &app.copied_object_info_indicator {
@@ -301,12 +402,9 @@ fn render_object_info(frame: &mut Frame, app: &mut App, area: Rect) {
                     };
 
                     let val_style = if is_copied_row {
-                        Style::new()
-                            .bg(Color::Rgb(46, 125, 50))
-                            .fg(Color::White)
-                            .add_modifier(Modifier::BOLD)
+                        get_style(Styles::Copied, app.mode).add_modifier(Modifier::BOLD)
                     } else if is_hovered_row {
-                        Style::new().bg(Color::Gray).fg(Color::Black)
+                        get_style(Styles::Hovered, app.mode)
                     } else {
                         get_style(Styles::Magenta, app.mode)
                     };
@@ -321,7 +419,8 @@ fn render_object_info(frame: &mut Frame, app: &mut App, area: Rect) {
         }
     }
 
-    let layout = if let Some(_) = histogram_data {
+    let plot = plot_data.and_then(|plots| Some((app.tab().plot_kind_for(&plots)?, plots)));
+    let layout = if plot.is_some() {
         Layout::vertical([Constraint::Percentage(30), Constraint::Percentage(70)])
     } else {
         Layout::vertical([Constraint::Percentage(100), Constraint::Percentage(0)])
@@ -330,20 +429,24 @@ fn render_object_info(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let table_area = layout[0];
     app.set_last_object_info_table_area(table_area);
-    let hist_area = layout[1];
+    let plot_area = layout[1];
 
     let num_lines_when_rendered: u16 = rows.len().try_into().unwrap();
     let max_scroll_state = num_lines_when_rendered.saturating_sub(table_area.height);
-    app.object_info_scroll_state = app.object_info_scroll_state.clamp(0, max_scroll_state);
+    let object_info_scroll_state = app
+        .tab()
+        .object_info_scroll_state
+        .clamp(0, max_scroll_state);
+    app.tab_mut().object_info_scroll_state = object_info_scroll_state;
 
     let table = Table::new(rows, table_widths);
-    let mut table_scroll = TableState::new().with_offset(app.object_info_scroll_state.into());
+    let mut table_scroll = TableState::new().with_offset(object_info_scroll_state.into());
     frame.render_stateful_widget(table, table_area, &mut table_scroll);
 
     let mut scrollbar_state = ScrollbarState::default()
         .content_length(max_scroll_state.into())
         .viewport_content_length((table_area.height).into())
-        .position(app.object_info_scroll_state.into());
+        .position(object_info_scroll_state.into());
 
     frame.render_stateful_widget(
         Scrollbar::new(ScrollbarOrientation::VerticalRight)
@@ -354,16 +457,246 @@ fn render_object_info(frame: &mut Frame, app: &mut App, area: Rect) {
         &mut scrollbar_state,
     );
 
-    if let Some(hist_data) = histogram_data {
-        let histogram_widget =
-            hist_plot::histogram_widget(&hist_data, hist_area.height, hist_area.width);
-        frame.render_widget(histogram_widget, hist_area);
+    app.last_heatmap_area = Rect::default();
+    if let Some((plot_kind, plots)) = plot {
+        let [title_area, plot_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(plot_area);
+        let settings = app.tab().histogram_settings;
+        let histogram = plots
+            .histogram
+            .as_ref()
+            .filter(|_| plot_kind == PlotKind::Histogram)
+            .map(|source| source.compute(&settings));
+        match plot_kind {
+            PlotKind::Histogram => {
+                if let Some(histogram) = &histogram {
+                    let histogram_widget = hist_plot::histogram_widget(
+                        histogram,
+                        settings.log_counts,
+                        plot_area.height,
+                        plot_area.width,
+                    );
+                    frame.render_widget(histogram_widget, plot_area);
+                }
+            }
+            PlotKind::Line => {
+                if let Some(line_data) = &plots.line {
+                    let line_widget = line_plot::line_plot_widget(
+                        line_data,
+                        app.tab().plot_x_range,
+                        plot_area.height,
+                        plot_area.width,
+                    );
+                    frame.render_widget(line_widget, plot_area);
+                }
+            }
+            PlotKind::Heatmap => {
+                if let Some(heatmap) = &plots.heatmap {
+                    let heatmap_widget = HeatmapWidget {
+                        data: heatmap,
+                        colormap: app.tab().colormap,
+                        label_style: get_style(Styles::DefaultText, app.mode),
+                    };
+                    frame.render_widget(heatmap_widget, plot_area);
+                    app.last_heatmap_area = plot_area;
+                }
+            }
+            PlotKind::Counts => {
+                if let Some(counts) = &plots.counts {
+                    let label_style = get_style(Styles::DefaultText, app.mode);
+                    frame.render_widget(
+                        hist_plot::count_bar_chart(counts, label_style),
+                        plot_area.inner(Margin::new(1, 0)),
+                    );
+                }
+            }
+        }
+        // After the plot, so the heatmap hover uses this frame's area
+        frame.render_widget(
+            plot_title_line(app, plot_kind, &plots, histogram.as_ref()),
+            title_area,
+        );
     }
 }
 
+/// Title and lines of the raw bytes or chunk map of the selected dataset, if one of them is on.
+fn dataset_view_lines(app: &mut App) -> Option<(&'static str, Vec<Line<'static>>)> {
+    let text_style = get_style(Styles::DefaultText, app.mode);
+    let dim_style = text_style.add_modifier(Modifier::DIM);
+    let error_line = |what: &str, e: &String| {
+        vec![Line::from(format!("Could not read the {}: {}", what, e)).style(text_style)]
+    };
+    if let Some(hex_dump) = app.tab_mut().selected_hex_dump() {
+        let lines = match hex_dump.as_ref() {
+            Ok(hex_dump) => hex_dump.lines(&HexStyles {
+                offset: dim_style,
+                elements: [text_style, get_style(Styles::Magenta, app.mode)],
+            }),
+            Err(e) => error_line("raw bytes", e),
+        };
+        return Some(("Raw bytes", lines));
+    }
+    if let Some(chunk_map) = app.tab_mut().selected_chunk_map() {
+        let lines = match chunk_map.as_ref() {
+            Ok(chunk_map) => chunk_map.lines(&ChunkStyles {
+                text: text_style,
+                dim: dim_style,
+                allocated: get_style(Styles::Magenta, app.mode),
+                warning: get_style(Styles::Warning, app.mode),
+            }),
+            Err(e) => error_line("chunks", e),
+        };
+        return Some(("Chunk map", lines));
+    }
+    None
+}
+
+/// Lines shown instead of the object info, scrolled like it.
+fn render_dataset_view(frame: &mut Frame, app: &mut App, lines: Vec<Line<'static>>, area: Rect) {
+    app.set_last_object_info_table_area(area);
+    let num_lines: u16 = lines.len().try_into().unwrap_or(u16::MAX);
+    let scroll = app
+        .tab()
+        .object_info_scroll_state
+        .min(num_lines.saturating_sub(area.height));
+    app.tab_mut().object_info_scroll_state = scroll;
+    frame.render_widget(Paragraph::new(lines).scroll((scroll, 0)), area);
+}
+
+/// Which plot is shown, the visible index range of a line plot and the keys to change them.
+fn plot_title_line(
+    app: &App,
+    plot_kind: PlotKind,
+    plots: &PlotData,
+    histogram: Option<&Histogram>,
+) -> Line<'static> {
+    let keymap = &config::get().keymap;
+    let key_style = get_style(Styles::KeyBinding, app.mode);
+    let text_style = get_style(Styles::DefaultText, app.mode);
+    let mut spans = vec![];
+    match (plot_kind, &plots.line) {
+        (PlotKind::Line, Some(line_data)) => {
+            let range = app.tab().plot_x_range;
+            let len = line_data.len as f64;
+            spans.push(Span::styled(
+                format!(
+                    "Line plot [{}..{}] of {}  ",
+                    num_utils::format_integer_with_separator((range.start * len) as u64),
+                    num_utils::format_integer_with_separator((range.end * len).ceil() as u64),
+                    num_utils::format_integer_with_separator(line_data.len as u64),
+                ),
+                text_style,
+            ));
+            if let Some(scale) = &line_data.x_scale {
+                spans.push(Span::styled(format!("x: {}  ", scale.name), text_style));
+            }
+            for (action, label) in [
+                (Action::ZoomIn, "zoom in"),
+                (Action::ZoomOut, "zoom out"),
+                (Action::PanLeft, "left"),
+                (Action::PanRight, "right"),
+            ] {
+                spans.push(Span::styled(keymap.keys_text(action), key_style));
+                spans.push(Span::styled(format!(" {}  ", label), text_style));
+            }
+        }
+        (PlotKind::Counts, _) => {
+            let num_values: u64 = plots.counts.iter().flatten().map(|(_, count)| count).sum();
+            spans.push(Span::styled(
+                format!(
+                    "Counts of {} values  ",
+                    num_utils::format_integer_with_separator(num_values)
+                ),
+                text_style,
+            ));
+        }
+        (PlotKind::Heatmap, _) => {
+            let Some(heatmap) = &plots.heatmap else {
+                return Line::default();
+            };
+            spans.push(Span::styled(
+                format!(
+                    "Heatmap {} {}×{}  {}  ",
+                    heatmap.slice,
+                    heatmap.shape.0,
+                    heatmap.shape.1,
+                    app.tab().colormap.name()
+                ),
+                text_style,
+            ));
+            let scales = heatmap.scales_text();
+            if !scales.is_empty() {
+                spans.push(Span::styled(scales + "  ", text_style));
+            }
+            let hovered_value = app
+                .hovered_heatmap_position
+                .and_then(|position| heatmap.value_under(app.last_heatmap_area, position));
+            if let Some((position, value)) = hovered_value {
+                spans.push(Span::styled(
+                    format!(
+                        "{} = {}  ",
+                        heatmap.position_text(position),
+                        num_utils::float_fmt(value)
+                    ),
+                    get_style(Styles::Magenta, app.mode),
+                ));
+            }
+            spans.push(Span::styled(
+                keymap.keys_text(Action::CycleColormap),
+                key_style,
+            ));
+            spans.push(Span::styled(" colormap  ", text_style));
+        }
+        _ => {
+            let settings = app.tab().histogram_settings;
+            let mut description = format!("Histogram {} bins", settings.bins);
+            if let Some((p_lo, p_hi)) = settings.clip_percentiles {
+                let num_outside = histogram.map_or(0, |h| h.num_below + h.num_above);
+                description += &format!(
+                    ", clipped to {}-{}% ({} outside)",
+                    p_lo,
+                    p_hi,
+                    num_utils::format_integer_with_separator(num_outside)
+                );
+            }
+            if settings.log_counts {
+                description += ", log counts";
+            }
+            if let Some(source) = plots.histogram.as_ref().filter(|s| s.stride > 1) {
+                description += &format!(", every {}th value", source.stride);
+            }
+            spans.push(Span::styled(description + "  ", text_style));
+            for (action, label) in [
+                (Action::MoreBins, "more"),
+                (Action::FewerBins, "fewer"),
+                (Action::ToggleLogCounts, "log"),
+                (Action::CycleHistogramClip, "clip"),
+                (Action::YankHistogram, "copy"),
+            ] {
+                spans.push(Span::styled(keymap.keys_text(action), key_style));
+                spans.push(Span::styled(format!(" {}  ", label), text_style));
+            }
+        }
+    }
+    if plots.available().len() > 1 {
+        spans.push(Span::styled(keymap.keys_text(Action::CyclePlot), key_style));
+        spans.push(Span::styled(" switch plot  ", text_style));
+    }
+    if app.tab().num_selected_fields() > 1 {
+        spans.push(Span::styled(keymap.keys_text(Action::NextField), key_style));
+        spans.push(Span::styled("/", text_style));
+        spans.push(Span::styled(keymap.keys_text(Action::PrevField), key_style));
+        spans.push(Span::styled(" field", text_style));
+    }
+    Line::from(spans)
+}
+
 fn render_search(frame: &mut Frame, app: &mut App, area: Rect) {
     let search_block = Block::new()
-        .title("Fuzzy search (type '/')")
+        .title(format!(
+            "Fuzzy search (type '{}')",
+            config::get().keymap.keys_text(Action::Search)
+        ))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(if app.mode == SelectionMode::SearchQueryEditing {
@@ -372,60 +705,83 @@ fn render_search(frame: &mut Frame, app: &mut App, area: Rect) {
             get_style(Styles::BorderDefault, app.mode)
         });
 
-    let (search_query_text, mut search_query_cursor_pos) = app.search_query_and_cursor();
+    let tab = app.tab_mut();
+    let (search_query_text, mut search_query_cursor_pos) = tab.search_query_and_cursor();
 
     let view_width = area.width - 2;
     let min_offset = search_query_cursor_pos.saturating_sub(view_width);
     let max_offset = min_offset.max(search_query_cursor_pos.saturating_sub(view_width / 2));
-    app.search_query_view_offset = clamp(app.search_query_view_offset, min_offset, max_offset);
-    search_query_cursor_pos = search_query_cursor_pos.saturating_sub(app.search_query_view_offset);
+    tab.search_query_view_offset = clamp(tab.search_query_view_offset, min_offset, max_offset);
+    search_query_cursor_pos = search_query_cursor_pos.saturating_sub(tab.search_query_view_offset);
     let visible_search_query: String = search_query_text
         .chars()
-        .skip(app.search_query_view_offset.into())
+        .skip(tab.search_query_view_offset.into())
         .take(view_width.into())
         .collect();
 
     let search_query = Paragraph::new(visible_search_query).block(search_block);
     frame.render_widget(search_query, area);
-    match app.mode {
-        SelectionMode::SearchQueryEditing => {
-            frame.set_cursor_position(Position::new(
-                // Draw the cursor at the current position in the input field.
-                // This position is can be controlled via the left and right arrow key
-                area.x + search_query_cursor_pos as u16 + 1,
-                // Move one line down, from the border to the input line
-                area.y + 1,
-            ))
-        }
-        _ => {}
+    if app.mode == SelectionMode::SearchQueryEditing {
+        frame.set_cursor_position(Position::new(
+            // Draw the cursor at the current position in the input field.
+            // This position is can be controlled via the left and right arrow key
+            area.x + search_query_cursor_pos + 1,
+            // Move one line down, from the border to the input line
+            area.y + 1,
+        ))
     }
 }
 
 fn render_tree(frame: &mut Frame, app: &mut App, area: Rect) {
+    let mode = app.mode;
+    let pending_mark = app.pending_mark;
+    let reference_prompt = app.pending_reference.as_ref().map(|paths| {
+        let choices: Vec<String> = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| format!("{} {}", app::reference_key(i), path))
+            .collect();
+        format!("Follow reference: {}", choices.join(", "))
+    });
+    let tab = &mut app.tabs[app.active_tab];
+    let status_line = match pending_mark {
+        Some(PendingMark::Set) => Some("Set mark: press a letter".to_string()),
+        Some(PendingMark::Jump) => Some("Jump to mark: press a letter".to_string()),
+        None if reference_prompt.is_some() => reference_prompt,
+        None => tab
+            .status_message
+            .as_ref()
+            .filter(|(_, time)| time.elapsed().as_secs() < 2)
+            .map(|(message, _)| message.clone()),
+    };
     let mut tree_block = Block::new()
         .title(
-            app.h5_file_path
+            tab.h5_file_path
                 .to_str()
                 .unwrap_or("unknown file")
                 .to_string(),
         )
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(if app.mode == SelectionMode::TreeBrowsing {
-            get_style(Styles::BorderHighlight, app.mode)
+        .border_style(if mode == SelectionMode::TreeBrowsing {
+            get_style(Styles::BorderHighlight, mode)
         } else {
-            get_style(Styles::BorderDefault, app.mode)
+            get_style(Styles::BorderDefault, mode)
         });
+    if let Some(status_line) = status_line {
+        tree_block = tree_block
+            .title_bottom(Line::from(status_line).style(get_style(Styles::DefaultText, mode)));
+    }
 
-    match &app.tree {
-        Some(_) => match &app.filtered_tree {
+    match &tab.tree {
+        Some(_) => match &tab.filtered_tree {
             Some(filtered_tree) => {
-                let mut current_path = vec![filtered_tree.id().clone()];
-                let hovered_path = app.hovered_node.as_deref();
-                let copied_indicator = app.copied_indicator.as_ref();
-                let selected_path = app.tree_state.selected();
-                let filtered_items = [filtered_tree.into_tree_item(
-                    app.mode,
+                let mut current_path = vec![filtered_tree.id()];
+                let hovered_path = tab.hovered_node.as_deref();
+                let copied_indicator = tab.copied_indicator.as_ref();
+                let selected_path = tab.tree_state.selected();
+                let filtered_items = [filtered_tree.to_tree_item(
+                    mode,
                     hovered_path,
                     copied_indicator,
                     selected_path,
@@ -438,14 +794,14 @@ fn render_tree(frame: &mut Frame, app: &mut App, area: Rect) {
                 };
 
                 let highlight_style = if is_selected_copied {
-                    Style::new().bg(Color::Rgb(46, 125, 50)).fg(Color::White)
+                    get_style(Styles::Copied, mode)
                 } else {
-                    get_style(Styles::TreeItemHighlight, app.mode)
+                    get_style(Styles::TreeItemHighlight, mode)
                 };
 
                 let tree_widget = WidgetTreeRoot::new(&filtered_items)
                     .expect("all item identifiers are unique")
-                    .style(get_style(Styles::DefaultText, app.mode))
+                    .style(get_style(Styles::DefaultText, mode))
                     .highlight_style(highlight_style)
                     .block(tree_block)
                     .experimental_scrollbar(Some(
@@ -455,15 +811,15 @@ fn render_tree(frame: &mut Frame, app: &mut App, area: Rect) {
                             .end_symbol(None),
                     ));
 
-                frame.render_stateful_widget(tree_widget, area, &mut app.tree_state);
+                frame.render_stateful_widget(tree_widget, area, &mut tab.tree_state);
             }
             None => {
-                tree_block = tree_block.border_style(get_style(Styles::BorderDefault, app.mode));
+                tree_block = tree_block.border_style(get_style(Styles::BorderDefault, mode));
                 frame.render_widget(
                     Paragraph::new("No matches found")
                         .centered()
                         .block(tree_block)
-                        .style(get_style(Styles::NoMatchesFound, app.mode)),
+                        .style(get_style(Styles::NoMatchesFound, mode)),
                     area,
                 );
             }
@@ -479,11 +835,214 @@ fn render_tree(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 }
 
+fn render_file_browser(frame: &mut Frame, app: &mut App, area: Rect) {
+    let mode = app.mode;
+    let browser = &mut app.file_browser;
+
+    let bottom_line = match &browser.status {
+        Some(status) => Line::from(status.clone()).style(get_style(Styles::Warning, mode)),
+        None => {
+            let keymap = &config::get().keymap;
+            let key_style = get_style(Styles::KeyBinding, mode);
+            let text_style = get_style(Styles::DefaultText, mode).add_modifier(Modifier::DIM);
+            let mut spans = vec![];
+            for (action, label) in [
+                (Action::ToggleNode, "open"),
+                (Action::ParentDir, "parent dir"),
+                (Action::Refresh, "refresh"),
+                (Action::RecentFiles, "recent files"),
+                (Action::Back, "back"),
+            ] {
+                let keys = keymap.keys_text(action);
+                if !keys.is_empty() {
+                    spans.push(Span::styled(keys, key_style));
+                    spans.push(Span::styled(format!(": {}  ", label), text_style));
+                }
+            }
+            Line::from(spans)
+        }
+    };
+
+    let title = if browser.showing_recent_files {
+        "Open recent file".to_string()
+    } else {
+        format!("Open file: {}", browser.dir.to_string_lossy())
+    };
+    let browser_block = Block::new()
+        .title(title)
+        .title_top(help_hint_line())
+        .title_bottom(bottom_line)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(if mode == SelectionMode::FileBrowsing {
+            get_style(Styles::BorderHighlight, mode)
+        } else {
+            get_style(Styles::BorderDefault, mode)
+        });
+
+    let rows: Vec<Row> = browser
+        .entries
+        .iter()
+        .map(|entry| {
+            let (name, size) = match entry.kind {
+                BrowserEntryKind::ParentDir | BrowserEntryKind::Dir => {
+                    (format!("{}/", entry.name), String::new())
+                }
+                BrowserEntryKind::Hdf5File => {
+                    (entry.name.clone(), num_utils::file_size_fmt(entry.size))
+                }
+            };
+            let name_style = match entry.kind {
+                BrowserEntryKind::Hdf5File => get_style(Styles::DefaultText, mode),
+                _ => get_style(Styles::Magenta, mode),
+            };
+            Row::new([
+                Cell::from(Text::from(name).style(name_style)),
+                Cell::from(Text::from(size).right_aligned()),
+                Cell::from(entry.modified_text()),
+            ])
+        })
+        .collect();
+
+    let is_empty = rows.is_empty();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(100),
+            Constraint::Min(12),
+            Constraint::Min(19),
+        ],
+    )
+    .header(
+        Row::new(["Name", "Size", "Modified"])
+            .style(get_style(Styles::DefaultText, mode).add_modifier(Modifier::BOLD)),
+    )
+    .style(get_style(Styles::DefaultText, mode))
+    .row_highlight_style(get_style(Styles::TreeItemHighlight, mode))
+    .block(browser_block);
+
+    frame.render_stateful_widget(table, area, &mut browser.table_state);
+
+    let inner = area.inner(Margin::new(1, 1));
+    if is_empty {
+        frame.render_widget(
+            Paragraph::new(if browser.showing_recent_files {
+                "No recent files"
+            } else {
+                "No HDF5 files or directories found"
+            })
+            .centered(),
+            Rect::new(inner.x, inner.y + 1, inner.width, 1).intersection(inner),
+        );
+    }
+    // The first line inside the border is the header
+    app.last_file_browser_table_area = Rect::new(
+        inner.x,
+        inner.y.saturating_add(1),
+        inner.width,
+        inner.height.saturating_sub(1),
+    );
+}
+
+fn render_command_palette(frame: &mut Frame, app: &mut App) {
+    if app.mode != SelectionMode::CommandPalette {
+        app.last_command_palette_table_area = Rect::default();
+        return;
+    }
+    // Drawn on top of the dimmed layout, so use the undimmed styles
+    let mode = SelectionMode::TreeBrowsing;
+    let keymap = &config::get().keymap;
+    let palette = &mut app.command_palette;
+    let entries = palette.entries();
+
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).min(70);
+    let height = area
+        .height
+        .saturating_sub(4)
+        .min(entries.len().max(1) as u16 + 4);
+    let [popup_area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(Rect::new(area.x, area.y + 2, area.width, height));
+
+    let block = Block::new()
+        .title("Command palette")
+        .title_bottom(
+            Line::from("Enter: run  Esc: close")
+                .style(get_style(Styles::DefaultText, mode).add_modifier(Modifier::DIM)),
+        )
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(get_style(Styles::BorderHighlight, mode));
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(&block, popup_area);
+
+    let [query_area, table_area] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)])
+        .areas(block.inner(popup_area));
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::from("> ").style(get_style(Styles::Magenta, mode)),
+            Span::from(palette.query.clone()).style(get_style(Styles::DefaultText, mode)),
+        ])),
+        query_area,
+    );
+    frame.set_cursor_position(Position::new(
+        query_area.x + 2 + palette.query.chars().count() as u16,
+        query_area.y,
+    ));
+
+    let rows: Vec<Row> = entries
+        .iter()
+        .map(|entry| {
+            let description = Line::from(
+                entry
+                    .action
+                    .description()
+                    .chars()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        if entry.matching_indices.contains(&i) {
+                            Span::styled(c.to_string(), get_style(Styles::SearchCharMatch, mode))
+                        } else {
+                            Span::raw(c.to_string())
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            Row::new([
+                Cell::from(description),
+                Cell::from(
+                    Text::from(keymap.keys_text(entry.action))
+                        .style(get_style(Styles::KeyBinding, mode))
+                        .right_aligned(),
+                ),
+            ])
+        })
+        .collect();
+
+    if rows.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No matching commands")
+                .style(get_style(Styles::DefaultText, mode).add_modifier(Modifier::DIM)),
+            table_area,
+        );
+    }
+
+    let table = Table::new(rows, [Constraint::Percentage(100), Constraint::Min(16)])
+        .style(get_style(Styles::DefaultText, mode))
+        .row_highlight_style(get_style(Styles::TreeItemHighlight, mode));
+    frame.render_stateful_widget(table, table_area, &mut palette.table_state);
+    app.last_command_palette_table_area = table_area;
+}
+
 fn render_logger(frame: &mut Frame, app: &App, area: Rect) {
     let logger_widget = tui_logger::TuiLoggerWidget::default()
         .block(
             Block::bordered()
-                .title("Logs (toggle with 'L')")
+                .title(format!(
+                    "Logs (toggle with '{}')",
+                    config::get().keymap.keys_text(Action::ToggleLogs)
+                ))
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
                 .border_style(get_style(Styles::LogBorder, app.mode)),
@@ -498,6 +1057,11 @@ fn render_logger(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(logger_widget, area);
 }
 
+fn help_hint_line() -> Line<'static> {
+    let keys = config::get().keymap.keys_text(Action::ShowHelp);
+    Line::from(format!("Help screen (type '{}')", keys)).right_aligned()
+}
+
 fn get_help_screen_area(area: Rect) -> Rect {
     let vertical = Layout::vertical([Constraint::Length(33)]).flex(Flex::Center);
     let horizontal = Layout::horizontal([Constraint::Length(110)]).flex(Flex::Center);
@@ -506,12 +1070,45 @@ fn get_help_screen_area(area: Rect) -> Rect {
     area
 }
 
+/// The description padded to the key column, with `$H5INSPECT_POST` highlighted.
+fn help_description_spans(description: &'static str, text_style: Style) -> Vec<Span<'static>> {
+    const KEY_COLUMN: usize = 40;
+    const POST_VAR: &str = "$H5INSPECT_POST";
+    let padding = " ".repeat(KEY_COLUMN.saturating_sub(description.chars().count() + 1));
+    match description.split_once(POST_VAR) {
+        Some((before, after)) => vec![
+            Span::from(before).style(text_style),
+            Span::from(POST_VAR).style(get_style(Styles::Code, SelectionMode::TreeBrowsing)),
+            Span::from(format!("{}:{}", after, padding)).style(text_style),
+        ],
+        None => vec![Span::from(format!("{}:{}", description, padding)).style(text_style)],
+    }
+}
+
+fn recent_files_lines(app: &App, title_style: Style, text_style: Style) -> Vec<Line<'static>> {
+    let recent_files = &app.session_store.recent_files;
+    if recent_files.is_empty() {
+        return vec![];
+    }
+    let mut lines = vec![
+        Line::from(""),
+        Line::from("Recent files").style(title_style).centered(),
+    ];
+    lines.extend(
+        recent_files
+            .iter()
+            .take(10)
+            .map(|path| Line::from(path.to_string_lossy().to_string()).style(text_style)),
+    );
+    lines
+}
+
 fn render_help_screen(frame: &mut Frame, app: &mut App, area: Rect) {
     let help_block = Block::new()
         .title("Help")
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+        .border_style(get_style(Styles::HelpTitle, SelectionMode::TreeBrowsing));
 
     frame.render_widget(Clear, area);
     frame.render_widget(help_block, area);
@@ -523,16 +1120,17 @@ fn render_help_screen(frame: &mut Frame, app: &mut App, area: Rect) {
     ])
     .areas(area.inner(Margin::new(1, 0)));
 
-    const KEY_BINDING_TITLE_STYLE: Style =
-        Style::new().fg(Color::White).add_modifier(Modifier::BOLD);
-    const KEY_BINDING_STYLE: Style = Style::new().fg(Color::Yellow);
-    const DEFAULT_TEXT_STYLE: Style = Style::new().fg(Color::White);
+    // The help screen is drawn on top of everything else so it is never dimmed
+    let key_binding_title_style =
+        get_style(Styles::DefaultText, SelectionMode::TreeBrowsing).add_modifier(Modifier::BOLD);
+    let key_binding_style = get_style(Styles::KeyBinding, SelectionMode::TreeBrowsing);
+    let default_text_style = get_style(Styles::DefaultText, SelectionMode::TreeBrowsing);
 
     let big_text = BigText::builder()
         .pixel_size(PixelSize::Full)
         .lines(vec![Line::styled(
             "h5inspect",
-            Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+            get_style(Styles::HelpTitle, SelectionMode::TreeBrowsing),
         )])
         .centered()
         .build();
@@ -540,7 +1138,7 @@ fn render_help_screen(frame: &mut Frame, app: &mut App, area: Rect) {
         .pixel_size(PixelSize::Quadrant)
         .lines(vec![Line::styled(
             format!("v{}", env!("CARGO_PKG_VERSION")),
-            Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+            get_style(Styles::HelpTitle, SelectionMode::TreeBrowsing),
         )])
         .right_aligned()
         .build();
@@ -559,7 +1157,8 @@ fn render_help_screen(frame: &mut Frame, app: &mut App, area: Rect) {
 
     big_text_version.render(version_area, &mut buf);
 
-    // Convert buffer contents to lines
+    // Convert buffer contents to lines, shading the title colour in a moving wave
+    let (r, g, b) = config::get().theme.help_title;
     let mut big_text_as_lines: Vec<Line> = Vec::new();
     for row in big_text_temp_render_area.rows() {
         let mut line: Vec<Span> = Vec::new();
@@ -571,81 +1170,56 @@ fn render_help_screen(frame: &mut Frame, app: &mut App, area: Rect) {
             let col: f32 = (col.x as f32 - col.y as f32 + app.animation_state as f32)
                 .mul(0.3)
                 .sin();
-            let color = ((col * 0.3 + 1.0) * 255.0).min(255.0) as u8;
-            let color = Color::Rgb(color, 0, 0);
+            let shade = (col * 0.3 + 1.0).min(1.0);
+            let color = Color::Rgb(
+                (r as f32 * shade) as u8,
+                (g as f32 * shade) as u8,
+                (b as f32 * shade) as u8,
+            );
             line.push(Span::raw(span).style(Style::default().fg(color)));
         }
         big_text_as_lines.push(Line::from(line));
     }
 
+    let keymap = &config::get().keymap;
+    let close_keys = [Action::Back, Action::Quit, Action::ShowHelp]
+        .into_iter()
+        .map(|action| keymap.keys_text(action))
+        .filter(|keys| !keys.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+
     let help_lines: Vec<Line> = big_text_as_lines
         .into_iter()
         .chain(vec![
             Line::from(vec![
                 Span::raw("A terminal based HDF5 file inspector. Press "),
-                Span::raw("Esc/q/?").style(KEY_BINDING_STYLE),
+                Span::raw(close_keys).style(key_binding_style),
                 Span::raw(" to close this help screen."),
             ]),
             Line::from(""),
             Line::from("Key bindings")
-                .style(KEY_BINDING_TITLE_STYLE)
+                .style(key_binding_title_style)
                 .centered(),
-            Line::from(vec![
-                Span::from("Navigate:                               ").style(DEFAULT_TEXT_STYLE),
-                Span::from("←,↑,→,↓,h,j,k,l,").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("                                        ").style(DEFAULT_TEXT_STYLE),
-                Span::from("Home,End,PageUp,PageDown,").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("                                        ").style(DEFAULT_TEXT_STYLE),
-                Span::from("click,scroll").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("Close/open group:                       ").style(DEFAULT_TEXT_STYLE),
-                Span::from("Enter/c").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("Go to top of tree:                      ").style(DEFAULT_TEXT_STYLE),
-                Span::from("g").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("Go to bottom of tree:                   ").style(DEFAULT_TEXT_STYLE),
-                Span::from("G").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("Fuzzy search:                           ").style(DEFAULT_TEXT_STYLE),
-                Span::from("/").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("Help screen:                            ").style(DEFAULT_TEXT_STYLE),
-                Span::from("?").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("Debug logs:                             ").style(DEFAULT_TEXT_STYLE),
-                Span::from("L").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("Quit:                                   ").style(DEFAULT_TEXT_STYLE),
-                Span::from("q/Ctrl+c").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("Yank (copy) node path:                  ").style(DEFAULT_TEXT_STYLE),
-                Span::from("y/double-click").style(KEY_BINDING_STYLE),
-            ]),
-            Line::from(vec![
-                Span::from("Run ").style(DEFAULT_TEXT_STYLE),
-                Span::from("$H5INSPECT_POST").style(
-                    Style::new()
-                        .fg(ratatui::style::palette::material::BLUE_GRAY.c200)
-                        .bg(ratatui::style::palette::material::GRAY.c800)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::from(":                    ").style(DEFAULT_TEXT_STYLE),
-                Span::from("i").style(KEY_BINDING_STYLE),
-            ]),
         ])
+        .chain(Action::ALL.into_iter().map(|action| {
+            let keys = [
+                keymap.keys_text(action),
+                action.mouse_hint().unwrap_or("").to_string(),
+            ]
+            .into_iter()
+            .filter(|k| !k.is_empty())
+            .collect::<Vec<_>>()
+            .join(",");
+            let mut spans = help_description_spans(action.description(), default_text_style);
+            spans.push(Span::from(keys).style(key_binding_style));
+            Line::from(spans)
+        }))
+        .chain(recent_files_lines(
+            app,
+            key_binding_title_style,
+            default_text_style,
+        ))
         .collect();
 
     let mut help_paragraph = Paragraph::new(help_lines).wrap(Wrap { trim: false });